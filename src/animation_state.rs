@@ -1,4 +1,6 @@
-use std::time::Instant;
+use std::{path::PathBuf, time::Instant};
+
+use crate::config::config_file::KeybindSlot;
 
 //Holds various information on the various animations
 pub struct AnimationState {
@@ -8,6 +10,9 @@ pub struct AnimationState {
     pub selected_item_gamemode_selector: i32,
     pub selected_item_singleplayer_selector: i32,
     pub selected_item_marathon_prompt: (usize, usize),
+    pub selected_item_sprint_prompt: (usize, usize),
+    pub selected_item_ultra_prompt: (usize, usize),
+    pub selected_item_dig_prompt: ([usize; 2], usize),
     pub selected_item_reset_selector: i32,
     pub selected_item_bot_selector: i32,
 
@@ -24,6 +29,15 @@ pub struct AnimationState {
     pub selected_item_settings: (usize, usize),
     pub edit_setting_value: bool,
     pub last_setting_tick: Option<Instant>,
+
+    pub selected_item_keybinds: usize,
+    pub keybind_slot: KeybindSlot,
+    pub waiting_for_key: bool,
+    pub waiting_since: Instant,
+    pub keybind_message: String,
+
+    pub selected_item_replay_browser: usize,
+    pub replay_list: Vec<(PathBuf, String)>, // (path, label)
 }
 
 impl AnimationState {
@@ -34,17 +48,26 @@ impl AnimationState {
             velocity: 0.,
 
             // Arrow Main Menu
-            selected_item_main_menu: 0, // (0 - START GAME)....(1 - SETTINGS)
+            selected_item_main_menu: 0, // (0 - START GAME)....(1 - HIGH SCORES) .....(2 - REPLAYS) .....(3 - SETTINGS)
 
             // Arrow GameMode Selector
             selected_item_gamemode_selector: 0, // (0 - 1v1)....(1 - singleplayer) .....(2 - vs bots)
 
             // Arrow Singleplayer Selector
-            selected_item_singleplayer_selector: 0, // (0 - Marathon)....(1 - 40L) .....(2 - Survival)
+            selected_item_singleplayer_selector: 0, // (0 - Marathon)....(1 - Sprint) .....(2 - Survival) .....(3 - Ultra) .....(4 - Dig) .....(5 - Master) .....(6 - PC Training)
 
             // Arrow(s) Marathon Prompt
             selected_item_marathon_prompt: (1, 0), // ((n, 0) - Start Level) ... ((n, 1) - Confirm)
 
+            // Arrow(s) Sprint Prompt
+            selected_item_sprint_prompt: (1, 0), // ((n, 0) - Line goal, index in SPRINT_LINE_GOALS) ... ((n, 1) - Confirm)
+
+            // Arrow(s) Ultra Prompt
+            selected_item_ultra_prompt: (0, 0), // ((n, 0) - Time limit, index in ULTRA_TIME_LIMITS) ... ((n, 1) - Confirm)
+
+            // Arrow(s) Dig Prompt
+            selected_item_dig_prompt: ([0, 0], 0), // (([goal, messiness], 0) - Line goal, index in DIG_LINE_GOALS) ... ((n, 1) - Messiness) ... ((n, 2) - Confirm)
+
             // Arrow Reset Selector
             selected_item_reset_selector: 0, // (0 - Reset) ... (1 - Main Menu)
 
@@ -69,6 +92,17 @@ impl AnimationState {
             selected_item_settings: (0, 0),
             edit_setting_value: false,
             last_setting_tick: None,
+
+            // Keybinds
+            selected_item_keybinds: 0, // (0 - Layout) ... (1-8 - Actions) ... (9 - Restore defaults) ... (10 - Back)
+            keybind_slot: KeybindSlot::Solo,
+            waiting_for_key: false,
+            waiting_since: Instant::now(),
+            keybind_message: String::new(),
+
+            // Replay Browser
+            selected_item_replay_browser: 0, // Index in replay_list, replay_list.len() is BACK
+            replay_list: Vec::new(),
         }
     }
 }
@@ -1,11 +1,13 @@
-use std::time::Instant;
 use crate::consts::GameMode;
 use crate::gamemodes::versus::versus::get_attack_value;
 use crate::scoring::ScoreType;
 use crate::Game;
 use crate::Piece;
+use crate::PieceType;
 pub use crate::consts::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS};
 
+pub type Board = [[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+
 impl Game {
     pub fn is_valid_position(&mut self, dx: isize, dy: isize) -> bool {
         let piece = &self.active_piece;
@@ -26,7 +28,7 @@ impl Game {
         })
     }
 
-    pub fn is_solid_tile(&mut self, r: isize, c: isize) -> bool {
+    pub fn is_solid_tile(&self, r: isize, c: isize) -> bool {
         if c >= BOARD_AMOUNT_COLUMNS as isize
         || c < 0
         || r < 0
@@ -50,6 +52,7 @@ impl Game {
     }
 
     pub fn place_piece(&mut self) -> bool {
+        self.last_placed = Some((self.active_piece.clone(), self.t_spin, self.t_spin_mini));
         let piece = &self.active_piece;
         let (mr, mc) = piece.midpoint;
         piece.block_positions.iter().for_each(|(dr, dc)| {
@@ -77,8 +80,16 @@ impl Game {
 
         self.pieces += 1;
 
-        self.spawn_piece_from_queue();
-        self.last_drop = Instant::now();
+        // The line clear delay only comes after clears
+        let mut delay = self.are;
+        if self.line_clear.is_some() {
+            delay += self.line_clear_delay;
+        }
+        if delay.is_zero() {
+            self.spawn_next_piece();
+        } else {
+            self.spawn_at = Some(self.clock.now() + delay);
+        }
 
         self.lowest_row = 21;
         self.action_count = 0;
@@ -122,6 +133,9 @@ impl Game {
         let lines_cleared = rows_to_remove.len();
 
         if !rows_to_remove.is_empty() {
+            if !self.line_clear_delay.is_zero() {
+                self.line_clear = Some((self.clock.now(), rows_to_remove.clone(), self.board));
+            }
             rows_to_remove.reverse();
 
             //MOVE DOWN THE ROWS ABOVE
@@ -136,42 +150,14 @@ impl Game {
 
         // Check gamemode specific conditions
         match self.gamemode {
-            GameMode::Marathon => {
-                if self.lines / 10 == self.level {
-                    self.level_up();
-                }
-            },
-            GameMode::FourtyLines => {
-                if self.lines >= 40 {
-                    self.end_game(true);
-                }
-            },
+            GameMode::Marathon if self.lines / 10 == self.level => self.level_up(),
+            GameMode::Sprint => self.update_sprint(),
+            GameMode::Dig => self.update_dig(),
+            GameMode::Master => self.update_master(lines_cleared),
             _ => (),
         }
 
 
-        match lines_cleared {
-            0 => {
-                if self.t_spin_mini {Some(ScoreType::TSpinMini)}
-                else if self.t_spin {Some(ScoreType::TSpin)}
-                else {None}
-            },
-            1 => {
-                if self.t_spin_mini {Some(ScoreType::TSpinMiniSingle)}
-                else if self.t_spin {Some(ScoreType::TSpinSingle)}
-                else {Some(ScoreType::Single)}
-            },
-            2 => {
-                if self.t_spin {Some(ScoreType::TspinDouble)}
-                else {Some(ScoreType::Double)}
-            },
-            3 => {
-                if self.t_spin {Some(ScoreType::TSpinTriple)}
-                else {Some(ScoreType::Triple)}
-            },
-            _ => {
-                Some(ScoreType::Tetris)
-            }
-        }
+        ScoreType::from_clear(lines_cleared, self.t_spin, self.t_spin_mini)
     }
 }
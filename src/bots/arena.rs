@@ -0,0 +1,215 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+
+use super::{
+    bot::Bot,
+    difficulty::BOT_DIFFICULTIES,
+    train_bot::parse_value,
+    versus_training::VersusStats,
+    weights::{Weights, BOT_WEIGHTS_PATH},
+};
+use crate::{clock::Clock, consts::GameMode};
+
+// Simulated time between bot updates, about one frame at 60 FPS
+const FRAME_TIME: Duration = Duration::from_millis(16);
+
+// One side of the arena
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArenaBot {
+    pub weights: Option<PathBuf>, // The weights in res/bot_weights.json if None
+    pub difficulty: usize, // Index into BOT_DIFFICULTIES, sets the speed and mistakes
+    pub search_depth: Option<usize>, // Overrides the depth of the difficulty
+}
+
+impl Default for ArenaBot {
+    fn default() -> Self {
+        Self {
+            weights: None,
+            difficulty: BOT_DIFFICULTIES.len() - 1,
+            search_depth: None,
+        }
+    }
+}
+
+impl ArenaBot {
+    pub fn create(&self, seed: u64) -> Bot {
+        let mut bot = Bot::new(self.difficulty, seed);
+        if let Some(path) = &self.weights {
+            bot.weights = Weights::load_from(path);
+        }
+        if let Some(depth) = self.search_depth {
+            bot.search_depth = depth;
+        }
+        bot.game.clock = Clock::manual();
+        bot.game.gamemode = GameMode::Versus;
+        bot.game.reset_game(Some(seed));
+        bot
+    }
+
+    pub fn get_description(&self) -> String {
+        let weights = match &self.weights {
+            Some(path) => path.display().to_string(),
+            None => BOT_WEIGHTS_PATH.to_string(),
+        };
+        let depth = self.search_depth.unwrap_or(BOT_DIFFICULTIES[self.difficulty].search_depth);
+        format!("{}, depth {}, {}", BOT_DIFFICULTIES[self.difficulty].name, depth, weights)
+    }
+}
+
+// Settings of an arena run, read from the arguments after --arena
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArenaConfig {
+    pub bots: [ArenaBot; 2],
+    pub matches: usize,
+    pub seed: u64, // Match i is played with seed + i
+    pub max_time: Duration, // Matches nobody has lost by then are draws
+    pub threads: usize,
+}
+
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        Self {
+            bots: [ArenaBot::default(), ArenaBot::default()],
+            matches: 20,
+            seed: 0,
+            max_time: Duration::from_secs(180),
+            threads: 4,
+        }
+    }
+}
+
+impl ArenaConfig {
+    // Same format as TrainConfig::from_args. Options of a bot start with --one- or --two-
+    pub fn from_args(args: &[String]) -> Result<ArenaConfig, String> {
+        let mut config = ArenaConfig::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or(format!("Missing value for {}", arg))?;
+
+            if let Some((side, option)) = arg
+                .strip_prefix("--one-")
+                .map(|o| (0, o))
+                .or(arg.strip_prefix("--two-").map(|o| (1, o)))
+            {
+                let bot = &mut config.bots[side];
+                match option {
+                    "weights" => bot.weights = Some(PathBuf::from(value)),
+                    "difficulty" => bot.difficulty = parse_difficulty(value)?,
+                    "depth" => bot.search_depth = Some(parse_value(arg, value)?),
+                    _ => return Err(format!("Unknown arena option {}", arg)),
+                }
+                continue;
+            }
+
+            match arg.as_str() {
+                "--matches" => config.matches = parse_value(arg, value)?,
+                "--seed" => config.seed = parse_value(arg, value)?,
+                "--max-time" => config.max_time = Duration::from_secs(parse_value(arg, value)?),
+                "--threads" => config.threads = parse_value(arg, value)?,
+                _ => return Err(format!("Unknown arena option {}", arg)),
+            }
+        }
+
+        if config.matches == 0 {
+            return Err("The arena needs at least 1 match".to_string());
+        }
+        if config.bots.iter().any(|bot| bot.search_depth == Some(0)) {
+            return Err("The search depth has to be at least 1".to_string());
+        }
+        Ok(config)
+    }
+}
+
+// A difficulty name like "hard", or its index
+fn parse_difficulty(value: &str) -> Result<usize, String> {
+    BOT_DIFFICULTIES
+        .iter()
+        .position(|d| d.name.eq_ignore_ascii_case(value))
+        .or(value.parse().ok().filter(|i| *i < BOT_DIFFICULTIES.len()))
+        .ok_or(format!(
+            "Unknown difficulty {}, use one of {}",
+            value,
+            BOT_DIFFICULTIES.map(|d| d.name.to_lowercase()).join(", ")
+        ))
+}
+
+/*
+    Plays one match like ScreenState::VsBots, only with a bot on both
+    sides and a simulated clock. Both bots get the same pieces and play at
+    the speed of their difficulty, with garbage exchanged every frame.
+*/
+pub fn play_arena_match(config: &ArenaConfig, seed: u64) -> [VersusStats; 2] {
+    let mut bots = config.bots.clone().map(|bot| bot.create(seed));
+
+    loop {
+        for bot in bots.iter_mut() {
+            bot.game.clock.advance(FRAME_TIME);
+            bot.game.clock.catch_up();
+        }
+
+        let [one, two] = &mut bots;
+        one.observe_opponent(&two.game);
+        two.observe_opponent(&one.game);
+        one.update_bot_game();
+        two.update_bot_game();
+        one.game.send_outbound_garbage(&mut two.game);
+        two.game.send_outbound_garbage(&mut one.game);
+
+        let started = one.game.countdown_start.is_none();
+        let time = one.game.clock.elapsed(one.game.start_time);
+        if bots.iter().any(|bot| bot.game.game_over) || (started && time >= config.max_time) {
+            break;
+        }
+    }
+
+    VersusStats::from_games([&bots[0].game, &bots[1].game], 0)
+}
+
+// Plays every match in parallel and sums up the stats of each side
+pub fn run_arena(config: &ArenaConfig) -> [VersusStats; 2] {
+    // Fails if the pool was already built, which is fine
+    let _ = ThreadPoolBuilder::new()
+        .num_threads(config.threads)
+        .build_global();
+
+    (0..config.matches as u64)
+        .into_par_iter()
+        .map(|i| play_arena_match(config, config.seed.wrapping_add(i)))
+        .reduce(
+            || [VersusStats::default(), VersusStats::default()],
+            |mut total, result| {
+                total[0].add(&result[0]);
+                total[1].add(&result[1]);
+                total
+            },
+        )
+}
+
+pub fn print_arena_report(config: &ArenaConfig, stats: &[VersusStats; 2]) {
+    let last_seed = config.seed.wrapping_add(config.matches as u64 - 1);
+    println!("{} matches, seeds {} to {}", config.matches, config.seed, last_seed);
+    for (name, (bot, stats)) in ["Bot one", "Bot two"].iter().zip(config.bots.iter().zip(stats)) {
+        let (low, high) = stats.get_win_rate_interval();
+        println!("{} ({})", name, bot.get_description());
+        println!(
+            "    win rate {:.1}% (95% CI {:.1}% to {:.1}%), {:.1} APM, {:.2} PPS",
+            stats.get_win_rate() * 100.,
+            low * 100.,
+            high * 100.,
+            stats.get_apm(),
+            stats.get_pps(),
+        );
+    }
+
+    let matches = stats[0].matches.max(1) as f64;
+    println!(
+        "Draws: {}, average game length {:.1} s and {:.0} pieces per bot",
+        stats[0].draws,
+        stats[0].time.as_secs_f64() / matches,
+        (stats[0].pieces + stats[1].pieces) as f64 / 2. / matches,
+    );
+}
@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+
+use rayon::prelude::*;
+
+use super::{
+    bitboard::BitBoard,
+    board_evaluation::ClearState,
+    bot::Bot,
+    bot_input::BotInput,
+    move_generator::{find_placements_on, Placement},
+    move_outcome::MoveOutcome,
+};
+use crate::{consts::NEXT_QUEUE_SIZE, Game, Piece, PieceType};
+
+// A game state reached after placing one or more pieces
+#[derive(Clone)]
+pub struct BeamNode {
+    pub game: Game,
+    pub first_moves: Vec<BotInput>, // Inputs for the first placement on the way here
+    pub lines_cleared: usize, // Lines cleared since the search started
+    pub attack: usize, // Garbage sent since the search started
+}
+
+// A placement that hasn't been turned into a BeamNode yet. Only the kept ones ever are,
+// since copying a Game is a lot slower than copying a BitBoard
+struct Candidate<'a> {
+    parent: &'a BeamNode,
+    held: bool,
+    placement: Placement,
+    key: (BitBoard, Option<PieceType>), // Board after line clears and the held piece
+    lines_cleared: usize,
+    attack: usize,
+    evaluation: f64,
+}
+
+impl Candidate<'_> {
+    fn into_node(self) -> BeamNode {
+        let mut game = self.parent.game.clone();
+        if self.held {
+            game.hold();
+        }
+        self.placement.apply(&mut game);
+
+        let mut first_moves = self.parent.first_moves.clone();
+        if first_moves.is_empty() {
+            if self.held {
+                first_moves.push(BotInput::Hold);
+            }
+            first_moves.extend(self.placement.move_sequence);
+        }
+
+        BeamNode {
+            game,
+            first_moves,
+            lines_cleared: self.lines_cleared,
+            attack: self.attack,
+        }
+    }
+}
+
+impl Bot {
+    /*
+        Plans several pieces ahead using the piece queue and hold.
+        Every depth places one more piece in each kept state, the children
+        are evaluated in parallel and only the `beam_width` best are kept.
+        Returns the inputs for the first placement of the best final state.
+    */
+    pub fn get_beam_search_move_sequence(&self) -> Vec<BotInput> {
+        // Never look further than the visible queue. Holding into an empty hold slot uses up one more piece
+        let visible = NEXT_QUEUE_SIZE + 1 - self.game.held_piece.is_none() as usize;
+        let depth = self.search_depth.min(visible);
+
+        let root = BeamNode {
+            game: self.game.clone(),
+            first_moves: Vec::new(),
+            lines_cleared: 0,
+            attack: 0,
+        };
+        let mut beam = vec![root];
+
+        for _ in 0..depth {
+            let mut children: Vec<Candidate> = beam
+                .par_iter()
+                .flat_map_iter(|node| self.expand_node(node))
+                .collect();
+
+            children.sort_by(|a, b| b.evaluation.total_cmp(&a.evaluation));
+
+            // Different placement orders often end up in the same state
+            let mut seen = HashSet::new();
+            children.retain(|child| seen.insert(child.key));
+            children.truncate(self.beam_width.max(1));
+
+            let next_beam: Vec<BeamNode> = children
+                .into_par_iter()
+                .map(|child| child.into_node())
+                .filter(|node| !node.game.game_over)
+                .collect();
+            if next_beam.is_empty() {
+                break;
+            }
+            beam = next_beam;
+        }
+
+        beam.into_iter().next().map(|node| node.first_moves).unwrap_or_default()
+    }
+
+    // Every placement of the active piece, and of the hold piece if holding is allowed
+    fn expand_node<'a>(&self, node: &'a BeamNode) -> Vec<Candidate<'a>> {
+        let game = &node.game;
+        let bitboard = BitBoard::from_board(&game.board);
+        let clear_state = ClearState::from_game(game);
+
+        let mut pieces = vec![(false, game.active_piece.clone(), game.t_spin, game.t_spin_mini)];
+        if game.can_hold {
+            if let Some(hold_type) = game.held_piece.or(game.piece_queue.front().copied()) {
+                let hold_piece = Piece::new(hold_type, 0);
+                // Holding into a blocked spawn ends the game
+                if bitboard.fits(&hold_piece.block_positions, hold_piece.midpoint) {
+                    pieces.push((true, hold_piece, false, false));
+                }
+            }
+        }
+
+        let mut candidates = Vec::new();
+        for (held, piece, t_spin, t_spin_mini) in pieces {
+            let held_piece = if held {Some(game.active_piece.piece_type)} else {game.held_piece};
+
+            for placement in find_placements_on(&bitboard, &piece, t_spin, t_spin_mini) {
+                let mut board = placement.get_resulting_bitboard(&bitboard);
+                let mut outcome = MoveOutcome::from_board(&mut board, placement.spin, &mut clear_state.clone());
+
+                // Lines and attack count for the whole path, not only the latest piece
+                let lines_cleared = node.lines_cleared + outcome.lines_cleared as usize;
+                let attack = node.attack + outcome.attack as usize;
+                outcome.lines_cleared = lines_cleared as f32;
+                outcome.attack = attack as f32;
+
+                candidates.push(Candidate {
+                    parent: node,
+                    held,
+                    evaluation: self.weights.evaluate(&outcome),
+                    key: (board, held_piece),
+                    placement,
+                    lines_cleared,
+                    attack,
+                });
+            }
+        }
+        candidates
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS},
+    PieceType,
+};
+
+pub const FULL_ROW: u16 = (1 << BOARD_AMOUNT_COLUMNS) - 1;
+
+/*
+    Compact copy of a board where every row is a u16 and bit c is set if
+    column c is filled. Copying one is a lot cheaper than copying the board
+    of a Game, so the bots search and evaluate on these instead.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BitBoard {
+    pub rows: [u16; BOARD_AMOUNT_ROWS],
+}
+
+impl BitBoard {
+    pub fn from_board(board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS]) -> Self {
+        let mut bitboard = BitBoard { rows: [0; BOARD_AMOUNT_ROWS] };
+        for (row, cells) in bitboard.rows.iter_mut().zip(board.iter()) {
+            for (c, cell) in cells.iter().enumerate() {
+                if cell.is_some() {
+                    *row |= 1 << c;
+                }
+            }
+        }
+        bitboard
+    }
+
+    // Tiles outside of the board count as solid
+    pub fn is_solid(&self, r: isize, c: isize) -> bool {
+        if c >= BOARD_AMOUNT_COLUMNS as isize
+            || c < 0
+            || r < 0
+            || r >= BOARD_AMOUNT_ROWS as isize
+        {
+            return true;
+        }
+
+        self.rows[r as usize] & (1 << c) != 0
+    }
+
+    // Whether a piece with these minos fits with its midpoint at `midpoint`
+    pub fn fits(&self, minos: &[(isize, isize)], midpoint: (isize, isize)) -> bool {
+        let (mr, mc) = midpoint;
+        minos.iter().all(|(dr, dc)| !self.is_solid(mr + dr, mc + dc))
+    }
+
+    pub fn place(&mut self, minos: &[(isize, isize)], midpoint: (isize, isize)) {
+        let (mr, mc) = midpoint;
+        for (dr, dc) in minos {
+            self.rows[(mr + dr) as usize] |= 1 << (mc + dc);
+        }
+    }
+
+    pub fn count_full_lines(&self) -> usize {
+        self.rows.iter().filter(|row| **row == FULL_ROW).count()
+    }
+
+    // Removes full rows and moves the rows above down. Returns the amount of cleared lines
+    pub fn clear_lines(&mut self) -> usize {
+        let mut kept = 0;
+        for r in 0..BOARD_AMOUNT_ROWS {
+            if self.rows[r] != FULL_ROW {
+                self.rows[kept] = self.rows[r];
+                kept += 1;
+            }
+        }
+        self.rows[kept..].fill(0);
+        BOARD_AMOUNT_ROWS - kept
+    }
+
+    pub fn get_column_heights(&self) -> [usize; BOARD_AMOUNT_COLUMNS] {
+        let mut heights = [0; BOARD_AMOUNT_COLUMNS];
+        let mut found: u16 = 0;
+        for r in (0..BOARD_AMOUNT_ROWS).rev() {
+            let new = self.rows[r] & !found;
+            if new == 0 {
+                continue;
+            }
+            for (c, height) in heights.iter_mut().enumerate() {
+                if new & (1 << c) != 0 {
+                    *height = r + 1;
+                }
+            }
+            found |= new;
+            if found == FULL_ROW {
+                break;
+            }
+        }
+        heights
+    }
+}
@@ -1,57 +1,224 @@
+use super::{
+    bitboard::{BitBoard, FULL_ROW},
+    move_outcome::{MoveOutcome, SpinType},
+};
 use crate::{
     board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS},
+    gamemodes::versus::versus::get_attack_value,
+    scoring::ScoreType,
     Game, PieceType,
 };
 
-impl Game {
-    pub fn get_aggregate_height(board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS]) -> f32 {
-        (0..BOARD_AMOUNT_COLUMNS)
-            .map(|c| column_height(board, c) as f32)
-            .sum()
+impl BitBoard {
+    pub fn get_aggregate_height(&self) -> f32 {
+        self.get_column_heights().iter().sum::<usize>() as f32
+    }
+
+    // Empty tiles with a filled tile somewhere above them
+    pub fn count_holes(&self) -> f32 {
+        let mut holes = 0;
+        let mut covered: u16 = 0;
+        for row in self.rows.iter().rev() {
+            holes += (covered & !row & FULL_ROW).count_ones();
+            covered |= row;
+        }
+        holes as f32
     }
-    
 
-    pub fn count_holes(
-        board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS],
-    ) -> f32 {
-        let mut holes: f32 = 0.;
+    pub fn count_bumpiness(&self) -> f32 {
+        self.get_column_heights()
+            .windows(2)
+            .map(|w| (w[0] as i32 - w[1] as i32).abs() as f32)
+            .sum()
+    }
 
-        for c in 0..BOARD_AMOUNT_COLUMNS {
-            let mut found_block = false;
-            for r in (0..BOARD_AMOUNT_ROWS).rev() {
-                if board[r][c].is_some() {
-                    found_block = true;
-                } else if found_block {
-                    holes += 1.0;
+    pub fn compute_well_depth(&self) -> i32 {
+        let mut total_depth = 0;
+        for col in 0..BOARD_AMOUNT_COLUMNS as isize {
+            let mut depth = 0;
+            for row in 0..BOARD_AMOUNT_ROWS as isize {
+                if !self.is_solid(row, col) && self.is_solid(row, col - 1) && self.is_solid(row, col + 1) {
+                    depth += 1;
+                    total_depth += depth;
+                } else {
+                    depth = 0;
                 }
             }
         }
+        total_depth
+    }
 
-        holes
+    pub fn get_max_height(&self) -> f32 {
+        self.get_column_heights().into_iter().max().unwrap_or(0) as f32
     }
 
-    pub fn count_bumpiness(board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS]) -> f32 {
-        let heights: Vec<usize> = (0..BOARD_AMOUNT_COLUMNS)
-            .map(|c| column_height(board, c))
-            .collect();
-    
-        heights
-            .windows(2)
-            .map(|w| (w[0] as i32 - w[1] as i32).abs() as f32)
-            .sum()
+    // Changes between filled and empty next to each other in a row, the walls count as filled
+    pub fn count_row_transitions(&self) -> f32 {
+        let max_height = self.get_max_height() as usize;
+        let transitions: u32 = self.rows[..max_height]
+            .iter()
+            .map(|row| {
+                let walled = (*row as u32) << 1 | 1 | 1 << (BOARD_AMOUNT_COLUMNS + 1);
+                ((walled ^ (walled >> 1)) & ((1 << (BOARD_AMOUNT_COLUMNS + 1)) - 1)).count_ones()
+            })
+            .sum();
+        transitions as f32
+    }
+
+    // Changes between filled and empty on top of each other in a column, the floor counts as filled
+    pub fn count_column_transitions(&self) -> f32 {
+        let mut below = FULL_ROW;
+        let mut transitions = 0;
+        for row in self.rows {
+            transitions += (row ^ below).count_ones();
+            below = row;
+        }
+        transitions as f32
+    }
+
+    // Filled cells that have a hole somewhere below them
+    pub fn count_covered_cells(&self) -> f32 {
+        let mut holes = [0u16; BOARD_AMOUNT_ROWS];
+        let mut covered: u16 = 0;
+        for r in (0..BOARD_AMOUNT_ROWS).rev() {
+            holes[r] = covered & !self.rows[r] & FULL_ROW;
+            covered |= self.rows[r];
+        }
+
+        let mut hole_below: u16 = 0;
+        let mut covered_cells = 0;
+        for (row, row_holes) in self.rows.iter().zip(holes) {
+            covered_cells += (row & hole_below).count_ones();
+            hole_below |= row_holes;
+        }
+        covered_cells as f32
+    }
+
+    /*
+        Spots where a T piece pointing down would fill an empty cell and
+        the three cells above it, with both lower corners filled and at
+        least one of the upper corners filled as an overhang.
+    */
+    pub fn count_t_slots(&self) -> f32 {
+        let max_height = self.get_max_height() as isize;
+        let mut slots = 0;
+        for r in 1..=max_height {
+            for c in 1..BOARD_AMOUNT_COLUMNS as isize - 1 {
+                let empty = [(r, c - 1), (r, c), (r, c + 1), (r - 1, c), (r + 1, c)]
+                    .iter()
+                    .all(|(r, c)| !self.is_solid(*r, *c));
+                if empty
+                    && self.is_solid(r - 1, c - 1)
+                    && self.is_solid(r - 1, c + 1)
+                    && (self.is_solid(r + 1, c - 1) || self.is_solid(r + 1, c + 1))
+                {
+                    slots += 1;
+                }
+            }
+        }
+        slots as f32
+    }
+}
+
+// The parts of a game that decide how much a line clear sends
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClearState {
+    pub back_to_back: bool,
+    pub latest_clear_difficult: bool,
+    pub combo: usize,
+    pub prev_clear: bool,
+}
+
+impl ClearState {
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            back_to_back: game.back_to_back,
+            latest_clear_difficult: game.latest_clear_difficult,
+            combo: game.combo,
+            prev_clear: game.prev_clear,
+        }
     }
-    
 
-    pub fn count_lines_cleared(
-        board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS],
-    ) -> f32 {
-        let mut lines: usize = 0;
-        for row in 0..BOARD_AMOUNT_ROWS {
-            if board[row].iter().all(|b| b.is_some()) {
-                lines += 1;
+    // Follows the combo and back to back rules of Game::add_score. Returns the attack of the clear
+    pub fn apply(&mut self, score_type: &Option<ScoreType>, all_clear: bool) -> usize {
+        let Some(s) = score_type else {
+            self.prev_clear = false;
+            self.combo = 0;
+            return 0;
+        };
+
+        match s {
+            ScoreType::TSpinMini | ScoreType::TSpin => {
+                self.prev_clear = false;
+                self.combo = 0;
+                return 0;
+            }
+            _ => {
+                if self.prev_clear {
+                    self.combo += 1;
+                }
+                self.prev_clear = true;
+            }
+        }
+
+        match s {
+            ScoreType::Single | ScoreType::Double | ScoreType::Triple => {
+                self.latest_clear_difficult = false;
+                self.back_to_back = false;
             }
+            _ => {
+                if self.latest_clear_difficult {self.back_to_back = true}
+                self.latest_clear_difficult = true;
+            }
+        }
+
+        let attack = get_attack_value(score_type, self.back_to_back, self.combo);
+        if all_clear {attack + 10} else {attack}
+    }
+}
+
+impl MoveOutcome {
+    // Evaluates a board where a piece was just locked. Clears the lines of `board` and updates `clear_state`
+    pub fn from_board(board: &mut BitBoard, spin: SpinType, clear_state: &mut ClearState) -> Self {
+        let lines_cleared = board.clear_lines();
+        let score_type = ScoreType::from_clear(lines_cleared, spin != SpinType::None, spin == SpinType::TSpinMini);
+        let attack = clear_state.apply(&score_type, lines_cleared > 0 && board.rows[0] == 0);
+
+        MoveOutcome {
+            move_sequence: Vec::new(),
+            lines_cleared: lines_cleared as f32,
+            aggregate_height: board.get_aggregate_height(),
+            holes: board.count_holes(),
+            bumpiness: board.count_bumpiness(),
+            row_transitions: board.count_row_transitions(),
+            column_transitions: board.count_column_transitions(),
+            well_depth: board.compute_well_depth() as f32,
+            covered_cells: board.count_covered_cells(),
+            max_height: board.get_max_height(),
+            t_slots: board.count_t_slots(),
+            back_to_back: if clear_state.back_to_back {1.} else {0.},
+            combo: clear_state.combo as f32,
+            attack: attack as f32,
         }
-        lines as f32
+    }
+}
+
+// Board versions of the evaluation functions, these convert the board to a BitBoard first
+impl Game {
+    pub fn get_aggregate_height(board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS]) -> f32 {
+        BitBoard::from_board(board).get_aggregate_height()
+    }
+
+    pub fn count_holes(board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS]) -> f32 {
+        BitBoard::from_board(board).count_holes()
+    }
+
+    pub fn count_bumpiness(board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS]) -> f32 {
+        BitBoard::from_board(board).count_bumpiness()
+    }
+
+    pub fn count_lines_cleared(board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS]) -> f32 {
+        BitBoard::from_board(board).count_full_lines() as f32
     }
 
     pub fn simulate_place_piece(&mut self) -> bool {
@@ -65,32 +232,6 @@ impl Game {
     }
 
     pub fn compute_well_depth(board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS]) -> i32 {
-        let mut total_depth = 0;
-        for col in 0..BOARD_AMOUNT_COLUMNS {
-            let mut depth = 0;
-            for row in 0..BOARD_AMOUNT_ROWS {
-                let cell = board[row][col];
-                let left = if col == 0 { Some(PieceType::I) } else { board[row][col - 1] };
-                let right = if col == BOARD_AMOUNT_COLUMNS - 1 { Some(PieceType::I) } else { board[row][col + 1] };
-    
-                if cell.is_none() && left.is_some() && right.is_some() {
-                    depth += 1;
-                    total_depth += depth;
-                } else {
-                    depth = 0;
-                }
-            }
-        }
-        total_depth
+        BitBoard::from_board(board).compute_well_depth()
     }
-    
 }
-
-pub fn column_height(board: &[[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS], col: usize) -> usize {
-    for row in (0..BOARD_AMOUNT_ROWS).rev() {
-        if board[row][col].is_some() {
-            return row + 1;
-        }
-    }
-    0
-}
\ No newline at end of file
@@ -1,16 +1,21 @@
-use std::{
-    collections::{HashSet, VecDeque}, time::{Duration, Instant}
-};
-
 use ggez::Context;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 use super::{
+    bitboard::BitBoard,
+    board_evaluation::ClearState,
     bot_input::BotInput,
-    move_outcome::{MoveOutcome, MovementState},
+    difficulty::{DifficultyProfile, BOT_DIFFICULTIES, PERFECT_PROFILE},
+    move_generator::find_placements,
+    move_outcome::MoveOutcome,
+    pc_solver::PcSolver,
+    strategy::Strategy,
+    tbp_client::TbpClient,
+    weights::{Weights, ALL_FEATURES},
 };
 use crate::{
-    board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS}, consts::{BOT_DIFFICULTY_SPEEDS, GAME_1_SOLO_POS, GAME_1_SOLO_SCL}, Game, PieceType, ROTATION_CCW, ROTATION_CW
+    clock::SimInstant, game_inputs::InputFrame, consts::{BOT_BEAM_WIDTH, BOT_MISTAKE_POOL, BOT_PC_MAX_NODES, GAME_1_SOLO_POS, GAME_1_SOLO_SCL}, Game
 };
 
 #[derive(Clone)]
@@ -18,9 +23,17 @@ pub struct Bot {
     pub game: Game,
     pub inputs: Vec<BotInput>,
     pub fitness: f64,
-    pub weights: [f64; 4],
+    pub weights: Weights,
     pub game_steps: i32,
-    pub difficulty: usize, // ranges for 0 - 2
+    pub difficulty: usize, // Index into BOT_DIFFICULTIES
+    pub profile: DifficultyProfile,
+    pub search_depth: usize, // Pieces planned ahead, 1 only looks at the active piece and hold
+    pub beam_width: usize, // Placements kept between each depth of the search
+    pub mistake_rng: StdRng,
+    pub next_input_time: SimInstant, // Earliest time for the next input
+    pub last_placement: SimInstant,
+    pub strategy: Strategy,
+    pub opponent_height: Option<usize>, // Max height of the opponent's stack in versus
 }
 
 impl Bot {
@@ -33,80 +46,125 @@ impl Bot {
             game: g,
             inputs: Vec::new(),
             fitness: 0.,
-            /*
-                aggregate height 
-                lines cleared
-                holes 
-                bumpiness
-            */
-            weights: [
-                -0.610066,
-                0.760666,
-                -0.35663,
-                -0.184483
-            ],
+            weights: Weights::load(),
             game_steps: 0,
             difficulty,
+            profile: BOT_DIFFICULTIES[difficulty],
+            search_depth: BOT_DIFFICULTIES[difficulty].search_depth,
+            beam_width: BOT_BEAM_WIDTH,
+            mistake_rng: StdRng::seed_from_u64(id),
+            next_input_time: SimInstant::default(),
+            last_placement: SimInstant::default(),
+            strategy: Strategy::BuildAttack,
+            opponent_height: None,
         }
     }
 
-    pub fn with_random_unit_weights() -> Self {
-        let mut w = Bot::random_weights();
-        Self::normalize(&mut w);
+    pub fn set_difficulty(&mut self, difficulty: usize) {
+        self.difficulty = difficulty;
+        self.profile = BOT_DIFFICULTIES[difficulty];
+        self.search_depth = self.profile.search_depth;
+    }
+
+    // Bot used for training, only looks at the active piece and hold
+    pub fn with_weights(weights: Weights) -> Self {
         Self {
-            weights: w,
+            weights,
             fitness: 0.0,
             game: Game::new(GAME_1_SOLO_POS, GAME_1_SOLO_SCL, 0),
             inputs: vec![],
             game_steps: 0,
-            difficulty: 0
+            difficulty: 0,
+            profile: PERFECT_PROFILE,
+            search_depth: PERFECT_PROFILE.search_depth,
+            beam_width: BOT_BEAM_WIDTH,
+            mistake_rng: StdRng::seed_from_u64(0),
+            next_input_time: SimInstant::default(),
+            last_placement: SimInstant::default(),
+            strategy: Strategy::BuildAttack,
+            opponent_height: None,
         }
     }
 
-    fn normalize(w: &mut [f64; 4]) {
-        let norm = w.iter().map(|x| x * x).sum::<f64>().sqrt();
-        for i in 0..4 {
-            w[i] /= norm;
-        }
+    pub fn with_random_unit_weights(rng: &mut impl Rng) -> Self {
+        let mut w = Weights::random(rng);
+        w.normalize();
+        Self::with_weights(w)
     }
 
-    pub fn random_crossover(p1: &Bot, p2: &Bot) -> Bot {
-        let mut rng = rand::rng();
-        let mut w = [0.0; 4];
-        for i in 0..4 {
-            w[i] = if rng.random_bool(0.5) {
-                p1.weights[i]
+    pub fn random_crossover(p1: &Bot, p2: &Bot, rng: &mut impl Rng) -> Bot {
+        let mut w = Weights::default();
+        for feature in ALL_FEATURES {
+            w.set(feature, if rng.random_bool(0.5) {
+                p1.weights.get(feature)
             } else {
-                p2.weights[i]
-            };
+                p2.weights.get(feature)
+            });
         }
-        Self::normalize(&mut w);
-        Self {
-            weights: w,
-            fitness: 0.0,
-            game: Game::new(GAME_1_SOLO_POS, GAME_1_SOLO_SCL,0),
-            inputs: vec![],
-            game_steps: 0,
-            difficulty: 0,
+        w.normalize();
+        Self::with_weights(w)
+    }
+
+    // The best move sequence for the current strategy, except when the profile makes the bot slip up
+    pub fn get_next_move_sequence(&mut self) -> Vec<BotInput> {
+        if !self.profile.uses_strategy {
+            return self.get_move_sequence_with_mistakes();
+        }
+
+        // Nothing the weights find beats a perfect clear
+        let solver = PcSolver { max_nodes: BOT_PC_MAX_NODES, ..Default::default() };
+        if let Some(steps) = solver.solve(&self.game) {
+            self.strategy = Strategy::PerfectClear;
+            return steps[0].get_move_sequence();
         }
+
+        self.strategy = Strategy::choose(&self.game, self.opponent_height);
+        let trained_weights = self.weights.clone();
+        self.weights = self.strategy.adjust(&trained_weights);
+        let moves = self.get_move_sequence_with_mistakes();
+        self.weights = trained_weights;
+        moves
     }
 
-    pub fn get_best_move_sequence(&mut self) -> Vec<BotInput> {
-        let mut bot_clone = self.clone(); 
+    fn get_move_sequence_with_mistakes(&mut self) -> Vec<BotInput> {
+        if self.profile.mistake_chance > 0. && self.mistake_rng.random_bool(self.profile.mistake_chance) {
+            if let Some(moves) = self.get_mistake_move_sequence() {
+                return moves;
+            }
+        }
+        self.get_best_move_sequence()
+    }
 
-        let held_piece = bot_clone.game.active_piece.piece_type;
+    pub fn observe_opponent(&mut self, opponent: &Game) {
+        self.opponent_height = Some(BitBoard::from_board(&opponent.board).get_max_height() as usize);
+    }
 
-        if let Some(current_held) = self.game.held_piece {
-            bot_clone.game.spawn_piece(current_held);
-        } else {
-            bot_clone.game.spawn_piece_from_queue();
+    // One of the placements right after the best one. Plausible enough to look like a human mistake
+    pub fn get_mistake_move_sequence(&mut self) -> Option<Vec<BotInput>> {
+        let mut outcomes: Vec<(f64, MoveOutcome)> = self
+            .get_all_move_outcomes()
+            .into_iter()
+            .map(|outcome| (self.weights.evaluate(&outcome), outcome))
+            .collect();
+        if outcomes.len() < 2 {
+            return None;
         }
 
-        bot_clone.game.held_piece = Some(held_piece);
-        bot_clone.game.can_hold = false;
+        outcomes.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let i = self.mistake_rng.random_range(1..outcomes.len().min(BOT_MISTAKE_POOL + 1));
+        Some(outcomes.swap_remove(i).1.move_sequence)
+    }
+
+    pub fn get_best_move_sequence(&mut self) -> Vec<BotInput> {
+        if self.search_depth > 1 {
+            return self.get_beam_search_move_sequence();
+        }
+
+        let mut bot_clone = self.clone(); 
+        bot_clone.game.hold();
         
-        let alternative_outcomes = bot_clone.get_all_move_outcomes_by_horizontal_drop(); 
-        let all_outcomes = self.get_all_move_outcomes_by_horizontal_drop();
+        let alternative_outcomes = bot_clone.get_all_move_outcomes(); 
+        let all_outcomes = self.get_all_move_outcomes();
 
         let active_piece_evaluation = self.evaluate_move_outcomes(all_outcomes);
         let alternative_piece_evalution = bot_clone.evaluate_move_outcomes(alternative_outcomes);
@@ -125,14 +183,8 @@ impl Bot {
     pub fn evaluate_move_outcomes(&self, outcomes: Vec<MoveOutcome>) -> (MoveOutcome, f64) {
         let mut evaluation: Vec<f64> = Vec::new();
 
-        let weights = self.weights;
         for outcome in &outcomes {
-            evaluation.push(
-                outcome.aggregate_height as f64 * weights[0]
-                    + outcome.lines_cleared as f64 * weights[1]
-                    + outcome.holes as f64 * weights[2]
-                    + outcome.bumpiness as f64 * weights[3],
-            );
+            evaluation.push(self.weights.evaluate(outcome));
             
             // println!(
             //     "lines: {}, height: {}, holes: {}, bump: {}, score: {}",
@@ -153,148 +205,28 @@ impl Bot {
         (outcomes[best_index].clone(), evaluation[best_index])
     }
 
-    // LEGACY FUNCTION wasnt able to use this!
-    #[allow(unused)]
+    // Every placement of the active piece, with the board stats right after it is locked
     pub fn get_all_move_outcomes(&mut self) -> Vec<MoveOutcome> {
-        let mut final_states: Vec<MoveOutcome> = Vec::new();
-        let mut visited: HashSet<((isize, isize), usize)> = HashSet::new();
-        let mut queue: VecDeque<MovementState> = VecDeque::new();
-        let mut visited_board_states: HashSet<
-            [[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS],
-        > = HashSet::new();
-        let moves: Vec<(i32, i32)> = vec![(-1, 0), (0, -1), (0, 1)]; // down, left, right
-
-        let start_state = MovementState {
-            game: self.game.clone(),
-            moves_so_far: Vec::new(),
-            rotations: 0,
-        };
-
-        queue.push_back(start_state.clone());
-        let p = start_state.game.active_piece;
-        visited.insert(((p.midpoint.0, p.midpoint.1), p.rotation));
-
-        while let Some(mut current_state) = queue.pop_front() {
-            //Move Right Left and Down
-            for (dr, dc) in &moves {
-                let mut game_clone = current_state.game.clone();
-
-                //If its possible to move the piece in the direction
-                if game_clone.move_piece(*dc as isize, *dr as isize) {
-                    let mut new_moves = current_state.moves_so_far.clone();
-                    new_moves.push(match (dr, dc) {
-                        (-1, 0) => BotInput::MoveDown,
-                        (0, -1) => BotInput::MoveLeft,
-                        (0, 1) => BotInput::MoveRight,
-                        _ => unreachable!(),
-                    });
-
-                    let new_state = MovementState {
-                        game: game_clone,
-                        moves_so_far: new_moves,
-                        rotations: 0,
-                    };
-
-                    let p = &new_state.game.active_piece;
-                    if !visited.contains(&((p.midpoint.0, p.midpoint.1), p.rotation)) {
-                        visited.insert(((p.midpoint.0, p.midpoint.1), p.rotation));
-                        queue.push_back(new_state.clone());
-                        // These Piece types only have 2 "types" of rotations
-                        if new_state.game.active_piece.piece_type == PieceType::Z
-                            || new_state.game.active_piece.piece_type == PieceType::S
-                            || new_state.game.active_piece.piece_type == PieceType::I
-                        {
-                            visited.insert((
-                                (new_state.game.active_piece.midpoint),
-                                (new_state.game.active_piece.rotation + 2) % 4,
-                            ));
-                        }
-                    }
-                } else {
-                    //If the Piece cant move down the current state is a final state
-                    if *dr == -1 {
-                        current_state.game.simulate_place_piece();
-
-                        // Only add this board state if we haven't seen it before
-                        if !visited_board_states.contains(&current_state.game.board) {
-                            visited_board_states.insert(current_state.game.board.clone());
-
-                            let mut moves = current_state.moves_so_far.clone();
-
-                            moves.push(BotInput::HardDrop);
-                            final_states.push(MoveOutcome {
-                                lines_cleared: Game::count_lines_cleared(&current_state.game.board),
-                                aggregate_height: Game::get_aggregate_height(
-                                    &current_state.game.board,
-                                ),
-                                holes: Game::count_holes(&current_state.game.board),
-                                bumpiness: Game::count_bumpiness(&current_state.game.board),
-                                move_sequence: moves,
-                            });
-                        }
-                    }
-                }
-            }
-
-            // Rotations
-            if current_state.game.active_piece.piece_type != PieceType::O
-                && current_state.rotations < 5
-            {
-                let rotations = vec![
-                    (ROTATION_CW, BotInput::RotateCW),
-                    (ROTATION_CCW, BotInput::RotateCCW),
-                ];
-
-                {
-                    for (r, b) in rotations {
-                        let mut game_clone = current_state.game.clone();
-                        if game_clone.rotate(r) {
-                            let mut new_moves = current_state.moves_so_far.clone();
-                            new_moves.push(b);
-
-                            let new_state = MovementState {
-                                game: game_clone,
-                                moves_so_far: new_moves,
-                                rotations: current_state.rotations + 1,
-                            };
-
-                            let p = &new_state.game.active_piece;
-                            if !visited.contains(&((p.midpoint.0, p.midpoint.1), p.rotation)) {
-                                visited.insert(((p.midpoint.0, p.midpoint.1), p.rotation));
-                                queue.push_back(new_state.clone());
-
-                                // These Piece types only have 2 "types" of rotations
-                                if new_state.game.active_piece.piece_type == PieceType::Z
-                                    || new_state.game.active_piece.piece_type == PieceType::S
-                                    || new_state.game.active_piece.piece_type == PieceType::I
-                                {
-                                    visited.insert((
-                                        (new_state.game.active_piece.midpoint),
-                                        (new_state.game.active_piece.rotation + 2) % 4,
-                                    ));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        final_states
-    }
-
-    pub fn random_weights() -> [f64; 4] {
-        let mut rng = rand::rng();
-        [(); 4].map(|_| rng.random::<f64>() * if rng.random_bool(0.5) { -1. } else { 1.0 })
+        let bitboard = BitBoard::from_board(&self.game.board);
+        let clear_state = ClearState::from_game(&self.game);
+        find_placements(&self.game)
+            .into_iter()
+            .map(|placement| {
+                let mut board = placement.get_resulting_bitboard(&bitboard);
+                let mut outcome = MoveOutcome::from_board(&mut board, placement.spin, &mut clear_state.clone());
+                outcome.move_sequence = placement.move_sequence;
+                outcome
+            })
+            .collect()
     }
 
-    pub fn mutate(&mut self, mutation_rate: f64) {
-        let mut rng = rand::rng();
-        for i in 0..4 {
+    pub fn mutate(&mut self, mutation_rate: f64, rng: &mut impl Rng) {
+        for w in self.weights.values.iter_mut() {
             if rng.random_bool(mutation_rate) {
-                self.weights[i] += rng.random_range(-0.2..0.2);
+                *w += rng.random_range(-0.2..0.2);
             }
         }
-        Self::normalize(&mut self.weights);
+        self.weights.normalize();
     }
 
     pub fn compute_fitness(&self) -> f64 {
@@ -302,69 +234,87 @@ impl Bot {
     }
 
     pub fn render_bot_game(&mut self, _ctx: &mut Context) {
+        self.game.clock.catch_up();
+        self.update_bot_game();
+    }
+
+    // Same as render_bot_game, but the moves come from an external TBP bot
+    pub fn render_tbp_bot_game(&mut self, _ctx: &mut Context, client: &mut TbpClient) {
+        self.game.clock.catch_up();
+        self.update_bot_game_with(|bot| client.poll_move_sequence(&bot.game));
+    }
+
+    // Plays the bot at the speed of its difficulty profile, up to the current time of the game clock
+    pub fn update_bot_game(&mut self) {
+        self.update_bot_game_with(|bot| Some(bot.get_next_move_sequence()));
+    }
+
+    // Plays the moves from `next_moves` at the speed of the profile. None means the next moves aren't ready yet
+    pub fn update_bot_game_with(&mut self, mut next_moves: impl FnMut(&mut Bot) -> Option<Vec<BotInput>>) {
 
         if let Some(start) = self.game.countdown_start{
-            let elapsed = start.elapsed(); 
+            let elapsed = self.game.clock.elapsed(start); 
             if elapsed >= self.game.countdown_duration {
                 self.game.countdown_start = None
             }
-            self.game.last_drop = Instant::now();
-            self.game.start_time = Instant::now();
+            self.game.last_drop = self.game.clock.now();
+            self.game.start_time = self.game.clock.now();
+            self.next_input_time = self.game.clock.now();
+            self.last_placement = self.game.clock.now();
+            return;
+        }
+        if self.game.game_over {
             return;
         }
-        if self.inputs.is_empty() {
-            self.inputs = self.get_best_move_sequence();
 
-            if self.inputs.is_empty() {
-                return;
-            }
+        self.game.update_garbage();
+        let now = self.game.clock.now();
 
+        // Thinks about every new piece before moving it
+        if self.inputs.is_empty() {
+            let Some(moves) = next_moves(self) else {
+                return;
+            };
+            self.inputs = moves;
             self.inputs.reverse();
+            self.next_input_time = now + self.profile.reaction_delay;
         }
 
-        if self.game.game_over {
-            return;
-        }   
-        
-        self.game.update_garbage();
-
-        if self.game.last_drop.elapsed() >= Duration::from_millis((1000. / BOT_DIFFICULTY_SPEEDS[self.difficulty]) as u64) {
-            self.game.last_drop += Duration::from_millis((1000. / 120.) as u64);
+        while now >= self.next_input_time {
+            let Some(input) = self.inputs.last().copied() else {
+                break;
+            };
+            if input == BotInput::HardDrop {
+                // Waits to not go above the PPS cap
+                if now < self.last_placement + self.profile.get_piece_interval() {
+                    break;
+                }
+                self.last_placement = now;
+            }
 
-            if let Some(input) = self.inputs.pop() {
-                match input {
-                    BotInput::MoveLeft => {
-                        self.game.move_piece(-1, 0);
-                    }
-                    BotInput::MoveRight => {
-                        self.game.move_piece(1, 0);
-                    }
-                    BotInput::RotateCCW => {
-                        self.game.rotate(ROTATION_CCW);
-                    }
-                    BotInput::RotateCW => {
-                        self.game.rotate(ROTATION_CW);
-                    }
-                    BotInput::MoveDown => {
-                        self.game.move_piece(0, -1);
-                        self.game.score += 1;
-                    }
-                    BotInput::HardDrop => {
-                        while self.game.move_piece(0, -1) {}
-                        self.game.score += 2;
-                        self.game.place_piece();
-                    }
-                    BotInput::Hold => {
-                        let held_piece = self.game.active_piece.piece_type;
+            self.inputs.pop();
+            self.apply_input(input);
+            self.next_input_time += self.profile.get_input_interval();
 
-                        if let Some(current_held) = self.game.held_piece {
-                            self.game.spawn_piece(current_held);
-                        } else {
-                            self.game.spawn_piece_from_queue();
-                        }
+            if input == BotInput::HardDrop {
+                break;
+            }
+        }
+    }
 
-                        self.game.held_piece = Some(held_piece);
-                        self.game.can_hold = false;
+    // Feeds a single bot input to the game as a tap of the matching game action
+    pub fn apply_input(&mut self, input: BotInput) {
+        match input.get_game_action() {
+            Some(action) => {
+                let mut inputs = InputFrame::new();
+                inputs.tap(action);
+                self.game.handle_game_inputs(&inputs);
+            }
+            None => {
+                while self.game.move_piece(0, -1) {
+                    self.game.score += 1;
+                    if input == BotInput::MoveDown {
+                        break;
                     }
                 }
             }
@@ -385,93 +335,11 @@ impl Bot {
 
             // IF THE TICK COUNT MATCHES THE CURRENT LEVELS TICK COUNT
             while let Some(input) = self.inputs.pop() {
-                match input {
-                    BotInput::MoveLeft => {
-                        self.game.move_piece(-1, 0);
-                    }
-                    BotInput::MoveRight => {
-                        self.game.move_piece(1, 0);
-                    }
-                    BotInput::RotateCCW => {
-                        self.game.rotate(ROTATION_CCW);
-                    }
-                    BotInput::RotateCW => {
-                        self.game.rotate(ROTATION_CW);
-                    }
-                    BotInput::MoveDown => {
-                        self.game.move_piece(0, -1);
-                        self.game.score += 1;
-                    }
-                    BotInput::HardDrop => {
-                        while self.game.move_piece(0, -1) {}
-                        self.game.score += 2;
-                        self.game.place_piece();
-                    }
-                    BotInput::Hold => {
-                        let held_piece = self.game.active_piece.piece_type;
-
-                        if let Some(current_held) = self.game.held_piece {
-                            self.game.spawn_piece(current_held);
-                        } else {
-                            self.game.spawn_piece_from_queue();
-                        }
-
-                        self.game.held_piece = Some(held_piece);
-                        self.game.can_hold = false;
-                    }
-                }
+                self.apply_input(input);
             }
         }
 
         self.fitness = self.compute_fitness();
         self.game.lines as f64
     }
-
-    pub fn get_all_move_outcomes_by_horizontal_drop(&mut self) -> Vec<MoveOutcome> {
-        let mut move_outcomes: Vec<MoveOutcome> = Vec::new();
-    
-        for rotation in 0..4 {
-            let mut base_game = self.game.clone();
-            let mut base_moves: Vec<BotInput> = Vec::new();
-    
-            for _ in 0..rotation {
-                if base_game.rotate(ROTATION_CW) {
-                    base_moves.push(BotInput::RotateCW);
-                }
-            }
-    
-            // Move piece all the way to the left
-            while base_game.move_piece(-1, 0) {
-                base_moves.push(BotInput::MoveLeft);
-            }
-    
-            loop {
-                let mut game_cc = base_game.clone();
-                let mut moves = base_moves.clone();
-    
-                // Drop the piece all the way down
-                while game_cc.move_piece(0, -1) {}
-                moves.push(BotInput::HardDrop);            
-                game_cc.place_piece();
-    
-                move_outcomes.push(MoveOutcome {
-                    lines_cleared: Game::count_lines_cleared(&game_cc.board),
-                    aggregate_height: Game::get_aggregate_height(&game_cc.board),
-                    holes: Game::count_holes(&game_cc.board),
-                    bumpiness: Game::count_bumpiness(&game_cc.board),
-                    move_sequence: moves,
-                });
-    
-                // Try moving the base piece one column to the right
-                if !base_game.move_piece(1, 0) {
-                    break;
-                }
-    
-                base_moves.push(BotInput::MoveRight);
-            }
-        }
-    
-        move_outcomes
-    }
-    
 }
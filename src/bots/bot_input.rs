@@ -1,11 +1,31 @@
+use crate::GameAction;
+
 #[derive(Clone, Debug, PartialEq, Copy)]
 #[allow(unused)]
 pub enum BotInput {
     MoveLeft,
     MoveRight,
     MoveDown,
+    SoftDrop, // Soft drops all the way down
     RotateCW,
     RotateCCW,
+    Rotate180,
     HardDrop,
     Hold
-}
\ No newline at end of file
+}
+
+impl BotInput {
+    // The game action that performs this input. MoveDown and SoftDrop have no matching action
+    pub fn get_game_action(&self) -> Option<GameAction> {
+        match self {
+            BotInput::MoveLeft => Some(GameAction::MoveLeft),
+            BotInput::MoveRight => Some(GameAction::MoveRight),
+            BotInput::MoveDown | BotInput::SoftDrop => None,
+            BotInput::RotateCW => Some(GameAction::RotateCw),
+            BotInput::RotateCCW => Some(GameAction::RotateCcw),
+            BotInput::Rotate180 => Some(GameAction::Rotate180),
+            BotInput::HardDrop => Some(GameAction::HardDrop),
+            BotInput::Hold => Some(GameAction::Hold),
+        }
+    }
+}
@@ -1,8 +1,32 @@
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
     use crate::{
+        clock::Clock,
         board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS},
-        bots::bot::Bot,
+        bots::{
+            bitboard::BitBoard,
+            board_evaluation::ClearState,
+            bot::Bot,
+            bot_input::BotInput,
+            move_generator::find_placements,
+            move_outcome::{MoveOutcome, SpinType},
+            cma_es::symmetric_eigen,
+            optimizer::OptimizerType,
+            train_bot::{train_ai, Checkpoint, FitnessType, TrainConfig},
+            versus_training::{play_match, VersusStats},
+            arena::{play_arena_match, ArenaBot, ArenaConfig},
+            coach::Coach,
+            pc_solver::PcSolver,
+            difficulty::BOT_DIFFICULTIES,
+            strategy::Strategy,
+            weights::{Feature, Weights, ALL_FEATURES},
+        },
+        consts::NEXT_QUEUE_SIZE,
+        randomizer::RandomizerType,
         Game, Piece, PieceType,
     };
 
@@ -380,4 +404,726 @@ mod tests {
         assert_eq!(best_outcome.0.lines_cleared, 2.0);
     }
 
+    #[test]
+    fn test_beam_search_places_one_piece() {
+        let mut bot = Bot::new(3, 3);
+        let pieces = bot.game.pieces;
+
+        let moves = bot.get_best_move_sequence();
+        assert_eq!(moves.last(), Some(&BotInput::HardDrop));
+
+        for input in moves {
+            bot.apply_input(input);
+        }
+        assert_eq!(bot.game.pieces, pieces + 1);
+    }
+
+    #[test]
+    fn test_beam_search_uses_queue() {
+        // Only an I piece can clear the four rows, and it is next in the queue
+        let mut bot = Bot::new(3, 0);
+        bot.game.board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        for row in 0..4 {
+            bot.game.board[row] = [Some(PieceType::I); BOARD_AMOUNT_COLUMNS];
+            bot.game.board[row][9] = None;
+        }
+        bot.game.active_piece = Piece::new(PieceType::O, 0);
+        bot.game.piece_queue[0] = PieceType::I;
+
+        bot.search_depth = 2;
+        for input in bot.get_best_move_sequence() {
+            bot.apply_input(input);
+        }
+        for input in bot.get_best_move_sequence() {
+            bot.apply_input(input);
+        }
+        assert_eq!(bot.game.lines, 4);
+    }
+
+    #[test]
+    fn test_beam_search_ignores_hidden_pieces() {
+        for id in 0..8 {
+            for held_piece in [None, Some(PieceType::T)] {
+                let mut bot = Bot::new(3, id);
+                bot.search_depth = 8;
+                bot.game.held_piece = held_piece;
+                let moves = bot.get_beam_search_move_sequence();
+
+                // Pieces after the next queue, and the ones the randomizer deals later, are swapped out
+                for piece in bot.game.piece_queue.iter_mut().skip(NEXT_QUEUE_SIZE) {
+                    *piece = if *piece == PieceType::I {PieceType::S} else {PieceType::I};
+                }
+                bot.game.randomizer = RandomizerType::SevenBag.create(id + 100);
+                assert_eq!(bot.get_beam_search_move_sequence(), moves);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_placements_shortest_paths() {
+        let mut bot = Bot::new(0,0);
+        bot.game.board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        bot.game.active_piece = Piece::new(PieceType::T, 0);
+
+        let placements = find_placements(&bot.game);
+        let sequences: Vec<&Vec<BotInput>> = placements.iter().map(|p| &p.move_sequence).collect();
+
+        assert!(sequences.iter().all(|s| s.last() == Some(&BotInput::HardDrop)));
+        assert!(sequences.contains(&&vec![BotInput::HardDrop]));
+        assert!(sequences.contains(&&vec![BotInput::Rotate180, BotInput::HardDrop]));
+    }
+
+    #[test]
+    fn test_find_placements_tuck() {
+        // The only way into the bottom left corner is to slide in under the overhang
+        let mut bot = Bot::new(0,0);
+        bot.game.board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        bot.game.board[2][0] = Some(PieceType::I);
+        bot.game.board[2][1] = Some(PieceType::I);
+        bot.game.active_piece = Piece::new(PieceType::O, 0);
+
+        let tuck = find_placements(&bot.game)
+            .into_iter()
+            .find(|p| p.piece.midpoint == (0, 0))
+            .expect("Expected the O piece to be tucked under the overhang");
+
+        assert!(tuck.move_sequence.contains(&BotInput::SoftDrop));
+        for input in tuck.move_sequence {
+            bot.apply_input(input);
+        }
+        assert_eq!(bot.game.board[0][0], Some(PieceType::O));
+        assert_eq!(bot.game.board[1][1], Some(PieceType::O));
+    }
+
+    #[test]
+    fn test_find_placements_t_spin_double() {
+        let mut bot = Bot::new(0,0);
+        bot.game.board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        bot.game.board[0] = [Some(PieceType::I); BOARD_AMOUNT_COLUMNS];
+        bot.game.board[0][2] = None;
+        bot.game.board[1] = [Some(PieceType::I); BOARD_AMOUNT_COLUMNS];
+        bot.game.board[1][1] = None;
+        bot.game.board[1][2] = None;
+        bot.game.board[1][3] = None;
+        bot.game.board[2][0] = Some(PieceType::I);
+        bot.game.board[2][1] = Some(PieceType::I);
+        bot.game.active_piece = Piece::new(PieceType::T, 0);
+
+        let placements = find_placements(&bot.game);
+        let t_spin = placements
+            .iter()
+            .find(|p| p.spin == SpinType::TSpin)
+            .expect("Expected a T-spin placement");
+        let mut game = bot.game.clone();
+        t_spin.apply(&mut game);
+        assert_eq!(game.lines, 2);
+
+        // Following the path in the real game gives the same T-spin
+        for input in t_spin.move_sequence.clone() {
+            bot.apply_input(input);
+        }
+        assert_eq!(bot.game.lines, 2);
+        assert_eq!(bot.game.board[1], [None; BOARD_AMOUNT_COLUMNS]);
+    }
+
+    #[test]
+    fn test_bitboard_clear_lines() {
+        let mut board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        board[0] = [Some(PieceType::I); BOARD_AMOUNT_COLUMNS];
+        board[1][3] = Some(PieceType::T);
+        board[2] = [Some(PieceType::I); BOARD_AMOUNT_COLUMNS];
+        board[3][7] = Some(PieceType::T);
+
+        let mut bitboard = BitBoard::from_board(&board);
+        assert_eq!(bitboard.count_full_lines(), 2);
+        assert_eq!(bitboard.clear_lines(), 2);
+
+        assert_eq!(bitboard.rows[0], 1 << 3);
+        assert_eq!(bitboard.rows[1], 1 << 7);
+        assert_eq!(bitboard.rows[2], 0);
+        assert_eq!(bitboard.get_column_heights(), [0, 0, 0, 1, 0, 0, 0, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_bitboard_evaluation() {
+        let mut bitboard = BitBoard::from_board(&[[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS]);
+        // Column 0 is three high with one hole, column 2 is one high
+        bitboard.place(&[(0, 0), (2, 0)], (0, 0));
+        bitboard.place(&[(0, 0)], (0, 2));
+
+        assert!(bitboard.is_solid(-1, 5));
+        assert!(!bitboard.is_solid(1, 0));
+        assert_eq!(bitboard.get_aggregate_height(), 4.);
+        assert_eq!(bitboard.count_holes(), 1.);
+        assert_eq!(bitboard.count_bumpiness(), 5.);
+    }
+
+    #[test]
+    fn test_extended_features() {
+        let mut board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        // T-spin double slot in columns 1-3 with an overhang at column 1
+        board[0] = [Some(PieceType::I); BOARD_AMOUNT_COLUMNS];
+        board[0][2] = None;
+        board[1] = [Some(PieceType::I); BOARD_AMOUNT_COLUMNS];
+        board[1][1] = None;
+        board[1][2] = None;
+        board[1][3] = None;
+        board[2][0] = Some(PieceType::I);
+        board[2][1] = Some(PieceType::I);
+        let bitboard = BitBoard::from_board(&board);
+
+        assert_eq!(bitboard.get_max_height(), 3.);
+        assert_eq!(bitboard.count_t_slots(), 1.);
+        assert_eq!(bitboard.count_covered_cells(), 1.);
+        // Row 0 has one gap, row 1 one gap and row 2 ends at column 1
+        assert_eq!(bitboard.count_row_transitions(), 6.);
+        assert_eq!(bitboard.count_column_transitions(), 12.);
+    }
+
+    #[test]
+    fn test_clear_state_attack() {
+        let mut board = BitBoard::from_board(&[[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS]);
+        let mut clear_state = ClearState::default();
+
+        // A tetris leaving a pile in column 0 sends 4 without back to back
+        for r in 0..5 {
+            board.rows[r] = (1 << BOARD_AMOUNT_COLUMNS) - 1;
+        }
+        board.rows[4] = 1;
+        let mut first = board;
+        let outcome = MoveOutcome::from_board(&mut first, SpinType::None, &mut clear_state);
+        assert_eq!(outcome.lines_cleared, 4.);
+        assert_eq!(outcome.attack, 4.);
+        assert_eq!(outcome.back_to_back, 0.);
+
+        // The next tetris is back to back and continues the combo
+        let mut second = board;
+        let outcome = MoveOutcome::from_board(&mut second, SpinType::None, &mut clear_state);
+        assert_eq!(outcome.back_to_back, 1.);
+        assert_eq!(outcome.combo, 1.);
+        assert!(outcome.attack > 4.);
+    }
+
+    #[test]
+    fn test_weights_by_name() {
+        let mut weights = Weights { values: vec![1., 2.] };
+        assert_eq!(weights.get(Feature::LinesCleared), 2.);
+        assert_eq!(weights.get(Feature::AttackSent), 0.);
+
+        weights.set(Feature::AttackSent, 3.);
+        assert_eq!(weights.values.len(), ALL_FEATURES.len());
+        assert!(weights.get_named().contains(&("attack_sent", 3.)));
+
+        let outcome = MoveOutcome { lines_cleared: 2., attack: 1., ..Default::default() };
+        assert_eq!(weights.evaluate(&outcome), 7.);
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("tetris_train_{}_{}", name, std::process::id()))
+    }
+
+    fn tiny_config(name: &str) -> TrainConfig {
+        let output_dir = temp_path(name);
+        let _ = std::fs::remove_dir_all(&output_dir);
+        TrainConfig {
+            pop_size: 4,
+            generations: 2,
+            tournament_size: 3,
+            games_per_evaluation: 1,
+            steps_per_game: 20,
+            threads: 2,
+            seed: 7,
+            weights_path: output_dir.join("weights.json"),
+            output_dir,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_train_config_from_args() {
+        let args: Vec<String> = ["--pop-size", "20", "--seed", "3", "--resume", "--out", "runs"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let config = TrainConfig::from_args(&args).unwrap();
+        assert_eq!(config.fitness, FitnessType::Lines);
+        assert_eq!(config.pop_size, 20);
+        assert_eq!(config.seed, 3);
+        assert!(config.resume);
+        assert_eq!(config.output_dir, std::path::PathBuf::from("runs"));
+        assert_eq!(config.generations, TrainConfig::default().generations);
+
+        assert!(TrainConfig::from_args(&["--pop-size".to_string()]).is_err());
+        assert!(TrainConfig::from_args(&["--pop-size".to_string(), "many".to_string()]).is_err());
+        assert!(TrainConfig::from_args(&["--popsize".to_string(), "5".to_string()]).is_err());
+
+        let args: Vec<String> = ["--fitness", "versus", "--opponent", "old.json"].iter().map(|s| s.to_string()).collect();
+        let config = TrainConfig::from_args(&args).unwrap();
+        assert_eq!(config.fitness, FitnessType::Versus);
+        assert_eq!(config.opponent, Some(std::path::PathBuf::from("old.json")));
+    }
+
+    #[test]
+    fn test_weights_save_and_load() {
+        let path = temp_path("weights.json");
+        let weights = Weights::from_pairs(&[(Feature::Holes, -0.5), (Feature::Combo, 0.25)]);
+        weights.save_to(&path).unwrap();
+        assert_eq!(Weights::load_from(&path), weights);
+
+        // Missing and broken files fall back to the defaults
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(Weights::load_from(&path), Weights::default());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(Weights::load_from(&path), Weights::default());
+    }
+
+    #[test]
+    fn test_train_writes_logs_checkpoint_and_weights() {
+        let config = tiny_config("full");
+        let best = train_ai(&config).unwrap();
+
+        let log = std::fs::read_to_string(config.output_dir.join("generations.csv")).unwrap();
+        assert_eq!(log.lines().count(), 1 + config.generations);
+        assert!(log.starts_with("generation,best,mean,worst"));
+
+        let checkpoint = Checkpoint::load(&config.output_dir).unwrap();
+        assert_eq!(checkpoint.generation, config.generations);
+        assert_eq!(checkpoint.optimizer, "ga");
+        assert_eq!(checkpoint.best_weights, best);
+        assert_eq!(Weights::load_from(&config.weights_path), best);
+
+        std::fs::remove_dir_all(&config.output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_train_resume_matches_full_run() {
+        for optimizer in [OptimizerType::Genetic, OptimizerType::CmaEs, OptimizerType::ParticleSwarm] {
+            check_resume_matches_full_run(optimizer);
+        }
+    }
+
+    fn check_resume_matches_full_run(optimizer: OptimizerType) {
+        let mut full_config = tiny_config(&format!("uninterrupted_{}", optimizer.get_name()));
+        full_config.optimizer = optimizer;
+        train_ai(&full_config).unwrap();
+
+        // Stop after the first generation and continue from the checkpoint
+        let mut config = tiny_config(&format!("resumed_{}", optimizer.get_name()));
+        config.optimizer = optimizer;
+        config.generations = 1;
+        train_ai(&config).unwrap();
+        config.generations = 2;
+        config.resume = true;
+        train_ai(&config).unwrap();
+
+        let full = Checkpoint::load(&full_config.output_dir).unwrap();
+        let resumed = Checkpoint::load(&config.output_dir).unwrap();
+        assert_eq!(resumed.generation, 2);
+        assert_eq!(resumed.state, full.state);
+        assert_eq!(resumed.best_weights, full.best_weights);
+
+        let log = std::fs::read_to_string(config.output_dir.join("generations.csv")).unwrap();
+        assert_eq!(log.lines().count(), 3);
+
+        std::fs::remove_dir_all(&full_config.output_dir).unwrap();
+        std::fs::remove_dir_all(&config.output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_train_resume_needs_same_optimizer() {
+        let mut config = tiny_config("switched");
+        config.generations = 1;
+        train_ai(&config).unwrap();
+
+        config.optimizer = OptimizerType::CmaEs;
+        config.resume = true;
+        assert!(train_ai(&config).is_err());
+
+        std::fs::remove_dir_all(&config.output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let matrix = vec![vec![2., 1., 0.], vec![1., 2., 0.], vec![0., 0., 5.]];
+        let (values, vectors) = symmetric_eigen(&matrix);
+
+        let mut sorted = values.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        for (value, expected) in sorted.iter().zip([1., 3., 5.]) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+
+        // Every column is an eigenvector
+        for (k, value) in values.iter().enumerate() {
+            for (i, row) in matrix.iter().enumerate() {
+                let product: f64 = row.iter().zip(&vectors).map(|(a, v)| a * v[k]).sum();
+                assert!((product - value * vectors[i][k]).abs() < 1e-9);
+            }
+        }
+    }
+
+    // Best fitness in the first and over all 40 generations when looking for the direction of `target`
+    fn optimize_towards(optimizer: OptimizerType, target: &Weights) -> (f64, f64) {
+        let config = TrainConfig { pop_size: 16, ..Default::default() };
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut optimizer = optimizer.create(&config, &mut rng);
+
+        let mut first = None;
+        let mut best = f64::NEG_INFINITY;
+        for _ in 0..40 {
+            let candidates = optimizer.ask(&mut rng);
+            let fitness: Vec<f64> = candidates
+                .iter()
+                .map(|w| w.values.iter().zip(&target.values).map(|(a, b)| a * b).sum())
+                .collect();
+            best = fitness.iter().copied().fold(best, f64::max);
+            first.get_or_insert(best);
+            optimizer.tell(&fitness, &mut rng);
+        }
+        (first.unwrap(), best)
+    }
+
+    #[test]
+    fn test_optimizers_improve() {
+        let mut target = Weights::from_pairs(&[
+            (Feature::Holes, -1.),
+            (Feature::LinesCleared, 0.5),
+            (Feature::TSlots, 0.7),
+            (Feature::MaxHeight, -0.3),
+        ]);
+        target.normalize();
+
+        let (first, best) = optimize_towards(OptimizerType::Genetic, &target);
+        assert!(best > first);
+
+        // Both should find the target almost exactly
+        for optimizer in [OptimizerType::CmaEs, OptimizerType::ParticleSwarm] {
+            let (_, best) = optimize_towards(optimizer, &target);
+            assert!(best > 0.99, "{} didn't converge", optimizer.get_name());
+        }
+    }
+
+    #[test]
+    fn test_versus_stats_fitness() {
+        let stats = VersusStats { matches: 4, wins: 2, draws: 1, attack: 30, pieces: 100, max_pieces: 200, time: Duration::from_secs(50) };
+        assert_eq!(stats.get_win_rate(), 0.625);
+        assert_eq!(stats.get_attack_per_piece(), 0.3);
+        assert_eq!(stats.get_survival(), 0.5);
+        assert!((stats.get_fitness() - 0.9).abs() < 1e-9);
+        assert_eq!(VersusStats::default().get_fitness(), 0.);
+    }
+
+    #[test]
+    fn test_versus_stats_rates() {
+        let stats = VersusStats { matches: 4, wins: 2, draws: 1, attack: 30, pieces: 100, max_pieces: 200, time: Duration::from_secs(50) };
+        assert_eq!(stats.get_apm(), 36.);
+        assert_eq!(stats.get_pps(), 2.);
+        assert_eq!(VersusStats::default().get_pps(), 0.);
+
+        // The interval holds the win rate and narrows with more matches
+        let (low, high) = stats.get_win_rate_interval();
+        assert!(low < 0.625 && 0.625 < high);
+        assert!(low >= 0. && high <= 1.);
+        let more = VersusStats { matches: 400, wins: 200, draws: 100, ..stats };
+        let (more_low, more_high) = more.get_win_rate_interval();
+        assert!(more_high - more_low < high - low);
+
+        // Never outside 0 to 1, even when every match is won
+        let (_, high) = VersusStats { matches: 3, wins: 3, ..Default::default() }.get_win_rate_interval();
+        assert_eq!(high, 1.);
+    }
+
+    #[test]
+    fn test_play_match() {
+        // The same bot with the same pieces plays the exact same game
+        let [one, two] = play_match(&Weights::default(), &Weights::default(), 3, 60);
+        assert_eq!(one, two);
+        assert_eq!(one.draws, 1);
+
+        // Bots that like holes and height lose to the default weights
+        let bad = Weights::from_pairs(&[(Feature::Holes, 1.), (Feature::AggregateHeight, 1.)]);
+        let [good, bad] = play_match(&Weights::default(), &bad, 3, 300);
+        assert_eq!((good.wins, bad.wins), (1, 0));
+        assert!(bad.pieces < 300);
+    }
+
+    #[test]
+    fn test_train_self_play() {
+        let mut config = tiny_config("self_play");
+        config.fitness = FitnessType::SelfPlay;
+        config.generations = 1;
+        train_ai(&config).unwrap();
+
+        let log = std::fs::read_to_string(config.output_dir.join("generations.csv")).unwrap();
+        let best: f64 = log.lines().nth(1).unwrap().split(',').nth(1).unwrap().parse().unwrap();
+        assert!(best > 0.);
+
+        std::fs::remove_dir_all(&config.output_dir).unwrap();
+    }
+
+    #[test]
+    fn test_difficulties_get_harder() {
+        for pair in BOT_DIFFICULTIES.windows(2) {
+            let (easier, harder) = (pair[0], pair[1]);
+            assert!(easier.search_depth <= harder.search_depth);
+            assert!(easier.mistake_chance >= harder.mistake_chance);
+            assert!(easier.reaction_delay >= harder.reaction_delay);
+            assert!(easier.inputs_per_second < harder.inputs_per_second);
+            assert!(easier.max_pps < harder.max_pps);
+        }
+    }
+
+    #[test]
+    fn test_mistake_move_sequence() {
+        let mut bot = Bot::new(0, 4);
+        let best = bot.get_best_move_sequence();
+        for _ in 0..20 {
+            let mistake = bot.get_mistake_move_sequence().unwrap();
+            assert_ne!(mistake, best);
+            assert_eq!(mistake.last(), Some(&BotInput::HardDrop));
+        }
+    }
+
+    // Pieces placed by a bot of this difficulty in `seconds` after the countdown
+    fn pieces_placed(difficulty: usize, seconds: u64) -> usize {
+        let mut bot = Bot::new(difficulty, 6);
+        bot.game.clock = Clock::manual();
+        bot.game.reset_game(Some(6));
+
+        let frame = Duration::from_millis(16);
+        let countdown = bot.game.countdown_duration;
+        while bot.game.clock.elapsed(bot.game.reset_time) < countdown + Duration::from_secs(seconds) {
+            bot.game.clock.advance(frame);
+            bot.game.clock.catch_up();
+            bot.update_bot_game();
+        }
+        bot.game.pieces
+    }
+
+    #[test]
+    fn test_difficulty_speed() {
+        // Nothing happens before the reaction delay is over
+        let beginner = BOT_DIFFICULTIES[0];
+        assert_eq!(pieces_placed(0, 0), 0);
+        assert!(beginner.reaction_delay > Duration::from_millis(100));
+
+        // The PPS cap holds even for the fastest bot
+        for (difficulty, profile) in [(1, BOT_DIFFICULTIES[1]), (4, BOT_DIFFICULTIES[4])] {
+            let pieces = pieces_placed(difficulty, 5);
+            assert!(pieces > 0);
+            assert!(pieces as f32 <= profile.max_pps * 5. + 1.);
+        }
+        assert!(pieces_placed(1, 5) < pieces_placed(4, 5));
+    }
+
+    #[test]
+    fn test_choose_strategy() {
+        let mut game = Bot::new(0, 0).game;
+        game.board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        assert_eq!(Strategy::choose(&game, None), Strategy::BuildAttack);
+        assert_eq!(Strategy::choose(&game, Some(5)), Strategy::BuildAttack);
+
+        // Finishing off an opponent close to the top
+        assert_eq!(Strategy::choose(&game, Some(16)), Strategy::Cancel);
+
+        game.garbage_inbound.push_back((3, 2, None));
+        assert_eq!(Strategy::choose(&game, None), Strategy::Cancel);
+
+        // Garbage counts towards the height of the stack
+        for row in 0..9 {
+            game.board[row][0] = Some(PieceType::X);
+        }
+        assert_eq!(Strategy::choose(&game, None), Strategy::Downstack);
+        game.garbage_inbound.clear();
+        assert_eq!(Strategy::choose(&game, None), Strategy::BuildAttack);
+    }
+
+    #[test]
+    fn test_strategy_adjusts_weights() {
+        let weights = Weights::default();
+        let adjusted = Strategy::Cancel.adjust(&weights);
+        assert!(adjusted.get(Feature::AttackSent) > weights.get(Feature::AttackSent));
+        assert_eq!(adjusted.get(Feature::Holes), weights.get(Feature::Holes));
+
+        let adjusted = Strategy::BuildAttack.adjust(&weights);
+        assert!(adjusted.get(Feature::LinesCleared) < weights.get(Feature::LinesCleared));
+        assert!(adjusted.get(Feature::TSlots) > 0.);
+    }
+
+    #[test]
+    fn test_strategy_only_for_the_next_move() {
+        let mut bot = Bot::new(3, 2);
+        bot.profile.mistake_chance = 0.;
+        bot.game.garbage_inbound.push_back((3, 2, None));
+
+        let weights = bot.weights.clone();
+        let moves = bot.get_next_move_sequence();
+        assert_eq!(moves.last(), Some(&BotInput::HardDrop));
+        assert_eq!(bot.strategy, Strategy::Cancel);
+        assert_eq!(bot.weights, weights);
+
+        // The easier bots play on their trained weights only
+        let mut bot = Bot::new(0, 2);
+        bot.profile.mistake_chance = 0.;
+        bot.game.garbage_inbound.push_back((3, 2, None));
+        bot.get_next_move_sequence();
+        assert_eq!(bot.strategy, Strategy::BuildAttack);
+    }
+
+    #[test]
+    fn test_arena_config_from_args() {
+        let args: Vec<String> = ["--matches", "5", "--one-difficulty", "Hard", "--two-difficulty", "0", "--two-depth", "2", "--one-weights", "old.json"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let config = ArenaConfig::from_args(&args).unwrap();
+        assert_eq!(config.matches, 5);
+        assert_eq!(config.bots[0].difficulty, 3);
+        assert_eq!(config.bots[0].weights, Some(std::path::PathBuf::from("old.json")));
+        assert_eq!(config.bots[1].difficulty, 0);
+        assert_eq!(config.bots[1].search_depth, Some(2));
+        assert_eq!(config.max_time, ArenaConfig::default().max_time);
+
+        assert!(ArenaConfig::from_args(&["--one-difficulty".to_string(), "impossible".to_string()]).is_err());
+        assert!(ArenaConfig::from_args(&["--two-difficulty".to_string(), "5".to_string()]).is_err());
+        assert!(ArenaConfig::from_args(&["--three-depth".to_string(), "1".to_string()]).is_err());
+        assert!(ArenaConfig::from_args(&["--matches".to_string(), "0".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_arena_match() {
+        let bot = |difficulty| ArenaBot {
+            difficulty,
+            search_depth: Some(1),
+            ..Default::default()
+        };
+        let mut config = ArenaConfig {
+            bots: [bot(3), bot(3)],
+            max_time: Duration::from_secs(20),
+            ..Default::default()
+        };
+
+        // The same bot on both sides plays the same game
+        let [one, two] = play_arena_match(&config, 4);
+        assert_eq!(one, two);
+        assert_eq!(one.draws, 1);
+        assert!(one.time >= config.max_time);
+
+        // Speed follows the difficulty
+        config.bots[1] = bot(1);
+        let [hard, easy] = play_arena_match(&config, 4);
+        assert!(hard.get_pps() > easy.get_pps());
+        assert!(hard.get_pps() <= BOT_DIFFICULTIES[3].max_pps as f64 + 0.1);
+        assert_eq!(play_arena_match(&config, 4), [hard, easy]);
+    }
+
+    #[test]
+    fn test_coach() {
+        let mut game = Bot::new(0, 5).game;
+        game.board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        game.countdown_start = None;
+        game.active_piece = Piece::new(PieceType::I, 0);
+        game.can_hold = false;
+
+        // Nothing is shown until the coach is turned on
+        let mut coach = Coach::new(Weights::default());
+        coach.update(&mut game);
+        assert!(game.hint_piece.is_none());
+        coach.toggle(&mut game);
+        coach.update(&mut game);
+        let hint = game.hint_piece.clone().unwrap();
+        assert_eq!(hint.piece_type, PieceType::I);
+        assert!(hint.block_positions.iter().any(|(dr, _)| hint.midpoint.0 + dr == 0));
+        assert_eq!(coach.last_delta, None);
+
+        // Following the hint is the best placement
+        game.active_piece = hint;
+        game.place_piece();
+        coach.update(&mut game);
+        assert!(Coach::is_best(coach.last_delta.unwrap()));
+        assert!(game.hint_piece.is_some());
+
+        // Leaving a piece floating in the air is not
+        game.active_piece.midpoint.0 = 10;
+        game.place_piece();
+        coach.update(&mut game);
+        assert!(coach.last_delta.unwrap() < -0.1);
+
+        coach.toggle(&mut game);
+        assert!(game.hint_piece.is_none());
+    }
+
+    // Two rows with a 4 wide hole, the T can't fill it but the two I's can
+    fn pc_game() -> Game {
+        let mut game = Bot::new(0, 0).game;
+        game.board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        for row in game.board.iter_mut().take(2) {
+            for cell in row.iter_mut().skip(4) {
+                *cell = Some(PieceType::X);
+            }
+        }
+        game.countdown_start = None;
+        game.active_piece = Piece::new(PieceType::T, 0);
+        game.held_piece = None;
+        game.can_hold = true;
+        game.piece_queue = [PieceType::I, PieceType::I, PieceType::O].into();
+        game
+    }
+
+    #[test]
+    fn test_pc_solver() {
+        let mut game = pc_game();
+        let steps = PcSolver::default().solve(&game).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert!(steps[0].hold);
+        assert_eq!(steps[0].get_move_sequence()[0], BotInput::Hold);
+        assert!(steps.iter().all(|step| step.placement.piece.piece_type == PieceType::I));
+
+        for step in &steps {
+            if step.hold {
+                game.hold();
+            }
+            step.placement.apply(&mut game);
+        }
+        assert!(game.all_clear);
+        assert!(game.board.iter().all(|row| row.iter().all(|cell| cell.is_none())));
+        assert_eq!(game.held_piece, Some(PieceType::T));
+
+        // Without hold the T has to go in first
+        let mut game = pc_game();
+        game.can_hold = false;
+        assert!(PcSolver::default().solve(&game).is_none());
+
+        // 3 filled cells never leave a multiple of 4 to fill
+        game.board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        game.board[0][0] = Some(PieceType::X);
+        game.board[0][1] = Some(PieceType::X);
+        game.board[0][2] = Some(PieceType::X);
+        assert!(PcSolver::default().solve(&game).is_none());
+
+        // A stack taller than the board is wide, only the well in column 0 is left
+        game.board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        for row in game.board.iter_mut().take(12) {
+            for cell in row.iter_mut().skip(1) {
+                *cell = Some(PieceType::X);
+            }
+        }
+        let tall = PcSolver { max_height: 12, ..Default::default() };
+        assert!(tall.is_possible(&BitBoard::from_board(&game.board)));
+        assert!(!PcSolver::default().is_possible(&BitBoard::from_board(&game.board)));
+
+        game.active_piece = Piece::new(PieceType::I, 0);
+        game.piece_queue = [PieceType::I, PieceType::I].into();
+        let steps = tall.solve(&game).unwrap();
+        assert_eq!(steps.len(), 3);
+    }
+
+    #[test]
+    fn test_bot_goes_for_perfect_clear() {
+        let mut bot = Bot::new(BOT_DIFFICULTIES.len() - 1, 0);
+        bot.game = pc_game();
+        let moves = bot.get_next_move_sequence();
+        assert_eq!(bot.strategy, Strategy::PerfectClear);
+        assert_eq!(moves[0], BotInput::Hold);
+    }
 }
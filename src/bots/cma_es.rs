@@ -0,0 +1,233 @@
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    optimizer::{sample_normal, to_weights, Optimizer},
+    weights::{Weights, ALL_FEATURES},
+};
+
+const INITIAL_SIGMA: f64 = 0.3;
+
+/*
+    Covariance matrix adaptation evolution strategy.
+    Candidates are sampled from a normal distribution around `mean`. After
+    every generation the mean moves towards the best half of the candidates,
+    and the covariance and step size adapt to the directions that worked.
+    Follows "The CMA Evolution Strategy: A Tutorial" by Nikolaus Hansen.
+*/
+#[derive(Serialize, Deserialize)]
+pub struct CmaEs {
+    lambda: usize, // Candidates per generation
+    generation: usize,
+    mean: Vec<f64>,
+    sigma: f64,
+    path_c: Vec<f64>, // Evolution path of the covariance
+    path_sigma: Vec<f64>, // Evolution path of the step size
+    covariance: Vec<Vec<f64>>,
+    eigenvectors: Vec<Vec<f64>>, // B, one eigenvector per column
+    eigenvalue_roots: Vec<f64>, // D
+    steps: Vec<Vec<f64>>, // Steps y = B * D * z of the latest candidates
+}
+
+impl CmaEs {
+    pub fn new(lambda: usize) -> Self {
+        // Starts around the default weights since they're known to be decent
+        let n = ALL_FEATURES.len();
+        let mut mean = Weights::default();
+        mean.normalize();
+
+        Self {
+            lambda: lambda.max(4),
+            generation: 0,
+            mean: mean.values,
+            sigma: INITIAL_SIGMA,
+            path_c: vec![0.; n],
+            path_sigma: vec![0.; n],
+            covariance: identity(n),
+            eigenvectors: identity(n),
+            eigenvalue_roots: vec![1.; n],
+            steps: vec![],
+        }
+    }
+
+    // Recombination weights for the best mu candidates and mu_eff
+    fn get_recombination_weights(&self) -> (Vec<f64>, f64) {
+        let mu = self.lambda / 2;
+        let raw: Vec<f64> = (1..=mu)
+            .map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let sum: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|w| w / sum).collect();
+        let mu_eff = 1. / weights.iter().map(|w| w * w).sum::<f64>();
+        (weights, mu_eff)
+    }
+}
+
+impl Optimizer for CmaEs {
+    fn get_name(&self) -> &'static str {
+        "cma-es"
+    }
+
+    fn ask(&mut self, rng: &mut StdRng) -> Vec<Weights> {
+        let n = self.mean.len();
+        let (eigenvalues, eigenvectors) = symmetric_eigen(&self.covariance);
+        self.eigenvalue_roots = eigenvalues.iter().map(|v| v.max(1e-20).sqrt()).collect();
+        self.eigenvectors = eigenvectors;
+
+        self.steps = (0..self.lambda)
+            .map(|_| {
+                let scaled: Vec<f64> = (0..n)
+                    .map(|i| self.eigenvalue_roots[i] * sample_normal(rng))
+                    .collect();
+                mat_vec(&self.eigenvectors, &scaled)
+            })
+            .collect();
+
+        self.steps
+            .iter()
+            .map(|y| {
+                let x: Vec<f64> = self.mean.iter().zip(y).map(|(m, y)| m + self.sigma * y).collect();
+                to_weights(&x)
+            })
+            .collect()
+    }
+
+    fn tell(&mut self, fitness: &[f64], _rng: &mut StdRng) {
+        let n = self.mean.len() as f64;
+        let (weights, mu_eff) = self.get_recombination_weights();
+
+        let c_c = (4. + mu_eff / n) / (n + 4. + 2. * mu_eff / n);
+        let c_sigma = (mu_eff + 2.) / (n + mu_eff + 5.);
+        let c_1 = 2. / ((n + 1.3).powi(2) + mu_eff);
+        let c_mu = (1. - c_1).min(2. * (mu_eff - 2. + 1. / mu_eff) / ((n + 2.).powi(2) + mu_eff));
+        let damping = 1. + 2. * (((mu_eff - 1.) / (n + 1.)).sqrt() - 1.).max(0.) + c_sigma;
+        let expected_norm = n.sqrt() * (1. - 1. / (4. * n) + 1. / (21. * n * n));
+
+        let mut order: Vec<usize> = (0..self.steps.len()).collect();
+        order.sort_by(|a, b| fitness[*b].total_cmp(&fitness[*a]));
+        let best_steps: Vec<&Vec<f64>> = order.iter().take(weights.len()).map(|i| &self.steps[*i]).collect();
+
+        // Weighted mean of the best steps
+        let mut step_w = vec![0.; self.mean.len()];
+        for (w, y) in weights.iter().zip(&best_steps) {
+            for (s, y) in step_w.iter_mut().zip(y.iter()) {
+                *s += w * y;
+            }
+        }
+        for (m, s) in self.mean.iter_mut().zip(&step_w) {
+            *m += self.sigma * s;
+        }
+
+        // C^(-1/2) * step_w = B * D^-1 * B^T * step_w
+        let rotated = mat_t_vec(&self.eigenvectors, &step_w);
+        let whitened: Vec<f64> = rotated.iter().zip(&self.eigenvalue_roots).map(|(r, d)| r / d).collect();
+        let whitened = mat_vec(&self.eigenvectors, &whitened);
+
+        let sigma_factor = (c_sigma * (2. - c_sigma) * mu_eff).sqrt();
+        for (p, w) in self.path_sigma.iter_mut().zip(&whitened) {
+            *p = (1. - c_sigma) * *p + sigma_factor * w;
+        }
+        let path_sigma_norm = self.path_sigma.iter().map(|p| p * p).sum::<f64>().sqrt();
+
+        // Stalls the covariance path while the step size is growing fast
+        self.generation += 1;
+        let h_sigma = path_sigma_norm / (1. - (1. - c_sigma).powi(2 * self.generation as i32)).sqrt() / expected_norm
+            < 1.4 + 2. / (n + 1.);
+        let h_sigma = if h_sigma {1.} else {0.};
+
+        let c_factor = (c_c * (2. - c_c) * mu_eff).sqrt();
+        for (p, s) in self.path_c.iter_mut().zip(&step_w) {
+            *p = (1. - c_c) * *p + h_sigma * c_factor * s;
+        }
+
+        let dim = self.mean.len();
+        for i in 0..dim {
+            for j in 0..dim {
+                let rank_mu: f64 = weights.iter().zip(&best_steps).map(|(w, y)| w * y[i] * y[j]).sum();
+                let rank_one = self.path_c[i] * self.path_c[j]
+                    + (1. - h_sigma) * c_c * (2. - c_c) * self.covariance[i][j];
+                self.covariance[i][j] = (1. - c_1 - c_mu) * self.covariance[i][j] + c_1 * rank_one + c_mu * rank_mu;
+            }
+        }
+
+        self.sigma *= ((c_sigma / damping) * (path_sigma_norm / expected_norm - 1.)).exp();
+    }
+
+    fn get_state(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+
+    fn set_state(&mut self, state: Value) -> Result<(), serde_json::Error> {
+        *self = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n).map(|i| (0..n).map(|j| if i == j {1.} else {0.}).collect()).collect()
+}
+
+fn mat_vec(m: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    m.iter().map(|row| row.iter().zip(v).map(|(a, b)| a * b).sum()).collect()
+}
+
+// Transposed matrix times vector
+fn mat_t_vec(m: &[Vec<f64>], v: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.; v.len()];
+    for (row, x) in m.iter().zip(v) {
+        for (r, a) in result.iter_mut().zip(row) {
+            *r += a * x;
+        }
+    }
+    result
+}
+
+/*
+    Eigenvalues and eigenvectors (as columns) of a symmetric matrix using
+    the cyclic Jacobi method. Fine for matrices as small as ours.
+*/
+pub fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a: Vec<Vec<f64>> = matrix.to_vec();
+    let mut v = identity(n);
+
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off_diagonal < 1e-22 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2. * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.).sqrt());
+                let c = 1. / (t * t + 1.).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (top, bottom) = a.split_at_mut(q);
+                for (apk, aqk) in top[p].iter_mut().zip(bottom[0].iter_mut()) {
+                    (*apk, *aqk) = (c * *apk - s * *aqk, s * *apk + c * *aqk);
+                }
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    ((0..n).map(|i| a[i][i]).collect(), v)
+}
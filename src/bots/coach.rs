@@ -0,0 +1,133 @@
+use super::{
+    bitboard::BitBoard,
+    board_evaluation::ClearState,
+    move_generator::find_placements,
+    move_outcome::{MoveOutcome, SpinType},
+    weights::Weights,
+};
+use crate::{Game, Piece, PieceType};
+
+// Deltas this close to 0 count as playing the best placement
+const BEST_MARGIN: f64 = 0.01;
+
+/*
+    Shows newer players where the bot would put the current piece, and
+    how much worse their own placement was. Looks one piece ahead like the
+    training bots, so every placement is judged on its own.
+*/
+#[derive(Clone)]
+pub struct Coach {
+    pub enabled: bool,
+    pub weights: Weights,
+    pub best_evaluation: Option<f64>, // Best placement of the current piece, hold included
+    pub last_delta: Option<f64>, // Evaluation of the latest placement minus the best one, at most 0
+    before: Option<(BitBoard, ClearState, usize)>, // Board, clear state and garbage received when the current piece spawned
+    hint_for: Option<(usize, PieceType, bool)>, // Pieces placed, active piece and can_hold the hint was made for
+}
+
+impl Coach {
+    pub fn new(weights: Weights) -> Self {
+        Self {
+            enabled: false,
+            weights,
+            best_evaluation: None,
+            last_delta: None,
+            before: None,
+            hint_for: None,
+        }
+    }
+
+    pub fn toggle(&mut self, game: &mut Game) {
+        self.enabled = !self.enabled;
+        self.hint_for = None;
+        self.last_delta = None;
+        game.hint_piece = None;
+    }
+
+    // Call once per update. Grades the latest placement and makes a new hint when the active piece changes
+    pub fn update(&mut self, game: &mut Game) {
+        // Nothing to hint during the entry delay, the next piece hasn't spawned
+        if !self.enabled || game.countdown_start.is_some() || game.game_over || game.spawn_at.is_some() {
+            game.hint_piece = None;
+            return;
+        }
+
+        let key = (game.pieces, game.active_piece.piece_type, game.can_hold);
+        if self.hint_for == Some(key) {
+            return;
+        }
+
+        let held = self.hint_for.is_some_and(|(pieces, _, _)| pieces == game.pieces);
+        match self.hint_for {
+            Some((pieces, _, _)) if game.pieces == pieces + 1 => self.grade_placement(game),
+            // Reset or a new game
+            Some((pieces, _, _)) if game.pieces < pieces => self.last_delta = None,
+            _ => (),
+        }
+        self.hint_for = Some(key);
+
+        let best = self.get_best_placement(game);
+        game.hint_piece = best.as_ref().map(|(piece, _)| piece.clone());
+        // After holding, the placement is still graded against the best from before the hold
+        if !held {
+            self.before = Some((BitBoard::from_board(&game.board), ClearState::from_game(game), game.garbage_received));
+            self.best_evaluation = best.map(|(_, evaluation)| evaluation);
+        }
+    }
+
+    fn grade_placement(&mut self, game: &Game) {
+        let (Some((bitboard, clear_state, garbage)), Some(best), Some((piece, t_spin, t_spin_mini))) =
+            (self.before, self.best_evaluation, &game.last_placed)
+        else {
+            return;
+        };
+        // Garbage that came in since the hint was made changed the board
+        if game.garbage_received != garbage {
+            self.last_delta = None;
+            return;
+        }
+
+        let spin = if *t_spin_mini {
+            SpinType::TSpinMini
+        } else if *t_spin {
+            SpinType::TSpin
+        } else {
+            SpinType::None
+        };
+        let evaluation = self.evaluate(&bitboard, &clear_state, piece, spin);
+        self.last_delta = Some((evaluation - best).min(0.));
+    }
+
+    pub fn is_best(delta: f64) -> bool {
+        delta > -BEST_MARGIN
+    }
+
+    fn evaluate(&self, bitboard: &BitBoard, clear_state: &ClearState, piece: &Piece, spin: SpinType) -> f64 {
+        let mut board = *bitboard;
+        board.place(&piece.block_positions, piece.midpoint);
+        self.weights.evaluate(&MoveOutcome::from_board(&mut board, spin, &mut clear_state.clone()))
+    }
+
+    // The best placement of the active piece or the piece that would be held, like Bot::get_best_move_sequence
+    pub fn get_best_placement(&self, game: &Game) -> Option<(Piece, f64)> {
+        let mut games = vec![game.clone()];
+        if game.can_hold {
+            let mut held = game.clone();
+            held.hold();
+            if !held.game_over {
+                games.push(held);
+            }
+        }
+
+        let bitboard = BitBoard::from_board(&game.board);
+        let clear_state = ClearState::from_game(game);
+        games
+            .iter()
+            .flat_map(find_placements)
+            .map(|placement| {
+                let evaluation = self.evaluate(&bitboard, &clear_state, &placement.piece, placement.spin);
+                (placement.piece, evaluation)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+/*
+    How well and how fast a bot plays. Lower difficulties don't only play
+    slower, they also think longer before moving and sometimes pick a
+    worse placement, which makes them feel a lot more human.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DifficultyProfile {
+    pub name: &'static str,
+    pub search_depth: usize, // Pieces planned ahead, see Bot::search_depth
+    pub mistake_chance: f64, // Chance per placement to pick one of the next best placements instead
+    pub reaction_delay: Duration, // Thinking time before the first input of every piece
+    pub inputs_per_second: f32,
+    pub max_pps: f32, // Pieces per second cap, the hard drop waits if the bot is faster
+    pub uses_strategy: bool, // Reacts to garbage and the opponent, see Strategy
+}
+
+impl DifficultyProfile {
+    pub fn get_input_interval(&self) -> Duration {
+        Duration::from_secs_f32(1. / self.inputs_per_second)
+    }
+
+    pub fn get_piece_interval(&self) -> Duration {
+        Duration::from_secs_f32(1. / self.max_pps)
+    }
+}
+
+pub const BOT_DIFFICULTIES: [DifficultyProfile; 5] = [
+    DifficultyProfile {
+        name: "BEGINNER",
+        search_depth: 1,
+        mistake_chance: 0.25,
+        reaction_delay: Duration::from_millis(900),
+        inputs_per_second: 4.,
+        max_pps: 0.5,
+        uses_strategy: false,
+    },
+    DifficultyProfile {
+        name: "EASY",
+        search_depth: 1,
+        mistake_chance: 0.12,
+        reaction_delay: Duration::from_millis(500),
+        inputs_per_second: 6.,
+        max_pps: 0.8,
+        uses_strategy: false,
+    },
+    DifficultyProfile {
+        name: "MEDIUM",
+        search_depth: 1,
+        mistake_chance: 0.05,
+        reaction_delay: Duration::from_millis(250),
+        inputs_per_second: 10.,
+        max_pps: 1.5,
+        uses_strategy: true,
+    },
+    DifficultyProfile {
+        name: "HARD",
+        search_depth: 3,
+        mistake_chance: 0.01,
+        reaction_delay: Duration::from_millis(100),
+        inputs_per_second: 20.,
+        max_pps: 2.5,
+        uses_strategy: true,
+    },
+    DifficultyProfile {
+        name: "EXPERT",
+        search_depth: 4,
+        mistake_chance: 0.,
+        reaction_delay: Duration::ZERO,
+        inputs_per_second: 60.,
+        max_pps: 4.,
+        uses_strategy: true,
+    },
+];
+
+// Used by the trainer, plays as well and as fast as possible
+pub const PERFECT_PROFILE: DifficultyProfile = DifficultyProfile {
+    name: "PERFECT",
+    search_depth: 1,
+    mistake_chance: 0.,
+    reaction_delay: Duration::ZERO,
+    inputs_per_second: f32::INFINITY,
+    max_pps: f32::INFINITY,
+    uses_strategy: false,
+};
@@ -0,0 +1,84 @@
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{bot::Bot, optimizer::Optimizer, train_bot::TrainConfig, weights::Weights};
+
+/*
+    Tournament selection GA. Every generation the worst part of the
+    population is replaced by crossovers of tournament winners, which
+    are sometimes mutated.
+*/
+#[derive(Serialize, Deserialize)]
+pub struct Genetic {
+    population: Vec<Weights>,
+    tournament_size: usize,
+    offspring_count: usize,
+}
+
+impl Genetic {
+    pub fn new(config: &TrainConfig, rng: &mut StdRng) -> Self {
+        Self {
+            population: (0..config.pop_size)
+                .map(|_| Bot::with_random_unit_weights(rng).weights)
+                .collect(),
+            tournament_size: config.tournament_size.clamp(2, config.pop_size),
+            offspring_count: ((config.pop_size as f64 * config.offspring_ratio) as usize)
+                .min(config.pop_size - 1),
+        }
+    }
+}
+
+impl Optimizer for Genetic {
+    fn get_name(&self) -> &'static str {
+        "ga"
+    }
+
+    fn ask(&mut self, _rng: &mut StdRng) -> Vec<Weights> {
+        self.population.clone()
+    }
+
+    fn tell(&mut self, fitness: &[f64], rng: &mut StdRng) {
+        let mut population: Vec<Bot> = self
+            .population
+            .iter()
+            .zip(fitness)
+            .map(|(weights, fitness)| {
+                let mut bot = Bot::with_weights(weights.clone());
+                bot.fitness = *fitness;
+                bot
+            })
+            .collect();
+        population.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+
+        let mut offspring: Vec<Bot> = vec![];
+        while offspring.len() < self.offspring_count {
+            let mut tournament: Vec<&Bot> = population
+                .choose_multiple(rng, self.tournament_size)
+                .collect();
+            tournament.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+
+            let mut child = Bot::random_crossover(tournament[0], tournament[1], rng);
+            if rng.random_bool(0.05) {
+                child.mutate(0.1, rng);
+            }
+
+            offspring.push(child);
+        }
+
+        population.truncate(population.len() - self.offspring_count);
+        population.extend(offspring);
+        self.population = population.into_iter().map(|b| b.weights).collect();
+    }
+
+    fn get_state(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+
+    fn set_state(&mut self, state: Value) -> Result<(), serde_json::Error> {
+        *self = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
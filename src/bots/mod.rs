@@ -1,6 +1,25 @@
+pub mod bitboard;
 pub mod bot;
+pub mod difficulty;
+pub mod strategy;
 pub mod bot_input;
 pub mod move_outcome;
+pub mod move_generator;
 pub mod board_evaluation;
+pub mod beam_search;
+pub mod weights;
 pub mod bot_tests;
-pub mod train_bot;
\ No newline at end of file
+pub mod train_bot;
+pub mod optimizer;
+pub mod genetic;
+pub mod cma_es;
+pub mod particle_swarm;
+pub mod versus_training;
+pub mod arena;
+pub mod coach;
+pub mod pc_solver;
+pub mod tbp;
+pub mod tbp_server;
+pub mod tbp_client;
+pub mod tbp_tests;
+
@@ -0,0 +1,211 @@
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+use super::{bitboard::BitBoard, bot_input::BotInput, move_outcome::SpinType};
+use crate::{
+    rotation::{get_kick_table, get_t_spin},
+    Game, Piece, PieceType, ROTATION_180, ROTATION_CCW, ROTATION_CW,
+};
+
+const STEP_INPUTS: [BotInput; 6] = [
+    BotInput::MoveLeft,
+    BotInput::MoveRight,
+    BotInput::RotateCW,
+    BotInput::RotateCCW,
+    BotInput::Rotate180,
+    BotInput::SoftDrop,
+];
+
+// Position of the active piece while searching. The spin flags are part of the state
+// since the same position can be reached both with and without a T-spin
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct PieceState {
+    midpoint: (isize, isize),
+    rotation: usize,
+    t_spin: bool,
+    t_spin_mini: bool,
+}
+
+impl PieceState {
+    fn get_spin_type(&self) -> SpinType {
+        if self.t_spin_mini {
+            SpinType::TSpinMini
+        } else if self.t_spin {
+            SpinType::TSpin
+        } else {
+            SpinType::None
+        }
+    }
+
+    // Moving the piece always removes the spin
+    fn moved(&self, dr: isize, dc: isize) -> Self {
+        Self {
+            midpoint: (self.midpoint.0 + dr, self.midpoint.1 + dc),
+            rotation: self.rotation,
+            t_spin: false,
+            t_spin_mini: false,
+        }
+    }
+}
+
+// A spot the active piece can be locked in and the shortest way of getting there
+#[derive(Clone)]
+pub struct Placement {
+    pub piece: Piece,
+    pub spin: SpinType,
+    pub move_sequence: Vec<BotInput>,
+}
+
+impl Placement {
+    // Locks the active piece of `game` in this spot
+    pub fn apply(&self, game: &mut Game) {
+        game.active_piece = self.piece.clone();
+        game.t_spin = self.spin != SpinType::None;
+        game.t_spin_mini = self.spin == SpinType::TSpinMini;
+        game.place_piece();
+    }
+
+    // The board right after the piece is locked, before any lines are cleared
+    pub fn get_resulting_bitboard(&self, bitboard: &BitBoard) -> BitBoard {
+        let mut bitboard = *bitboard;
+        bitboard.place(&self.piece.block_positions, self.piece.midpoint);
+        bitboard
+    }
+
+    pub fn get_cells(&self) -> [(isize, isize); 4] {
+        get_cells(&self.piece.block_positions, self.piece.midpoint)
+    }
+}
+
+pub fn find_placements(game: &Game) -> Vec<Placement> {
+    find_placements_on(&BitBoard::from_board(&game.board), &game.active_piece, game.t_spin, game.t_spin_mini)
+}
+
+/*
+    Breadth first search over every position the piece can reach with
+    shifts, soft drops and all three rotations. Everything is checked against
+    the BitBoard, using the same kick tables and T-spin checks as
+    Game::rotate. Since the search is breadth first, the first path found to
+    a placement is also the shortest one. Placements covering the same cells
+    with the same spin are only returned once.
+*/
+pub fn find_placements_on(bitboard: &BitBoard, piece: &Piece, t_spin: bool, t_spin_mini: bool) -> Vec<Placement> {
+    let piece_type = piece.piece_type;
+
+    let start = PieceState {
+        midpoint: piece.midpoint,
+        rotation: piece.rotation,
+        t_spin,
+        t_spin_mini,
+    };
+    let mut paths: HashMap<PieceState, Vec<BotInput>> = HashMap::new();
+    let mut queue: VecDeque<PieceState> = VecDeque::new();
+    paths.insert(start, Vec::new());
+    queue.push_back(start);
+
+    let mut seen_placements: HashSet<([(isize, isize); 4], SpinType)> = HashSet::new();
+    let mut placements = Vec::new();
+
+    while let Some(state) = queue.pop_front() {
+        let path = paths[&state].clone();
+        let minos = Piece::get_minos(piece_type, state.rotation);
+
+        // Resting on something, the piece can be locked here
+        if !bitboard.fits(minos, state.moved(-1, 0).midpoint) {
+            let spin = state.get_spin_type();
+            if seen_placements.insert((get_cells(minos, state.midpoint), spin)) {
+                let mut move_sequence = path.clone();
+                // Hard dropping does the same as a final soft drop
+                if move_sequence.last() == Some(&BotInput::SoftDrop) {
+                    move_sequence.pop();
+                }
+                move_sequence.push(BotInput::HardDrop);
+
+                placements.push(Placement {
+                    piece: Piece {
+                        piece_type,
+                        midpoint: state.midpoint,
+                        block_positions: minos.to_vec(),
+                        rotation: state.rotation,
+                    },
+                    spin,
+                    move_sequence,
+                });
+            }
+        }
+
+        for input in STEP_INPUTS {
+            let next = match input {
+                BotInput::MoveLeft => try_move(bitboard, piece_type, &state, 0, -1),
+                BotInput::MoveRight => try_move(bitboard, piece_type, &state, 0, 1),
+                BotInput::RotateCW => try_rotate(bitboard, piece_type, &state, ROTATION_CW),
+                BotInput::RotateCCW => try_rotate(bitboard, piece_type, &state, ROTATION_CCW),
+                BotInput::Rotate180 => try_rotate(bitboard, piece_type, &state, ROTATION_180),
+                _ => {
+                    let mut dropped = try_move(bitboard, piece_type, &state, -1, 0);
+                    while let Some(next) = dropped.and_then(|s| try_move(bitboard, piece_type, &s, -1, 0)) {
+                        dropped = Some(next);
+                    }
+                    dropped
+                }
+            };
+
+            if let Some(next) = next {
+                if let Entry::Vacant(entry) = paths.entry(next) {
+                    let mut next_path = path.clone();
+                    next_path.push(input);
+                    entry.insert(next_path);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    placements
+}
+
+fn try_move(bitboard: &BitBoard, piece_type: PieceType, state: &PieceState, dr: isize, dc: isize) -> Option<PieceState> {
+    let next = state.moved(dr, dc);
+    bitboard.fits(Piece::get_minos(piece_type, state.rotation), next.midpoint).then_some(next)
+}
+
+// Same as Game::rotate but on the BitBoard
+fn try_rotate(bitboard: &BitBoard, piece_type: PieceType, state: &PieceState, rotation_type: usize) -> Option<PieceState> {
+    // O pieces look the same in every rotation
+    if piece_type == PieceType::O {
+        return None;
+    }
+
+    let new_rotation = (state.rotation + rotation_type) % 4;
+    let minos = Piece::get_minos(piece_type, new_rotation);
+
+    for (dx, dy) in get_kick_table(piece_type, rotation_type, new_rotation) {
+        let midpoint = (state.midpoint.0 + dy, state.midpoint.1 + dx);
+        if bitboard.fits(minos, midpoint) {
+            let mut next = PieceState {
+                midpoint,
+                rotation: new_rotation,
+                t_spin: false,
+                t_spin_mini: false,
+            };
+            if piece_type == PieceType::T {
+                if let Some(mini) = get_t_spin(|r, c| bitboard.is_solid(r, c), midpoint, new_rotation) {
+                    next.t_spin = true;
+                    next.t_spin_mini = mini;
+                }
+            }
+            return Some(next);
+        }
+    }
+    None
+}
+
+// Board cells covered by the piece, sorted so rotations covering the same cells compare equal
+fn get_cells(minos: &[(isize, isize)], midpoint: (isize, isize)) -> [(isize, isize); 4] {
+    let (mr, mc) = midpoint;
+    let mut cells = [(0, 0); 4];
+    for (cell, (dr, dc)) in cells.iter_mut().zip(minos.iter()) {
+        *cell = (mr + dr, mc + dc);
+    }
+    cells.sort();
+    cells
+}
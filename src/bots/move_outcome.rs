@@ -1,18 +1,47 @@
-use super::bot_input::BotInput;
-use crate::Game;
+use super::{bot_input::BotInput, weights::Feature};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SpinType {
+    None,
+    TSpinMini,
+    TSpin,
+}
+
+// Features of the board after a placement and its line clears
+#[derive(Clone, Debug, Default)]
 pub struct MoveOutcome {
     pub move_sequence: Vec<BotInput>,
     pub lines_cleared: f32,
     pub aggregate_height: f32,
     pub holes: f32,
     pub bumpiness: f32,
+    pub row_transitions: f32,
+    pub column_transitions: f32,
+    pub well_depth: f32,
+    pub covered_cells: f32, // Filled cells above a hole in the same column
+    pub max_height: f32,
+    pub t_slots: f32, // Spots where a T-spin double can be done
+    pub back_to_back: f32, // 1 if back to back is active after the placement
+    pub combo: f32,
+    pub attack: f32, // Garbage lines sent by the placement
 }
 
-#[derive(Clone)]
-pub struct MovementState {
-    pub game: Game,
-    pub moves_so_far: Vec<BotInput>,
-    pub rotations: usize,
+impl MoveOutcome {
+    pub fn get_feature(&self, feature: Feature) -> f32 {
+        match feature {
+            Feature::AggregateHeight => self.aggregate_height,
+            Feature::LinesCleared => self.lines_cleared,
+            Feature::Holes => self.holes,
+            Feature::Bumpiness => self.bumpiness,
+            Feature::RowTransitions => self.row_transitions,
+            Feature::ColumnTransitions => self.column_transitions,
+            Feature::WellDepth => self.well_depth,
+            Feature::CoveredCells => self.covered_cells,
+            Feature::MaxHeight => self.max_height,
+            Feature::TSlots => self.t_slots,
+            Feature::BackToBack => self.back_to_back,
+            Feature::Combo => self.combo,
+            Feature::AttackSent => self.attack,
+        }
+    }
 }
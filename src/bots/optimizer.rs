@@ -0,0 +1,76 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde_json::Value;
+
+use super::{
+    cma_es::CmaEs,
+    genetic::Genetic,
+    particle_swarm::ParticleSwarm,
+    train_bot::TrainConfig,
+    weights::Weights,
+};
+
+/*
+    Searches for good weights by asking for a batch of candidates,
+    having them evaluated and telling it the fitness of each. The
+    trainer does the evaluation, so every optimizer is measured the
+    same way on the same games.
+*/
+pub trait Optimizer {
+    fn get_name(&self) -> &'static str;
+
+    // Weights to evaluate this generation
+    fn ask(&mut self, rng: &mut StdRng) -> Vec<Weights>;
+
+    // Fitness of the weights from the latest ask, in the same order. Higher is better
+    fn tell(&mut self, fitness: &[f64], rng: &mut StdRng);
+
+    // Everything needed to continue after a restart
+    fn get_state(&self) -> Result<Value, serde_json::Error>;
+    fn set_state(&mut self, state: Value) -> Result<(), serde_json::Error>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptimizerType {
+    Genetic,
+    CmaEs,
+    ParticleSwarm,
+}
+
+impl OptimizerType {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            OptimizerType::Genetic => "ga",
+            OptimizerType::CmaEs => "cma-es",
+            OptimizerType::ParticleSwarm => "pso",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<OptimizerType> {
+        [OptimizerType::Genetic, OptimizerType::CmaEs, OptimizerType::ParticleSwarm]
+            .into_iter()
+            .find(|o| o.get_name() == name)
+    }
+
+    pub fn create(&self, config: &TrainConfig, rng: &mut StdRng) -> Box<dyn Optimizer> {
+        match self {
+            OptimizerType::Genetic => Box::new(Genetic::new(config, rng)),
+            OptimizerType::CmaEs => Box::new(CmaEs::new(config.pop_size)),
+            OptimizerType::ParticleSwarm => Box::new(ParticleSwarm::new(config.pop_size, rng)),
+        }
+    }
+}
+
+// Standard normal sample using the Box-Muller transform
+pub fn sample_normal(rng: &mut impl Rng) -> f64 {
+    let u1: f64 = rng.random_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+// The weights for a point in the search space, scaled to length 1 like the GA does
+pub fn to_weights(values: &[f64]) -> Weights {
+    let mut weights = Weights { values: values.to_vec() };
+    weights.normalize();
+    weights
+}
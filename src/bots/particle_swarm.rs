@@ -0,0 +1,103 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    optimizer::{to_weights, Optimizer},
+    weights::{Weights, ALL_FEATURES},
+};
+
+// Constriction coefficients from Clerc and Kennedy
+const INERTIA: f64 = 0.729;
+const PERSONAL_ATTRACTION: f64 = 1.49445;
+const GLOBAL_ATTRACTION: f64 = 1.49445;
+const MAX_VELOCITY: f64 = 0.5;
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Particle {
+    position: Vec<f64>,
+    velocity: Vec<f64>,
+    best_position: Vec<f64>,
+    best_fitness: f64,
+}
+
+/*
+    Particle swarm optimization. Every particle is a set of weights that
+    moves through the search space, pulled towards the best weights it has
+    found itself and the best weights found by the whole swarm.
+*/
+#[derive(Serialize, Deserialize)]
+pub struct ParticleSwarm {
+    particles: Vec<Particle>,
+    best_position: Vec<f64>,
+    best_fitness: f64,
+}
+
+impl ParticleSwarm {
+    pub fn new(swarm_size: usize, rng: &mut StdRng) -> Self {
+        let particles: Vec<Particle> = (0..swarm_size.max(2))
+            .map(|_| {
+                let position = Weights::random(rng).values;
+                Particle {
+                    velocity: ALL_FEATURES.iter().map(|_| rng.random_range(-0.1..0.1)).collect(),
+                    best_position: position.clone(),
+                    best_fitness: f64::NEG_INFINITY,
+                    position,
+                }
+            })
+            .collect();
+
+        Self {
+            best_position: particles[0].position.clone(),
+            best_fitness: f64::NEG_INFINITY,
+            particles,
+        }
+    }
+}
+
+impl Optimizer for ParticleSwarm {
+    fn get_name(&self) -> &'static str {
+        "pso"
+    }
+
+    fn ask(&mut self, _rng: &mut StdRng) -> Vec<Weights> {
+        self.particles.iter().map(|p| to_weights(&p.position)).collect()
+    }
+
+    fn tell(&mut self, fitness: &[f64], rng: &mut StdRng) {
+        for (particle, fitness) in self.particles.iter_mut().zip(fitness) {
+            if *fitness > particle.best_fitness {
+                particle.best_fitness = *fitness;
+                particle.best_position = particle.position.clone();
+            }
+            if *fitness > self.best_fitness {
+                self.best_fitness = *fitness;
+                self.best_position = particle.position.clone();
+            }
+        }
+
+        for particle in self.particles.iter_mut() {
+            for i in 0..particle.position.len() {
+                let personal = rng.random::<f64>() * PERSONAL_ATTRACTION
+                    * (particle.best_position[i] - particle.position[i]);
+                let global = rng.random::<f64>() * GLOBAL_ATTRACTION
+                    * (self.best_position[i] - particle.position[i]);
+
+                let velocity = (INERTIA * particle.velocity[i] + personal + global)
+                    .clamp(-MAX_VELOCITY, MAX_VELOCITY);
+                particle.velocity[i] = velocity;
+                particle.position[i] += velocity;
+            }
+        }
+    }
+
+    fn get_state(&self) -> Result<Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+
+    fn set_state(&mut self, state: Value) -> Result<(), serde_json::Error> {
+        *self = serde_json::from_value(state)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashSet;
+
+use super::{
+    bitboard::{BitBoard, FULL_ROW},
+    bot_input::BotInput,
+    move_generator::{find_placements_on, Placement},
+};
+use crate::{
+    board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS},
+    consts::{NEXT_QUEUE_SIZE, PC_TRAINING_HEIGHT},
+    Game, Piece, PieceType,
+};
+
+// One piece of a perfect clear
+#[derive(Clone)]
+pub struct PcStep {
+    pub hold: bool, // Hold before moving the piece
+    pub placement: Placement,
+}
+
+impl PcStep {
+    pub fn get_move_sequence(&self) -> Vec<BotInput> {
+        let mut moves = self.placement.move_sequence.clone();
+        if self.hold {
+            moves.insert(0, BotInput::Hold);
+        }
+        moves
+    }
+}
+
+// Pieces the search still has, in the order the game hands them out
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct PcState {
+    board: BitBoard,
+    height: usize, // Rows that have to be cleared, nothing may be placed above them
+    current: Option<PieceType>,
+    hold: Option<PieceType>,
+    next: usize, // Index of the next piece in the queue
+}
+
+/*
+    Searches for placements of the active, held and queued pieces that
+    leave the board empty. Depth first, trying the lowest height that can
+    be filled first. A search that has to place pieces above `max_height`
+    rows is never tried, and `max_nodes` limits how many states are looked
+    at so the bots can run it every piece.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PcSolver {
+    pub max_height: usize,
+    pub max_nodes: usize,
+    pub preview: usize, // Pieces of the queue the search may use, the ones a player can see
+}
+
+impl Default for PcSolver {
+    fn default() -> Self {
+        Self {
+            max_height: PC_TRAINING_HEIGHT,
+            max_nodes: 2000,
+            preview: NEXT_QUEUE_SIZE,
+        }
+    }
+}
+
+impl PcSolver {
+    pub fn solve(&self, game: &Game) -> Option<Vec<PcStep>> {
+        let board = BitBoard::from_board(&game.board);
+        let filled = count_filled(&board);
+        let max_height = board.get_max_height() as usize;
+        let queue: Vec<PieceType> = game.piece_queue.iter().take(self.preview).copied().collect();
+        let pieces = 1 + game.held_piece.is_some() as usize + queue.len();
+
+        let mut search = PcSearch {
+            queue,
+            failed: HashSet::new(),
+            nodes: 0,
+            max_nodes: self.max_nodes,
+        };
+
+        for height in max_height.max(1)..=self.max_height {
+            // Cheap checks first, the search itself is slow
+            let empty = height * BOARD_AMOUNT_COLUMNS - filled;
+            if !empty.is_multiple_of(4) || empty / 4 > pieces || !can_be_filled(&board, height) {
+                continue;
+            }
+
+            let state = PcState {
+                board,
+                height,
+                current: Some(game.active_piece.piece_type),
+                hold: game.held_piece,
+                next: 0,
+            };
+            // The active piece may already have been moved, so its placements start from where it is
+            let first = (&game.active_piece, game.t_spin, game.t_spin_mini);
+            if let Some(steps) = search.solve(state, Some(first), game.can_hold) {
+                return Some(steps);
+            }
+        }
+        None
+    }
+
+    // False if no pieces at all could make a perfect clear without going above `max_height`
+    pub fn is_possible(&self, board: &BitBoard) -> bool {
+        let filled = count_filled(board);
+        let max_height = board.get_max_height() as usize;
+        (max_height.max(1)..=self.max_height).any(|height| {
+            (height * BOARD_AMOUNT_COLUMNS - filled).is_multiple_of(4) && can_be_filled(board, height)
+        })
+    }
+}
+
+fn count_filled(board: &BitBoard) -> usize {
+    board.rows.iter().map(|row| row.count_ones() as usize).sum()
+}
+
+struct PcSearch {
+    queue: Vec<PieceType>,
+    failed: HashSet<PcState>, // States known to have no perfect clear
+    nodes: usize,
+    max_nodes: usize,
+}
+
+impl PcSearch {
+    fn solve(&mut self, state: PcState, first: Option<(&Piece, bool, bool)>, can_hold: bool) -> Option<Vec<PcStep>> {
+        if self.nodes >= self.max_nodes || self.failed.contains(&state) {
+            return None;
+        }
+        self.nodes += 1;
+        let current = state.current?;
+
+        // (hold, piece placed, piece held after, next piece, next queue index)
+        let mut options = vec![(false, current, state.hold, self.queue.get(state.next).copied(), state.next + 1)];
+        if can_hold {
+            match state.hold {
+                Some(held) if held != current => options.push((true, held, Some(current), self.queue.get(state.next).copied(), state.next + 1)),
+                Some(_) => (),
+                None => {
+                    if let Some(next) = self.queue.get(state.next) {
+                        options.push((true, *next, Some(current), self.queue.get(state.next + 1).copied(), state.next + 2));
+                    }
+                }
+            }
+        }
+
+        for (hold, piece_type, held, next_current, next) in options {
+            let (piece, t_spin, t_spin_mini) = match first {
+                Some((piece, t_spin, t_spin_mini)) if !hold => (piece.clone(), t_spin, t_spin_mini),
+                _ => (Piece::new(piece_type, 0), false, false),
+            };
+
+            let mut seen = HashSet::new();
+            for placement in find_placements_on(&state.board, &piece, t_spin, t_spin_mini) {
+                // Everything has to fit in the rows that are being cleared
+                if placement.get_cells().iter().any(|(r, _)| *r >= state.height as isize) {
+                    continue;
+                }
+                let mut board = placement.get_resulting_bitboard(&state.board);
+                if !seen.insert(board) {
+                    continue;
+                }
+                let cleared = board.clear_lines();
+                let step = PcStep { hold, placement };
+
+                if board.rows.iter().all(|row| *row == 0) {
+                    return Some(vec![step]);
+                }
+                let next_state = PcState {
+                    board,
+                    height: state.height - cleared,
+                    current: next_current,
+                    hold: held,
+                    next,
+                };
+                if !can_be_filled(&next_state.board, next_state.height) {
+                    continue;
+                }
+                if let Some(mut steps) = self.solve(next_state, None, true) {
+                    steps.insert(0, step);
+                    return Some(steps);
+                }
+            }
+        }
+
+        // Running out of nodes says nothing about the state
+        if self.nodes < self.max_nodes {
+            self.failed.insert(state);
+        }
+        None
+    }
+}
+
+/*
+    Every enclosed area of empty cells below `height` has to be filled by
+    whole pieces, so its size must be a multiple of 4. Line clears can join
+    areas later on, so this may skip a few perfect clears, but it cuts the
+    search down a lot.
+*/
+fn can_be_filled(board: &BitBoard, height: usize) -> bool {
+    let mut visited = [0u16; BOARD_AMOUNT_ROWS];
+    for r in 0..height {
+        let empty = !board.rows[r] & FULL_ROW & !visited[r];
+        for c in 0..BOARD_AMOUNT_COLUMNS {
+            if empty & (1 << c) == 0 || visited[r] & (1 << c) != 0 {
+                continue;
+            }
+
+            let mut size = 0usize;
+            let mut stack = vec![(r, c)];
+            visited[r] |= 1 << c;
+            while let Some((r, c)) = stack.pop() {
+                size += 1;
+                let neighbours = [
+                    (r.wrapping_sub(1), c),
+                    (r + 1, c),
+                    (r, c.wrapping_sub(1)),
+                    (r, c + 1),
+                ];
+                for (nr, nc) in neighbours {
+                    if nr < height
+                        && nc < BOARD_AMOUNT_COLUMNS
+                        && board.rows[nr] & (1 << nc) == 0
+                        && visited[nr] & (1 << nc) == 0
+                    {
+                        visited[nr] |= 1 << nc;
+                        stack.push((nr, nc));
+                    }
+                }
+            }
+            if !size.is_multiple_of(4) {
+                return false;
+            }
+        }
+    }
+    true
+}
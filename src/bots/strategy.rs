@@ -0,0 +1,74 @@
+use super::{
+    bitboard::BitBoard,
+    weights::{Feature, Weights},
+};
+use crate::Game;
+
+// Height of the stack, incoming garbage included, where the bot only tries to survive
+const DOWNSTACK_HEIGHT: usize = 10;
+// Opponent height where any attack might be enough to top them out
+const KILL_HEIGHT: usize = 15;
+
+/*
+    What the bot is going for right now. Picked before every piece from
+    the incoming garbage and the height of both boards, and applied as an
+    adjustment of the trained weights.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    Downstack, // The stack is dangerously high, clear lines and holes
+    BuildAttack, // Nothing is happening, build for Tetrises and T-spins to keep back to back
+    Cancel, // Garbage is coming or the opponent is almost dead, attack as soon as possible
+    PerfectClear, // The pieces in the queue can clear the whole board, see PcSolver
+}
+
+impl Strategy {
+    pub fn choose(game: &Game, opponent_height: Option<usize>) -> Strategy {
+        let incoming: usize = game.garbage_inbound.iter().map(|g| g.1).sum();
+        let height = BitBoard::from_board(&game.board).get_max_height() as usize;
+
+        if height + incoming >= DOWNSTACK_HEIGHT {
+            Strategy::Downstack
+        } else if incoming > 0 || opponent_height.is_some_and(|h| h >= KILL_HEIGHT) {
+            Strategy::Cancel
+        } else {
+            Strategy::BuildAttack
+        }
+    }
+
+    // Added to the trained weights while the strategy is active
+    pub fn get_adjustments(&self) -> &'static [(Feature, f64)] {
+        match self {
+            Strategy::Downstack => &[
+                (Feature::LinesCleared, 0.5),
+                (Feature::Holes, -0.2),
+                (Feature::MaxHeight, -0.2),
+                (Feature::WellDepth, -0.05),
+            ],
+            Strategy::BuildAttack => &[
+                (Feature::AggregateHeight, 0.3), // A higher stack is fine while building
+                (Feature::LinesCleared, -0.3), // Small clears waste the stack and break back to back
+                (Feature::AttackSent, 1.),
+                (Feature::BackToBack, 0.5),
+                (Feature::TSlots, 0.3),
+                (Feature::WellDepth, 0.2),
+                (Feature::Bumpiness, 0.1), // The well itself counts as bumpiness
+            ],
+            Strategy::Cancel => &[
+                (Feature::LinesCleared, 0.3),
+                (Feature::AttackSent, 0.6),
+                (Feature::Combo, 0.2),
+            ],
+            // Follows the solver instead of the weights
+            Strategy::PerfectClear => &[],
+        }
+    }
+
+    pub fn adjust(&self, weights: &Weights) -> Weights {
+        let mut adjusted = weights.clone();
+        for (feature, adjustment) in self.get_adjustments() {
+            adjusted.set(*feature, weights.get(*feature) + adjustment);
+        }
+        adjusted
+    }
+}
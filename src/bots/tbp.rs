@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+
+use super::move_outcome::SpinType;
+use crate::{
+    board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS},
+    Game, Piece, PieceType,
+};
+
+/*
+    Messages of the Tetris Bot Protocol, one JSON object per line over
+    stdin and stdout. The frontend is the side running the game, the bot
+    only suggests moves. See https://github.com/tetris-bot-protocol/tbp-spec
+*/
+
+// Frontend to bot
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FrontendMessage {
+    Rules {},
+    Start(TbpStart),
+    Stop,
+    Suggest,
+    Play {
+        #[serde(rename = "move")]
+        tbp_move: TbpMove,
+    },
+    NewPiece { piece: char },
+    Quit,
+}
+
+// Bot to frontend
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotMessage {
+    Info {
+        name: String,
+        version: String,
+        author: String,
+        features: Vec<String>,
+    },
+    Ready,
+    Error { reason: String },
+    Suggestion { moves: Vec<TbpMove> },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TbpStart {
+    pub hold: Option<char>,
+    pub queue: Vec<char>, // Starts with the current piece
+    pub combo: usize,
+    pub back_to_back: bool,
+    pub board: Vec<Vec<Option<char>>>, // 40 rows of 10 cells, bottom row first
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TbpMove {
+    pub location: TbpLocation,
+    pub spin: TbpSpin,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TbpLocation {
+    #[serde(rename = "type")]
+    pub piece: char,
+    pub orientation: Orientation,
+    pub x: isize, // Column of the SRS rotation center
+    pub y: isize, // Row of the SRS rotation center, 0 is the bottom row
+}
+
+// Same order as our rotation indices
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    North,
+    East,
+    South,
+    West,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TbpSpin {
+    None,
+    Mini,
+    Full,
+}
+
+impl Orientation {
+    pub fn from_rotation(rotation: usize) -> Orientation {
+        [Orientation::North, Orientation::East, Orientation::South, Orientation::West][rotation % 4]
+    }
+
+    pub fn get_rotation(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl TbpSpin {
+    pub fn from_spin_type(spin: SpinType) -> TbpSpin {
+        match spin {
+            SpinType::None => TbpSpin::None,
+            SpinType::TSpinMini => TbpSpin::Mini,
+            SpinType::TSpin => TbpSpin::Full,
+        }
+    }
+}
+
+// Garbage is sent as G
+pub fn piece_to_char(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::I => 'I',
+        PieceType::J => 'J',
+        PieceType::L => 'L',
+        PieceType::O => 'O',
+        PieceType::S => 'S',
+        PieceType::T => 'T',
+        PieceType::Z => 'Z',
+        PieceType::X => 'G',
+    }
+}
+
+pub fn char_to_piece(c: char) -> Option<PieceType> {
+    match c {
+        'I' => Some(PieceType::I),
+        'J' => Some(PieceType::J),
+        'L' => Some(PieceType::L),
+        'O' => Some(PieceType::O),
+        'S' => Some(PieceType::S),
+        'T' => Some(PieceType::T),
+        'Z' => Some(PieceType::Z),
+        'G' => Some(PieceType::X),
+        _ => None,
+    }
+}
+
+// Minos of the north orientation relative to the rotation center (x, y)
+fn get_tbp_minos(piece_type: PieceType) -> [(isize, isize); 4] {
+    match piece_type {
+        PieceType::I => [(-1, 0), (0, 0), (1, 0), (2, 0)],
+        PieceType::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+        PieceType::T => [(-1, 0), (0, 0), (1, 0), (0, 1)],
+        PieceType::L => [(-1, 0), (0, 0), (1, 0), (1, 1)],
+        PieceType::J => [(-1, 0), (0, 0), (1, 0), (-1, 1)],
+        PieceType::S => [(-1, 0), (0, 0), (0, 1), (1, 1)],
+        PieceType::Z => [(-1, 1), (0, 1), (0, 0), (1, 0)],
+        PieceType::X => [(0, 0); 4],
+    }
+}
+
+// Board cells (R, C) covered by a piece at a TBP location, sorted
+fn get_location_cells(piece_type: PieceType, orientation: Orientation, x: isize, y: isize) -> [(isize, isize); 4] {
+    let mut cells = get_tbp_minos(piece_type).map(|(dx, dy)| {
+        // Rotating clockwise turns (x, y) into (y, -x)
+        let (dx, dy) = match orientation {
+            Orientation::North => (dx, dy),
+            Orientation::East => (dy, -dx),
+            Orientation::South => (-dx, -dy),
+            Orientation::West => (-dy, dx),
+        };
+        (y + dy, x + dx)
+    });
+    cells.sort();
+    cells
+}
+
+impl TbpLocation {
+    /*
+        Our midpoints are the SRS rotation centers except for I and O,
+        which are offset by one cell in some rotations. Both describe the
+        same shape, so the difference is found by comparing the lowest cell.
+    */
+    pub fn from_piece(piece: &Piece) -> TbpLocation {
+        let orientation = Orientation::from_rotation(piece.rotation);
+        let mut ours: Vec<(isize, isize)> = piece
+            .block_positions
+            .iter()
+            .map(|(dr, dc)| (piece.midpoint.0 + dr, piece.midpoint.1 + dc))
+            .collect();
+        ours.sort();
+        let theirs = get_location_cells(piece.piece_type, orientation, 0, 0);
+
+        TbpLocation {
+            piece: piece_to_char(piece.piece_type),
+            orientation,
+            x: ours[0].1 - theirs[0].1,
+            y: ours[0].0 - theirs[0].0,
+        }
+    }
+
+    pub fn get_piece_type(&self) -> Option<PieceType> {
+        char_to_piece(self.piece).filter(|p| *p != PieceType::X)
+    }
+
+    pub fn get_cells(&self) -> Option<[(isize, isize); 4]> {
+        let piece_type = self.get_piece_type()?;
+        Some(get_location_cells(piece_type, self.orientation, self.x, self.y))
+    }
+
+    pub fn to_piece(self) -> Option<Piece> {
+        let piece_type = self.get_piece_type()?;
+        let rotation = self.orientation.get_rotation();
+        let minos = Piece::get_minos(piece_type, rotation);
+
+        let mut ours = minos.to_vec();
+        ours.sort();
+        let theirs = self.get_cells()?;
+
+        Some(Piece {
+            piece_type,
+            midpoint: (theirs[0].0 - ours[0].0, theirs[0].1 - ours[0].1),
+            block_positions: minos.to_vec(),
+            rotation,
+        })
+    }
+}
+
+impl TbpStart {
+    pub fn from_game(game: &Game) -> TbpStart {
+        let mut queue = vec![piece_to_char(game.active_piece.piece_type)];
+        queue.extend(game.piece_queue.iter().map(|p| piece_to_char(*p)));
+
+        TbpStart {
+            hold: game.held_piece.map(piece_to_char),
+            queue,
+            combo: game.combo,
+            back_to_back: game.back_to_back,
+            board: game
+                .board
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.map(piece_to_char)).collect())
+                .collect(),
+        }
+    }
+
+    // Unknown cells count as garbage, missing rows as empty
+    pub fn get_board(&self) -> [[Option<PieceType>; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS] {
+        let mut board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        for (row, cells) in board.iter_mut().zip(self.board.iter()) {
+            for (cell, c) in row.iter_mut().zip(cells.iter()) {
+                *cell = c.map(|c| char_to_piece(c).unwrap_or(PieceType::X));
+            }
+        }
+        board
+    }
+}
@@ -0,0 +1,248 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use super::{
+    bot_input::BotInput,
+    move_generator::{find_placements, Placement},
+    tbp::{piece_to_char, BotMessage, FrontendMessage, TbpLocation, TbpMove, TbpSpin, TbpStart},
+};
+use crate::Game;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClientState {
+    WaitingForInfo,
+    WaitingForReady,
+    Idle, // Ready for the next suggest
+    Thinking, // Waiting for a suggestion
+    Failed, // The bot errored or quit, see TbpClient::has_failed
+}
+
+/*
+    Plays the bot side of versus with an external TBP bot. Never blocks,
+    the replies of the bot are read on another thread and polled every
+    frame. Whenever the board changes in a way the bot can't know about,
+    like incoming garbage, the bot is stopped and started again.
+*/
+pub struct TbpClient {
+    pub name: Option<String>,
+    pub state: ClientState,
+    child: Option<Child>,
+    writer: Box<dyn Write + Send>,
+    messages: Receiver<BotMessage>,
+    started: bool,
+    revealed: usize, // Pieces the bot has been told about since the latest start
+    garbage_received: usize,
+    out_of_sync: bool, // The latest move wasn't the one the bot suggested
+}
+
+impl TbpClient {
+    // The command is split on whitespace, the first part is the program
+    pub fn spawn(command: &str) -> io::Result<TbpClient> {
+        let mut parts = command.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or(io::Error::new(io::ErrorKind::InvalidInput, "Empty TBP bot command"))?;
+
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let mut client = TbpClient::from_streams(BufReader::new(stdout), stdin);
+        client.child = Some(child);
+        Ok(client)
+    }
+
+    pub fn from_streams(reader: impl BufRead + Send + 'static, writer: impl Write + Send + 'static) -> TbpClient {
+        let (sender, messages) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                // Unknown messages are ignored, like the spec says
+                if let Ok(message) = serde_json::from_str::<BotMessage>(&line) {
+                    if sender.send(message).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        TbpClient {
+            name: None,
+            state: ClientState::WaitingForInfo,
+            child: None,
+            writer: Box::new(writer),
+            messages,
+            started: false,
+            revealed: 0,
+            garbage_received: 0,
+            out_of_sync: false,
+        }
+    }
+
+    pub fn has_failed(&self) -> bool {
+        self.state == ClientState::Failed
+    }
+
+    fn send(&mut self, message: &FrontendMessage) {
+        let json = serde_json::to_string(message).unwrap();
+        if writeln!(self.writer, "{}", json).and_then(|_| self.writer.flush()).is_err() {
+            self.state = ClientState::Failed;
+        }
+    }
+
+    fn receive(&mut self) -> Option<BotMessage> {
+        match self.messages.try_recv() {
+            Ok(BotMessage::Error { reason }) => {
+                eprintln!("The TBP bot stopped: {}", reason);
+                self.state = ClientState::Failed;
+                None
+            }
+            Ok(message) => Some(message),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.state = ClientState::Failed;
+                None
+            }
+        }
+    }
+
+    /*
+        Moves the handshake and the current suggestion along. Returns the
+        inputs for the active piece once the bot has picked a move, None
+        while it is still thinking.
+    */
+    pub fn poll_move_sequence(&mut self, game: &Game) -> Option<Vec<BotInput>> {
+        loop {
+            match self.state {
+                ClientState::WaitingForInfo => {
+                    if let Some(BotMessage::Info { name, .. }) = self.receive() {
+                        self.name = Some(name);
+                        self.send(&FrontendMessage::Rules {});
+                        self.state = ClientState::WaitingForReady;
+                        continue;
+                    }
+                }
+                ClientState::WaitingForReady => {
+                    if let Some(BotMessage::Ready) = self.receive() {
+                        self.state = ClientState::Idle;
+                        continue;
+                    }
+                }
+                ClientState::Idle => {
+                    self.request_suggestion(game);
+                    continue;
+                }
+                ClientState::Thinking => {
+                    if let Some(BotMessage::Suggestion { moves }) = self.receive() {
+                        self.state = ClientState::Idle;
+                        return Some(self.play_suggestion(game, &moves));
+                    }
+                }
+                ClientState::Failed => (),
+            }
+            return None;
+        }
+    }
+
+    // All pieces of the game the bot could know about: placed, held, active and the queue
+    fn get_revealed(game: &Game) -> usize {
+        game.pieces + game.held_piece.is_some() as usize + 1 + game.piece_queue.len()
+    }
+
+    fn request_suggestion(&mut self, game: &Game) {
+        let revealed = Self::get_revealed(game);
+
+        if !self.started
+            || self.out_of_sync
+            || game.garbage_received != self.garbage_received
+            || revealed < self.revealed // The game was reset
+        {
+            if self.started {
+                self.send(&FrontendMessage::Stop);
+            }
+            self.send(&FrontendMessage::Start(TbpStart::from_game(game)));
+            self.started = true;
+            self.out_of_sync = false;
+            self.garbage_received = game.garbage_received;
+        } else {
+            // New pieces are always at the end of the queue
+            let new_pieces = revealed - self.revealed;
+            for i in game.piece_queue.len().saturating_sub(new_pieces)..game.piece_queue.len() {
+                let piece = piece_to_char(game.piece_queue[i]);
+                self.send(&FrontendMessage::NewPiece { piece });
+            }
+        }
+        self.revealed = revealed;
+
+        self.send(&FrontendMessage::Suggest);
+        if self.state != ClientState::Failed {
+            self.state = ClientState::Thinking;
+        }
+    }
+
+    // Plays the first suggested move the piece can reach, or hard drops if there is none
+    fn play_suggestion(&mut self, game: &Game, moves: &[TbpMove]) -> Vec<BotInput> {
+        for tbp_move in moves {
+            if let Some((held, placement)) = find_move(game, tbp_move) {
+                let played = TbpMove {
+                    location: TbpLocation::from_piece(&placement.piece),
+                    spin: TbpSpin::from_spin_type(placement.spin),
+                };
+                self.send(&FrontendMessage::Play { tbp_move: played });
+
+                let mut inputs = placement.move_sequence;
+                if held {
+                    inputs.insert(0, BotInput::Hold);
+                }
+                return inputs;
+            }
+        }
+
+        self.out_of_sync = true;
+        vec![BotInput::HardDrop]
+    }
+}
+
+// The placement covering the same cells as the move, preferring the same spin. True if it needs a hold first
+pub fn find_move(game: &Game, tbp_move: &TbpMove) -> Option<(bool, Placement)> {
+    let piece_type = tbp_move.location.get_piece_type()?;
+    let cells = tbp_move.location.get_cells()?;
+
+    let held = piece_type != game.active_piece.piece_type;
+    let placements = if held {
+        let hold_type = game.held_piece.or(game.piece_queue.front().copied());
+        if !game.can_hold || hold_type != Some(piece_type) {
+            return None;
+        }
+        let mut game = game.clone();
+        game.hold();
+        find_placements(&game)
+    } else {
+        find_placements(game)
+    };
+
+    let mut matching: Vec<Placement> = placements.into_iter().filter(|p| p.get_cells() == cells).collect();
+    let same_spin = matching
+        .iter()
+        .position(|p| TbpSpin::from_spin_type(p.spin) == tbp_move.spin)
+        .unwrap_or(0);
+    (!matching.is_empty()).then(|| (held, matching.swap_remove(same_spin)))
+}
+
+impl Drop for TbpClient {
+    fn drop(&mut self) {
+        self.send(&FrontendMessage::Quit);
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+
+use super::{
+    bot::Bot,
+    bot_input::BotInput,
+    difficulty::BOT_DIFFICULTIES,
+    tbp::{char_to_piece, BotMessage, FrontendMessage, TbpLocation, TbpMove, TbpSpin, TbpStart},
+    weights::Weights,
+};
+use crate::{clock::Clock, consts::GameMode, Piece, PieceType};
+
+/*
+    Our bot as a TBP bot, so it can be used by other frontends. The game
+    of the bot only mirrors what the frontend tells it, the pieces come
+    from start and new_piece instead of the randomizer.
+*/
+pub struct TbpServer {
+    pub bot: Bot,
+    pub current: Option<PieceType>, // None until the frontend reveals a piece
+    pub queue: VecDeque<PieceType>,
+    pub running: bool, // Between start and stop
+}
+
+impl Default for TbpServer {
+    fn default() -> Self {
+        Self::new(Weights::load())
+    }
+}
+
+impl TbpServer {
+    // Plans as far ahead as the hardest difficulty, without its speed limits
+    pub fn new(weights: Weights) -> Self {
+        let mut bot = Bot::with_weights(weights);
+        bot.search_depth = BOT_DIFFICULTIES[BOT_DIFFICULTIES.len() - 1].search_depth;
+        bot.game.clock = Clock::manual();
+        // Marathon and 40 lines have their own rules for line clears
+        bot.game.gamemode = GameMode::Versus;
+
+        Self {
+            bot,
+            current: None,
+            queue: VecDeque::new(),
+            running: false,
+        }
+    }
+
+    pub fn get_info() -> BotMessage {
+        BotMessage::Info {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            author: "davidn5, mja8".to_string(),
+            features: Vec::new(),
+        }
+    }
+
+    // The reply to a message, if it needs one
+    pub fn handle(&mut self, message: FrontendMessage) -> Option<BotMessage> {
+        match message {
+            FrontendMessage::Rules {} => return Some(BotMessage::Ready),
+            FrontendMessage::Start(start) => self.start(&start),
+            FrontendMessage::Stop => self.running = false,
+            FrontendMessage::Suggest if self.running => {
+                return Some(BotMessage::Suggestion { moves: self.suggest() })
+            }
+            FrontendMessage::Play { tbp_move } if self.running => self.play(&tbp_move),
+            FrontendMessage::NewPiece { piece } if self.running => {
+                if let Some(piece_type) = char_to_piece(piece).filter(|p| *p != PieceType::X) {
+                    match self.current {
+                        Some(_) => self.queue.push_back(piece_type),
+                        None => self.current = Some(piece_type),
+                    }
+                }
+            }
+            _ => (),
+        }
+        None
+    }
+
+    fn start(&mut self, start: &TbpStart) {
+        let mut pieces = start.queue.iter().filter_map(|c| char_to_piece(*c));
+        self.current = pieces.next();
+        self.queue = pieces.collect();
+
+        let game = &mut self.bot.game;
+        game.board = start.get_board();
+        game.held_piece = start.hold.and_then(char_to_piece);
+        // TBP counts the first clear as combo 1, we count it as 0
+        game.prev_clear = start.combo > 0;
+        game.combo = start.combo.saturating_sub(1);
+        game.back_to_back = start.back_to_back;
+        game.latest_clear_difficult = start.back_to_back;
+        self.running = true;
+    }
+
+    // Puts the known pieces in the game of the bot. TBP allows holding every piece
+    fn sync_game(&mut self) -> bool {
+        let Some(current) = self.current else {
+            return false;
+        };
+        let game = &mut self.bot.game;
+        game.active_piece = Piece::new(current, 0);
+        game.piece_queue = self.queue.clone();
+        game.can_hold = true;
+        game.t_spin = false;
+        game.t_spin_mini = false;
+        game.game_over = false;
+        true
+    }
+
+    /*
+        Plays the inputs of the best move on a copy of the game, without
+        the hard drop, and reads where the piece ends up. Holding may take
+        a piece from the queue, which is fine since the copy is thrown away.
+    */
+    pub fn suggest(&mut self) -> Vec<TbpMove> {
+        if !self.sync_game() {
+            return Vec::new();
+        }
+
+        let mut bot = self.bot.clone();
+        for input in self.bot.get_best_move_sequence() {
+            if input == BotInput::HardDrop {
+                break;
+            }
+            bot.apply_input(input);
+        }
+        while bot.game.move_piece(0, -1) {}
+
+        let spin = if bot.game.t_spin_mini {
+            TbpSpin::Mini
+        } else if bot.game.t_spin {
+            TbpSpin::Full
+        } else {
+            TbpSpin::None
+        };
+        vec![TbpMove {
+            location: TbpLocation::from_piece(&bot.game.active_piece),
+            spin,
+        }]
+    }
+
+    // Moves of a piece other than the current one are played after holding
+    pub fn play(&mut self, tbp_move: &TbpMove) {
+        let (Some(piece), Some(current)) = (tbp_move.location.to_piece(), self.current) else {
+            return;
+        };
+
+        let game = &mut self.bot.game;
+        // Holding into an empty hold plays the next piece
+        if piece.piece_type != current && game.held_piece.replace(current).is_none() {
+            self.queue.pop_front();
+        }
+
+        game.active_piece = piece;
+        game.t_spin = tbp_move.spin != TbpSpin::None;
+        game.t_spin_mini = tbp_move.spin == TbpSpin::Mini;
+        game.place_piece();
+        // Attack is up to the frontend
+        game.garbage_outbound.clear();
+
+        self.current = self.queue.pop_front();
+    }
+}
+
+fn send(output: &mut impl Write, message: &BotMessage) -> io::Result<()> {
+    writeln!(output, "{}", serde_json::to_string(message)?)?;
+    output.flush()
+}
+
+// Talks TBP until quit or the end of the input. Messages that can't be read are ignored, like the spec says
+pub fn run_tbp_server(input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut server = TbpServer::default();
+    send(&mut output, &TbpServer::get_info())?;
+
+    for line in input.lines() {
+        let Ok(message) = serde_json::from_str::<FrontendMessage>(&line?) else {
+            continue;
+        };
+        if message == FrontendMessage::Quit {
+            break;
+        }
+        if let Some(reply) = server.handle(message) {
+            send(&mut output, &reply)?;
+        }
+    }
+    Ok(())
+}
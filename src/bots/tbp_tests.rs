@@ -0,0 +1,308 @@
+#[cfg(test)]
+mod tests {
+    use std::io::{pipe, BufReader, Cursor};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS},
+        bots::{
+            bot::Bot,
+            bot_input::BotInput,
+            tbp::{BotMessage, FrontendMessage, Orientation, TbpLocation, TbpMove, TbpSpin, TbpStart},
+            tbp_client::{find_move, TbpClient},
+            tbp_server::{run_tbp_server, TbpServer},
+            weights::Weights,
+        },
+        clock::Clock,
+        consts::GameMode,
+        Game, Piece, PieceType,
+    };
+
+    const ALL_PIECES: [PieceType; 7] = [
+        PieceType::I,
+        PieceType::J,
+        PieceType::L,
+        PieceType::O,
+        PieceType::S,
+        PieceType::T,
+        PieceType::Z,
+    ];
+
+    fn get_piece_cells(piece: &Piece) -> Vec<(isize, isize)> {
+        let mut cells: Vec<(isize, isize)> = piece
+            .block_positions
+            .iter()
+            .map(|(dr, dc)| (piece.midpoint.0 + dr, piece.midpoint.1 + dc))
+            .collect();
+        cells.sort();
+        cells
+    }
+
+    // Versus game on an empty board with a manual clock and the countdown done
+    fn headless_bot(seed: u64) -> Bot {
+        let mut bot = Bot::with_weights(Weights::default());
+        bot.game.clock = Clock::manual();
+        bot.game.gamemode = GameMode::Versus;
+        bot.game.reset_game(Some(seed));
+        bot.game.countdown_start = None;
+        bot
+    }
+
+    fn wait_for_moves(client: &mut TbpClient, game: &Game) -> Vec<BotInput> {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(30) {
+            if let Some(moves) = client.poll_move_sequence(game) {
+                return moves;
+            }
+            assert!(!client.has_failed());
+            thread::sleep(Duration::from_millis(1));
+        }
+        panic!("The TBP bot never suggested a move");
+    }
+
+    #[test]
+    fn test_tbp_messages() {
+        assert_eq!(serde_json::to_string(&FrontendMessage::Suggest).unwrap(), r#"{"type":"suggest"}"#);
+        assert_eq!(serde_json::to_string(&BotMessage::Ready).unwrap(), r#"{"type":"ready"}"#);
+
+        // Fields we don't know about are fine
+        let rules: FrontendMessage = serde_json::from_str(r#"{"type":"rules","randomizer":"seven_bag"}"#).unwrap();
+        assert_eq!(rules, FrontendMessage::Rules {});
+
+        let play: FrontendMessage = serde_json::from_str(
+            r#"{"type":"play","move":{"location":{"type":"T","orientation":"east","x":4,"y":1},"spin":"full"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            play,
+            FrontendMessage::Play {
+                tbp_move: TbpMove {
+                    location: TbpLocation {
+                        piece: 'T',
+                        orientation: Orientation::East,
+                        x: 4,
+                        y: 1,
+                    },
+                    spin: TbpSpin::Full,
+                },
+            }
+        );
+
+        let new_piece: FrontendMessage = serde_json::from_str(r#"{"type":"new_piece","piece":"L"}"#).unwrap();
+        assert_eq!(new_piece, FrontendMessage::NewPiece { piece: 'L' });
+    }
+
+    #[test]
+    fn test_tbp_location() {
+        // Flat I on the floor, rotating around the second mino from the left
+        let location = TbpLocation {
+            piece: 'I',
+            orientation: Orientation::North,
+            x: 4,
+            y: 0,
+        };
+        assert_eq!(location.get_cells(), Some([(0, 3), (0, 4), (0, 5), (0, 6)]));
+
+        // Every rotation converts to a location and back to the same cells
+        for piece_type in ALL_PIECES {
+            for rotation in 0..4 {
+                let mut piece = Piece::new(piece_type, rotation);
+                piece.midpoint = (10, 4);
+                piece.rotation = rotation;
+
+                let location = TbpLocation::from_piece(&piece);
+                assert_eq!(location.orientation.get_rotation(), rotation);
+                assert_eq!(location.get_cells().unwrap().to_vec(), get_piece_cells(&piece));
+
+                let converted = location.to_piece().unwrap();
+                assert_eq!(converted.rotation, rotation);
+                assert_eq!(get_piece_cells(&converted), get_piece_cells(&piece));
+            }
+        }
+
+        // JLSTZ rotate around the same mino in both
+        let t = Piece::new(PieceType::T, 0);
+        let location = TbpLocation::from_piece(&t);
+        assert_eq!((location.y, location.x), t.midpoint);
+    }
+
+    #[test]
+    fn test_tbp_start_from_game() {
+        let mut game = headless_bot(3).game;
+        game.board[0][0] = Some(PieceType::X);
+        game.held_piece = Some(PieceType::S);
+
+        let start = TbpStart::from_game(&game);
+        assert_eq!(start.hold, Some('S'));
+        assert_eq!(start.queue.len(), game.piece_queue.len() + 1);
+        assert_eq!(start.board.len(), BOARD_AMOUNT_ROWS);
+        assert_eq!(start.board[0][0], Some('G'));
+        assert_eq!(start.get_board(), game.board);
+    }
+
+    #[test]
+    fn test_tbp_server() {
+        let mut server = TbpServer::new(Weights::default());
+        assert_eq!(server.handle(FrontendMessage::Rules {}), Some(BotMessage::Ready));
+
+        // Nothing to suggest before the game starts
+        assert_eq!(server.handle(FrontendMessage::Suggest), None);
+
+        let mut start = TbpStart {
+            hold: None,
+            queue: vec!['T', 'I', 'O', 'L'],
+            combo: 0,
+            back_to_back: false,
+            board: vec![vec![None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS],
+        };
+        server.handle(FrontendMessage::Start(start.clone()));
+
+        // One piece is left over, either in hold or as the current piece
+        for _ in 0..3 {
+            let Some(BotMessage::Suggestion { moves }) = server.handle(FrontendMessage::Suggest) else {
+                panic!("Expected a suggestion");
+            };
+            assert_eq!(moves.len(), 1);
+            let cells = moves[0].location.get_cells().unwrap();
+            assert!(cells.iter().all(|(r, c)| *r >= 0 && (0..BOARD_AMOUNT_COLUMNS as isize).contains(c)));
+            server.handle(FrontendMessage::Play { tbp_move: moves[0] });
+        }
+        let filled = server.bot.game.board.iter().flatten().filter(|c| c.is_some()).count();
+        assert_eq!(filled + BOARD_AMOUNT_COLUMNS * server.bot.game.lines, 12);
+        assert_eq!(server.bot.game.pieces, 3);
+        assert!(server.queue.is_empty());
+
+        // Pieces revealed later are played in order
+        start.queue.clear();
+        server.handle(FrontendMessage::Stop);
+        server.handle(FrontendMessage::Start(start));
+        assert_eq!(server.handle(FrontendMessage::Suggest), Some(BotMessage::Suggestion { moves: vec![] }));
+        server.handle(FrontendMessage::NewPiece { piece: 'L' });
+        server.handle(FrontendMessage::NewPiece { piece: 'J' });
+        assert_eq!(server.current, Some(PieceType::L));
+        assert_eq!(server.queue, [PieceType::J]);
+    }
+
+    #[test]
+    fn test_tbp_server_streams() {
+        let input = [
+            r#"{"type":"rules"}"#,
+            "not json",
+            r#"{"type":"start","hold":null,"queue":["O"],"combo":0,"back_to_back":false,"board":[]}"#,
+            r#"{"type":"suggest"}"#,
+            r#"{"type":"quit"}"#,
+            r#"{"type":"suggest"}"#,
+        ]
+        .join("\n");
+        let mut output = Vec::new();
+        run_tbp_server(Cursor::new(input), &mut output).unwrap();
+
+        let replies: Vec<BotMessage> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(replies.len(), 3);
+        assert!(matches!(replies[0], BotMessage::Info { .. }));
+        assert_eq!(replies[1], BotMessage::Ready);
+        let BotMessage::Suggestion { moves } = &replies[2] else {
+            panic!("Expected a suggestion");
+        };
+        assert_eq!(moves[0].location.piece, 'O');
+    }
+
+    #[test]
+    fn test_find_move() {
+        let mut game = headless_bot(0).game;
+        game.active_piece = Piece::new(PieceType::T, 0);
+        game.held_piece = Some(PieceType::I);
+
+        let flat_i = TbpMove {
+            location: TbpLocation {
+                piece: 'I',
+                orientation: Orientation::North,
+                x: 1,
+                y: 0,
+            },
+            spin: TbpSpin::None,
+        };
+        let (held, placement) = find_move(&game, &flat_i).unwrap();
+        assert!(held);
+        assert_eq!(placement.get_cells(), [(0, 0), (0, 1), (0, 2), (0, 3)]);
+
+        // The piece can't go through the floor, and can't be held twice
+        let mut below = flat_i;
+        below.location.y = -1;
+        assert!(find_move(&game, &below).is_none());
+        game.can_hold = false;
+        assert!(find_move(&game, &flat_i).is_none());
+    }
+
+    // Our own bot on the other end, through the same kind of pipes as a child process
+    #[test]
+    fn test_tbp_client_with_our_bot() {
+        let (client_reader, server_writer) = pipe().unwrap();
+        let (server_reader, client_writer) = pipe().unwrap();
+        let server = thread::spawn(move || run_tbp_server(BufReader::new(server_reader), server_writer));
+
+        let mut client = TbpClient::from_streams(BufReader::new(client_reader), client_writer);
+        let mut bot = headless_bot(7);
+
+        for i in 0..20 {
+            // Garbage makes the client start the bot again with the new board
+            if i == 10 {
+                bot.game.add_garbage_row(0);
+                bot.game.add_garbage_row(0);
+            }
+            for input in wait_for_moves(&mut client, &bot.game) {
+                bot.apply_input(input);
+            }
+        }
+        assert_eq!(client.name.as_deref(), Some(env!("CARGO_PKG_NAME")));
+        assert_eq!(bot.game.pieces, 20);
+        assert!(!bot.game.game_over);
+
+        // Dropping the client makes the bot quit
+        drop(client);
+        server.join().unwrap().unwrap();
+    }
+
+    // A stub bot that always suggests a flat I on the left
+    #[cfg(unix)]
+    #[test]
+    fn test_tbp_client_with_stub_process() {
+        let script = std::env::temp_dir().join(format!("tbp_stub_{}.sh", std::process::id()));
+        std::fs::write(
+            &script,
+            r#"echo '{"type":"info","name":"stub","version":"1","author":"test","features":[]}'
+while read line; do
+    case "$line" in
+        *'"rules"'*) echo '{"type":"ready"}' ;;
+        *'"suggest"'*) echo '{"type":"suggestion","moves":[{"location":{"type":"I","orientation":"north","x":1,"y":0},"spin":"none"}]}' ;;
+        *'"quit"'*) exit 0 ;;
+    esac
+done
+"#,
+        )
+        .unwrap();
+
+        let mut client = TbpClient::spawn(&format!("sh {}", script.display())).unwrap();
+        let mut bot = headless_bot(1);
+        bot.game.active_piece = Piece::new(PieceType::I, 0);
+
+        for input in wait_for_moves(&mut client, &bot.game) {
+            bot.apply_input(input);
+        }
+        assert_eq!(client.name.as_deref(), Some("stub"));
+        assert!((0..4).all(|c| bot.game.board[0][c] == Some(PieceType::I)));
+
+        // A move that can't be reached is hard dropped instead
+        bot.game.active_piece = Piece::new(PieceType::O, 0);
+        bot.game.held_piece = Some(PieceType::T);
+        assert_eq!(wait_for_moves(&mut client, &bot.game), vec![BotInput::HardDrop]);
+
+        drop(client);
+        std::fs::remove_file(script).unwrap();
+    }
+}
@@ -1,68 +1,301 @@
-use rand::seq::IndexedRandom;
-use rand::Rng;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use csv::Writer;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use super::bot::Bot;
+use super::optimizer::OptimizerType;
+use super::versus_training::{play_match, play_matches, VersusStats};
+use super::weights::{Weights, ALL_FEATURES, BOT_WEIGHTS_PATH};
 
-const POP_SIZE: usize = 100;
-const ITERATIONS: usize = 10;
-const TOURNAMENT_SIZE: usize = 100;
-const OFFSPRING_COUNT: usize = (POP_SIZE as f64 * 0.3) as usize;
-const GAMES_PER_EVALUATION: usize = 25;
-const STEPS_PER_GAME: i32 = 800;
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+const LOG_FILE: &str = "generations.csv";
 
-pub fn train_ai() {
-    let _ = ThreadPoolBuilder::new()
-        .num_threads(4)
-        .build_global()
-        .expect("Failed to build thread pool");
+// What the fitness of a candidate is measured by
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FitnessType {
+    Lines, // Lines cleared in solo games
+    Versus, // Matches against a fixed opponent
+    SelfPlay, // Matches against other candidates of the same generation
+}
 
-    let mut population: Vec<Bot> = (0..POP_SIZE)
-        .map(|_| Bot::with_random_unit_weights())
-        .collect();
+impl FitnessType {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            FitnessType::Lines => "lines",
+            FitnessType::Versus => "versus",
+            FitnessType::SelfPlay => "self-play",
+        }
+    }
 
-    for generation in 0..ITERATIONS {
-        println!("Generation {} ...", generation);
+    pub fn from_name(name: &str) -> Option<FitnessType> {
+        [FitnessType::Lines, FitnessType::Versus, FitnessType::SelfPlay]
+            .into_iter()
+            .find(|f| f.get_name() == name)
+    }
+}
 
-        population.par_iter_mut().for_each(|bot| {
-            bot.fitness = (0..GAMES_PER_EVALUATION)
-                .map(|_| bot.run_game_without_ui(STEPS_PER_GAME))
-                .sum();
-        });
+// Settings of a training run, read from the arguments after --train
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrainConfig {
+    pub optimizer: OptimizerType,
+    pub fitness: FitnessType,
+    pub opponent: Option<PathBuf>, // Weights of the versus opponent, the default weights if None
+    pub pop_size: usize, // Candidates evaluated per generation
+    pub generations: usize,
+    pub tournament_size: usize, // GA only
+    pub offspring_ratio: f64, // GA only, part of the population replaced every generation
+    pub games_per_evaluation: usize,
+    pub steps_per_game: i32, // Pieces per game, or per bot in a match
+    pub threads: usize,
+    pub seed: u64, // Seeds both the evaluation games and the evolution
+    pub output_dir: PathBuf, // Checkpoint and logs
+    pub weights_path: PathBuf, // Where the best weights end up
+    pub resume: bool,
+}
 
-        population.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+impl Default for TrainConfig {
+    fn default() -> Self {
+        Self {
+            optimizer: OptimizerType::Genetic,
+            fitness: FitnessType::Lines,
+            opponent: None,
+            pop_size: 100,
+            generations: 10,
+            tournament_size: 100,
+            offspring_ratio: 0.3,
+            games_per_evaluation: 25,
+            steps_per_game: 800,
+            threads: 4,
+            seed: 0,
+            output_dir: PathBuf::from("res/training"),
+            weights_path: PathBuf::from(BOT_WEIGHTS_PATH),
+            resume: false,
+        }
+    }
+}
 
-        println!("Top fitness: {}", population[0].fitness);
-        println!("Best weights: {:?}", population[0].weights);
-        println!("Amount of steps: {}", population[0].game_steps);
+impl TrainConfig {
+    /*
+        Reads options like `--pop-size 50`. Options that aren't given keep
+        their default value. Unknown options are an error so typos don't
+        silently start a long run with the wrong settings.
+    */
+    pub fn from_args(args: &[String]) -> Result<TrainConfig, String> {
+        let mut config = TrainConfig::default();
+        let mut args = args.iter();
 
-        let mut rng = rand::rng();
-        let mut offspring: Vec<Bot> = vec![];
+        while let Some(arg) = args.next() {
+            if arg == "--resume" {
+                config.resume = true;
+                continue;
+            }
 
-        while offspring.len() < OFFSPRING_COUNT {
-            let tournament: Vec<&Bot> = population
-                .choose_multiple(&mut rng, TOURNAMENT_SIZE)
-                .collect();
+            let value = args.next().ok_or(format!("Missing value for {}", arg))?;
+            match arg.as_str() {
+                "--optimizer" => {
+                    config.optimizer = OptimizerType::from_name(value)
+                        .ok_or(format!("Unknown optimizer {}, use ga, cma-es or pso", value))?
+                }
+                "--fitness" => {
+                    config.fitness = FitnessType::from_name(value)
+                        .ok_or(format!("Unknown fitness {}, use lines, versus or self-play", value))?
+                }
+                "--opponent" => config.opponent = Some(PathBuf::from(value)),
+                "--pop-size" => config.pop_size = parse_value(arg, value)?,
+                "--generations" => config.generations = parse_value(arg, value)?,
+                "--tournament-size" => config.tournament_size = parse_value(arg, value)?,
+                "--offspring-ratio" => config.offspring_ratio = parse_value(arg, value)?,
+                "--games" => config.games_per_evaluation = parse_value(arg, value)?,
+                "--steps" => config.steps_per_game = parse_value(arg, value)?,
+                "--threads" => config.threads = parse_value(arg, value)?,
+                "--seed" => config.seed = parse_value(arg, value)?,
+                "--out" => config.output_dir = PathBuf::from(value),
+                "--weights" => config.weights_path = PathBuf::from(value),
+                _ => return Err(format!("Unknown training option {}", arg)),
+            }
+        }
 
-            let mut selected = tournament.clone();
-            selected.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+        if config.pop_size < 2 {
+            return Err("The population needs at least 2 bots".to_string());
+        }
+        Ok(config)
+    }
 
-            let parent1 = selected[0];
-            let parent2 = selected[1];
+    // Seeds of the games every bot is evaluated on. The same for every generation
+    pub fn get_game_seeds(&self) -> Vec<u64> {
+        (0..self.games_per_evaluation as u64).map(|i| self.seed.wrapping_add(i)).collect()
+    }
+}
 
-            let mut child = Bot::random_crossover(parent1, parent2);
-            if rng.random_bool(0.05) {
-                child.mutate(0.1);
-            }
+pub fn parse_value<T: FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid value {} for {}", value, arg))
+}
+
+// Everything needed to continue a run
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub optimizer: String,
+    pub generation: usize, // Next generation to run
+    pub best_weights: Weights, // Best weights over all generations
+    pub best_fitness: f64,
+    pub state: Value, // From Optimizer::get_state
+}
 
-            offspring.push(child);
+impl Checkpoint {
+    pub fn load(dir: &Path) -> Result<Checkpoint, Box<dyn Error>> {
+        let json = fs::read_to_string(dir.join(CHECKPOINT_FILE))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        // Written to a temporary file first so a crash never leaves half a checkpoint
+        let temp_path = dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        fs::write(&temp_path, serde_json::to_string(self)?)?;
+        fs::rename(temp_path, dir.join(CHECKPOINT_FILE))?;
+        Ok(())
+    }
+}
+
+// Total lines cleared over one game per seed
+pub fn evaluate_bot(bot: &mut Bot, seeds: &[u64], steps_per_game: i32) -> f64 {
+    seeds
+        .iter()
+        .map(|seed| {
+            bot.game.reset_game(Some(*seed));
+            bot.game_steps = 0;
+            bot.inputs.clear();
+            bot.run_game_without_ui(steps_per_game)
+        })
+        .sum()
+}
+
+// Evaluates every candidate in parallel on the same games
+pub fn evaluate_weights(candidates: &[Weights], config: &TrainConfig, opponent: &Weights) -> Vec<f64> {
+    let seeds = config.get_game_seeds();
+    let max_pieces = config.steps_per_game.max(0) as usize;
+
+    match config.fitness {
+        FitnessType::Lines => candidates
+            .par_iter()
+            .map(|weights| evaluate_bot(&mut Bot::with_weights(weights.clone()), &seeds, config.steps_per_game))
+            .collect(),
+        FitnessType::Versus => candidates
+            .par_iter()
+            .map(|weights| play_matches(weights, opponent, &seeds, max_pieces).get_fitness())
+            .collect(),
+        FitnessType::SelfPlay => (0..candidates.len())
+            .into_par_iter()
+            .map(|i| {
+                // Every game is against the next candidate in line, skipping itself
+                let mut stats = VersusStats::default();
+                for (g, seed) in seeds.iter().enumerate() {
+                    let j = (i + 1 + g % (candidates.len() - 1)) % candidates.len();
+                    let [result, _] = play_match(&candidates[i], &candidates[j], *seed, max_pieces);
+                    stats.add(&result);
+                }
+                stats.get_fitness()
+            })
+            .collect(),
+    }
+}
+
+// Appends one row per generation, the header is only written to a new file
+fn log_generation(dir: &Path, generation: usize, candidates: &[Weights], fitness: &[f64]) -> Result<(), Box<dyn Error>> {
+    let path = dir.join(LOG_FILE);
+    let new_file = !path.exists();
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut wtr = Writer::from_writer(file);
+
+    if new_file {
+        let mut header = vec!["generation", "best", "mean", "worst"];
+        header.extend(ALL_FEATURES.iter().map(|f| f.get_name()));
+        wtr.write_record(&header)?;
+    }
+
+    let best = (0..fitness.len()).max_by(|a, b| fitness[*a].total_cmp(&fitness[*b])).unwrap();
+    let mean = fitness.iter().sum::<f64>() / fitness.len() as f64;
+    let worst = fitness.iter().copied().fold(f64::INFINITY, f64::min);
+    let mut record = vec![
+        generation.to_string(),
+        fitness[best].to_string(),
+        mean.to_string(),
+        worst.to_string(),
+    ];
+    record.extend(ALL_FEATURES.iter().map(|f| candidates[best].get(*f).to_string()));
+    wtr.write_record(&record)?;
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn train_ai(config: &TrainConfig) -> Result<Weights, Box<dyn Error>> {
+    // Fails if the pool was already built, which is fine
+    let _ = ThreadPoolBuilder::new()
+        .num_threads(config.threads)
+        .build_global();
+
+    fs::create_dir_all(&config.output_dir)?;
+    // Loaded once, the run may overwrite the file with its own weights
+    let opponent = match &config.opponent {
+        Some(path) => Weights::load_from(path),
+        None => Weights::default(),
+    };
+
+    let mut optimizer = config.optimizer.create(config, &mut StdRng::seed_from_u64(config.seed));
+    let mut start = 0;
+    let mut best_weights = Weights::default();
+    let mut best_fitness = f64::NEG_INFINITY;
+
+    if config.resume {
+        let checkpoint = Checkpoint::load(&config.output_dir)?;
+        if checkpoint.optimizer != optimizer.get_name() {
+            return Err(format!("The checkpoint was made with {}", checkpoint.optimizer).into());
+        }
+        println!("Resuming from generation {}", checkpoint.generation);
+        optimizer.set_state(checkpoint.state)?;
+        start = checkpoint.generation;
+        best_weights = checkpoint.best_weights;
+        best_fitness = checkpoint.best_fitness;
+    }
+
+    for generation in start..config.generations {
+        println!("Generation {} ({}) ...", generation, optimizer.get_name());
+        // Seeded per generation so a resumed run continues exactly like an uninterrupted one
+        let mut rng = StdRng::seed_from_u64(config.seed.wrapping_add(generation as u64 + 1));
+
+        let candidates = optimizer.ask(&mut rng);
+        let fitness = evaluate_weights(&candidates, config, &opponent);
+        log_generation(&config.output_dir, generation, &candidates, &fitness)?;
+
+        for (weights, fitness) in candidates.iter().zip(&fitness) {
+            if *fitness > best_fitness {
+                best_fitness = *fitness;
+                best_weights = weights.clone();
+            }
         }
+        println!("Top fitness: {}", best_fitness);
+        println!("Best weights: {:?}", best_weights.get_named());
+
+        optimizer.tell(&fitness, &mut rng);
 
-        population.truncate(POP_SIZE - OFFSPRING_COUNT);
-        population.extend(offspring);
+        // The best weights so far are kept even if the run is stopped
+        best_weights.save_to(&config.weights_path)?;
+        Checkpoint {
+            optimizer: optimizer.get_name().to_string(),
+            generation: generation + 1,
+            best_weights: best_weights.clone(),
+            best_fitness,
+            state: optimizer.get_state()?,
+        }.save(&config.output_dir)?;
     }
 
-    population.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
-    println!("Best weights: {:?}", population[0].weights);
+    Ok(best_weights)
 }
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use super::{bot::Bot, weights::Weights};
+use crate::{clock::Clock, consts::GameMode, Game};
+
+// Simulated time per piece, both bots play at the same speed
+const PIECE_TIME: Duration = Duration::from_millis(500);
+
+// How much attack per piece and survival count next to the win rate
+const ATTACK_PER_PIECE_FACTOR: f64 = 0.5;
+const SURVIVAL_FACTOR: f64 = 0.25;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VersusStats {
+    pub matches: usize,
+    pub wins: usize,
+    pub draws: usize, // Both topped out the same piece or neither did in time
+    pub attack: usize,
+    pub pieces: usize,
+    pub max_pieces: usize, // Pieces the bot could have placed if it never lost
+    pub time: Duration, // Simulated time played, summed over the matches
+}
+
+impl VersusStats {
+    // The stats of both sides of one finished match, a win if only the other side topped out
+    pub fn from_games(games: [&Game; 2], max_pieces: usize) -> [VersusStats; 2] {
+        let over = games.map(|game| game.game_over);
+        let draw = over[0] == over[1];
+
+        [0, 1].map(|i| VersusStats {
+            matches: 1,
+            wins: (!draw && !over[i]) as usize,
+            draws: draw as usize,
+            attack: games[i].garbage_sent,
+            pieces: games[i].pieces,
+            max_pieces,
+            time: games[i].clock.elapsed(games[i].start_time),
+        })
+    }
+
+    pub fn add(&mut self, other: &VersusStats) {
+        self.matches += other.matches;
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.attack += other.attack;
+        self.pieces += other.pieces;
+        self.max_pieces += other.max_pieces;
+        self.time += other.time;
+    }
+
+    pub fn get_win_rate(&self) -> f64 {
+        if self.matches == 0 {
+            return 0.;
+        }
+        (self.wins as f64 + self.draws as f64 / 2.) / self.matches as f64
+    }
+
+    /*
+        95% Wilson score interval of the win rate, draws count as half a
+        win. Stays inside 0 to 1 and behaves well for few matches, unlike
+        the plain normal approximation.
+    */
+    pub fn get_win_rate_interval(&self) -> (f64, f64) {
+        if self.matches == 0 {
+            return (0., 1.);
+        }
+        const Z: f64 = 1.96;
+        let n = self.matches as f64;
+        let p = self.get_win_rate();
+
+        let center = (p + Z * Z / (2. * n)) / (1. + Z * Z / n);
+        let margin = Z / (1. + Z * Z / n) * (p * (1. - p) / n + Z * Z / (4. * n * n)).sqrt();
+        ((center - margin).max(0.), (center + margin).min(1.))
+    }
+
+    pub fn get_attack_per_piece(&self) -> f64 {
+        if self.pieces == 0 {
+            return 0.;
+        }
+        self.attack as f64 / self.pieces as f64
+    }
+
+    // Attack per minute
+    pub fn get_apm(&self) -> f64 {
+        if self.time.is_zero() {
+            return 0.;
+        }
+        self.attack as f64 / self.time.as_secs_f64() * 60.
+    }
+
+    // Pieces per second
+    pub fn get_pps(&self) -> f64 {
+        if self.time.is_zero() {
+            return 0.;
+        }
+        self.pieces as f64 / self.time.as_secs_f64()
+    }
+
+    pub fn get_survival(&self) -> f64 {
+        if self.max_pieces == 0 {
+            return 0.;
+        }
+        self.pieces as f64 / self.max_pieces as f64
+    }
+
+    /*
+        Winning matters most. Attack per piece and survival break ties
+        between bots that win equally often, and give losing bots
+        something to improve on early in training.
+    */
+    pub fn get_fitness(&self) -> f64 {
+        self.get_win_rate()
+            + ATTACK_PER_PIECE_FACTOR * self.get_attack_per_piece()
+            + SURVIVAL_FACTOR * self.get_survival()
+    }
+}
+
+fn versus_bot(weights: &Weights, seed: u64) -> Bot {
+    let mut bot = Bot::with_weights(weights.clone());
+    bot.game.clock = Clock::manual();
+    bot.game.gamemode = GameMode::Versus;
+    bot.game.reset_game(Some(seed));
+    bot
+}
+
+/*
+    Headless 1v1 where both bots get the same pieces. Every turn each bot
+    places one piece, then time moves on and garbage is exchanged like in
+    ScreenState::Versus. Returns the stats of both bots.
+*/
+pub fn play_match(one: &Weights, two: &Weights, seed: u64, max_pieces: usize) -> [VersusStats; 2] {
+    let mut bots = [versus_bot(one, seed), versus_bot(two, seed)];
+
+    for _ in 0..max_pieces {
+        for bot in bots.iter_mut() {
+            let mut inputs = bot.get_best_move_sequence();
+            inputs.reverse();
+            while let Some(input) = inputs.pop() {
+                bot.apply_input(input);
+            }
+        }
+
+        for bot in bots.iter_mut() {
+            bot.game.clock.advance(PIECE_TIME);
+            bot.game.clock.catch_up();
+            bot.game.update_garbage();
+        }
+
+        let [bot_one, bot_two] = &mut bots;
+        bot_one.game.send_outbound_garbage(&mut bot_two.game);
+        bot_two.game.send_outbound_garbage(&mut bot_one.game);
+
+        if bots.iter().any(|bot| bot.game.game_over) {
+            break;
+        }
+    }
+
+    VersusStats::from_games([&bots[0].game, &bots[1].game], max_pieces)
+}
+
+// Plays one match per seed, the stats are from the point of view of `weights`
+pub fn play_matches(weights: &Weights, opponent: &Weights, seeds: &[u64], max_pieces: usize) -> VersusStats {
+    let mut stats = VersusStats::default();
+    for seed in seeds {
+        let [result, _] = play_match(weights, opponent, *seed, max_pieces);
+        stats.add(&result);
+    }
+    stats
+}
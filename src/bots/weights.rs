@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::move_outcome::MoveOutcome;
+
+// Written by the trainer, Bot::new uses the default weights if it doesn't exist
+pub const BOT_WEIGHTS_PATH: &str = "res/bot_weights.json";
+
+// Everything the bot looks at when evaluating a placement
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Feature {
+    AggregateHeight,
+    LinesCleared,
+    Holes,
+    Bumpiness,
+    RowTransitions,
+    ColumnTransitions,
+    WellDepth,
+    CoveredCells,
+    MaxHeight,
+    TSlots,
+    BackToBack,
+    Combo,
+    AttackSent,
+}
+
+pub const ALL_FEATURES: [Feature; 13] = [
+    Feature::AggregateHeight,
+    Feature::LinesCleared,
+    Feature::Holes,
+    Feature::Bumpiness,
+    Feature::RowTransitions,
+    Feature::ColumnTransitions,
+    Feature::WellDepth,
+    Feature::CoveredCells,
+    Feature::MaxHeight,
+    Feature::TSlots,
+    Feature::BackToBack,
+    Feature::Combo,
+    Feature::AttackSent,
+];
+
+impl Feature {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Feature::AggregateHeight => "aggregate_height",
+            Feature::LinesCleared => "lines_cleared",
+            Feature::Holes => "holes",
+            Feature::Bumpiness => "bumpiness",
+            Feature::RowTransitions => "row_transitions",
+            Feature::ColumnTransitions => "column_transitions",
+            Feature::WellDepth => "well_depth",
+            Feature::CoveredCells => "covered_cells",
+            Feature::MaxHeight => "max_height",
+            Feature::TSlots => "t_slots",
+            Feature::BackToBack => "back_to_back",
+            Feature::Combo => "combo",
+            Feature::AttackSent => "attack_sent",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Feature> {
+        ALL_FEATURES.into_iter().find(|f| f.get_name() == name)
+    }
+}
+
+/*
+    One weight per feature, in the order of ALL_FEATURES.
+    The vector can be shorter than the amount of features, missing weights
+    count as 0. That way old weight sets keep working when features are added.
+    Saved as a map from feature name to weight.
+*/
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, f64>", into = "BTreeMap<String, f64>")]
+pub struct Weights {
+    pub values: Vec<f64>,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        // Features added after the first training run start at 0 until trained
+        Weights::from_pairs(&[
+            (Feature::AggregateHeight, -0.610066),
+            (Feature::LinesCleared, 0.760666),
+            (Feature::Holes, -0.35663),
+            (Feature::Bumpiness, -0.184483),
+        ])
+    }
+}
+
+// Names that don't match a feature are skipped
+impl From<BTreeMap<String, f64>> for Weights {
+    fn from(map: BTreeMap<String, f64>) -> Self {
+        let mut weights = Weights { values: Vec::new() };
+        for (name, value) in map {
+            if let Some(feature) = Feature::from_name(&name) {
+                weights.set(feature, value);
+            }
+        }
+        weights
+    }
+}
+
+impl From<Weights> for BTreeMap<String, f64> {
+    fn from(weights: Weights) -> Self {
+        weights.get_named().into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+    }
+}
+
+impl Weights {
+    pub fn load() -> Weights {
+        Weights::load_from(Path::new(BOT_WEIGHTS_PATH))
+    }
+
+    pub fn load_from(path: &Path) -> Weights {
+        let Ok(json) = fs::read_to_string(path) else {
+            return Weights::default();
+        };
+        match serde_json::from_str::<Weights>(&json) {
+            Ok(weights) => weights,
+            Err(e) => {
+                eprintln!("Couldn't parse bot weights, using defaults: {}", e);
+                Weights::default()
+            }
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn from_pairs(pairs: &[(Feature, f64)]) -> Self {
+        let mut weights = Weights { values: vec![0.; ALL_FEATURES.len()] };
+        for (feature, value) in pairs {
+            weights.set(*feature, *value);
+        }
+        weights
+    }
+
+    // Random weights in -1..1 for every feature
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Weights {
+            values: ALL_FEATURES.iter().map(|_| rng.random_range(-1.0..1.0)).collect(),
+        }
+    }
+
+    pub fn get(&self, feature: Feature) -> f64 {
+        self.values.get(feature as usize).copied().unwrap_or(0.)
+    }
+
+    pub fn set(&mut self, feature: Feature, value: f64) {
+        let i = feature as usize;
+        if self.values.len() <= i {
+            self.values.resize(i + 1, 0.);
+        }
+        self.values[i] = value;
+    }
+
+    // Every weight with the name of its feature
+    pub fn get_named(&self) -> Vec<(&'static str, f64)> {
+        ALL_FEATURES.iter().map(|f| (f.get_name(), self.get(*f))).collect()
+    }
+
+    pub fn normalize(&mut self) {
+        let norm = self.values.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm > 0. {
+            self.values.iter_mut().for_each(|w| *w /= norm);
+        }
+    }
+
+    pub fn evaluate(&self, outcome: &MoveOutcome) -> f64 {
+        ALL_FEATURES
+            .iter()
+            .map(|f| outcome.get_feature(*f) as f64 * self.get(*f))
+            .sum()
+    }
+}
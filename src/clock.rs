@@ -0,0 +1,130 @@
+use std::{
+    ops::{Add, AddAssign, Sub},
+    time::{Duration, Instant},
+};
+
+// Length of one simulation tick. All engine timing is done in whole ticks
+pub const TICK: Duration = Duration::from_millis(1);
+
+// A point in simulated time, measured from when the clock was created
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SimInstant(Duration);
+
+impl SimInstant {
+    pub fn from_ticks(ticks: u64) -> Self {
+        SimInstant(TICK * ticks as u32)
+    }
+
+    pub fn as_ticks(&self) -> u64 {
+        (self.0.as_nanos() / TICK.as_nanos()) as u64
+    }
+
+    pub fn duration_since(&self, earlier: SimInstant) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+}
+
+impl Add<Duration> for SimInstant {
+    type Output = SimInstant;
+    fn add(self, rhs: Duration) -> SimInstant {
+        SimInstant(self.0 + rhs)
+    }
+}
+
+impl AddAssign<Duration> for SimInstant {
+    fn add_assign(&mut self, rhs: Duration) {
+        self.0 += rhs;
+    }
+}
+
+impl Sub<Duration> for SimInstant {
+    type Output = SimInstant;
+    fn sub(self, rhs: Duration) -> SimInstant {
+        SimInstant(self.0.saturating_sub(rhs))
+    }
+}
+
+/*
+    Tick based clock used by Game.
+    `now` is the tick currently being simulated and `target` is how far the
+    simulation is allowed to run. A realtime clock moves its target along with
+    the wall clock, a manual clock only moves when `advance` is called.
+*/
+#[derive(Clone, Debug)]
+pub struct Clock {
+    now: SimInstant,
+    target: SimInstant,
+    realtime_anchor: Option<Instant>,
+}
+
+impl Clock {
+    pub fn realtime() -> Self {
+        Self {
+            now: SimInstant::default(),
+            target: SimInstant::default(),
+            realtime_anchor: Some(Instant::now()),
+        }
+    }
+
+    pub fn manual() -> Self {
+        Self {
+            now: SimInstant::default(),
+            target: SimInstant::default(),
+            realtime_anchor: None,
+        }
+    }
+
+    pub fn now(&self) -> SimInstant {
+        self.now
+    }
+
+    pub fn elapsed(&self, since: SimInstant) -> Duration {
+        self.now.duration_since(since)
+    }
+
+    pub fn is_realtime(&self) -> bool {
+        self.realtime_anchor.is_some()
+    }
+
+    // Allow the simulation to run `duration` further
+    pub fn advance(&mut self, duration: Duration) {
+        self.target += duration;
+    }
+
+    // Allow the simulation to run until `target`
+    pub fn advance_to(&mut self, target: SimInstant) {
+        self.target = self.target.max(target);
+    }
+
+    // Move the target along with the wall clock. Does nothing for manual clocks
+    pub fn sync(&mut self) {
+        if let Some(anchor) = self.realtime_anchor {
+            let ticks = (anchor.elapsed().as_nanos() / TICK.as_nanos()) as u32;
+            self.target += TICK * ticks;
+            self.realtime_anchor = Some(anchor + TICK * ticks);
+        }
+    }
+
+    // Steps one tick towards the target. Returns false once the target is reached
+    pub fn next_tick(&mut self) -> bool {
+        if self.now >= self.target {
+            return false;
+        }
+        self.now += TICK;
+        true
+    }
+
+    // Jump straight to the target without simulating the ticks in between
+    pub fn catch_up(&mut self) {
+        self.sync();
+        self.now = self.target;
+    }
+
+    // Throw away any time that has not been simulated yet
+    pub fn discard_pending(&mut self) {
+        if self.realtime_anchor.is_some() {
+            self.realtime_anchor = Some(Instant::now());
+        }
+        self.target = self.now;
+    }
+}
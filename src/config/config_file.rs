@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use ggez::event::Button;
+use serde::{Deserialize, Serialize};
+
+use crate::consts::{DEFAULT_ARR, DEFAULT_DAS, DEFAULT_SDS};
+use crate::randomizer::RandomizerType;
+use crate::{Game, GameAction, KeyCode};
+
+use super::input_config::*;
+
+pub const CONFIG_PATH: &str = "res/config.json";
+
+// Limits used by the settings screen
+pub const DAS_RANGE: (u64, u64) = (50, 200);
+pub const ARR_RANGE: (u64, u64) = (0, 100);
+pub const SDS_RANGE: (f32, f32) = (5., 100.);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandlingConfig {
+    pub das: u64, // in millis
+    pub arr: u64, // in millis
+    pub sds: Option<f32>, // None means infinite soft drop speed
+}
+
+impl Default for HandlingConfig {
+    fn default() -> Self {
+        Self {
+            das: DEFAULT_DAS,
+            arr: DEFAULT_ARR,
+            sds: Some(DEFAULT_SDS),
+        }
+    }
+}
+
+impl HandlingConfig {
+    pub fn from_game(game: &Game) -> Self {
+        Self {
+            das: game.das.as_millis() as u64,
+            arr: game.arr.as_millis() as u64,
+            sds: if game.sds.is_finite() {Some(game.sds)} else {None},
+        }
+    }
+
+    pub fn apply(&self, game: &mut Game) {
+        game.das = Duration::from_millis(self.das);
+        game.arr = Duration::from_millis(self.arr);
+        game.sds = self.sds.unwrap_or(f32::INFINITY);
+    }
+
+    // Clamps values that are out of range of what the settings screen allows
+    fn validate(&mut self) {
+        self.das = self.das.clamp(DAS_RANGE.0, DAS_RANGE.1);
+        self.arr = self.arr.clamp(ARR_RANGE.0, ARR_RANGE.1);
+        self.sds = self.sds.filter(|s| s.is_finite()).map(|s| s.clamp(SDS_RANGE.0, SDS_RANGE.1));
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeybindConfig {
+    pub solo: HashMap<GameAction, KeyCode>,
+    pub versus: [HashMap<GameAction, KeyCode>; 2],
+    pub drifarkaden: [HashMap<GameAction, KeyCode>; 2],
+    pub gamepad: [HashMap<GameAction, Button>; 2],
+}
+
+impl Default for KeybindConfig {
+    fn default() -> Self {
+        let [versus_one, versus_two]: [_; 2] = multi_controller_keyboard_keybindings().try_into().unwrap();
+        let [drifarkaden_one, drifarkaden_two]: [_; 2] = default_drivarkaden_keybindings().try_into().unwrap();
+        Self {
+            solo: default_keyboard_keybindings(),
+            versus: [versus_one, versus_two],
+            drifarkaden: [drifarkaden_one, drifarkaden_two],
+            gamepad: [default_gamepad_bindings(), default_gamepad_bindings()],
+        }
+    }
+}
+
+// One of the keybind maps in KeybindConfig
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeybindSlot {
+    Solo,
+    Versus(usize), // Player index
+    Drifarkaden(usize), // Player index
+}
+
+impl KeybindSlot {
+    // The map of the other player that is used at the same time as this one
+    pub fn get_opponent(&self) -> Option<KeybindSlot> {
+        match self {
+            KeybindSlot::Solo => None,
+            KeybindSlot::Versus(p) => Some(KeybindSlot::Versus(1 - p)),
+            KeybindSlot::Drifarkaden(p) => Some(KeybindSlot::Drifarkaden(1 - p)),
+        }
+    }
+
+    pub fn get_player(&self) -> usize {
+        match self {
+            KeybindSlot::Solo => 0,
+            KeybindSlot::Versus(p) | KeybindSlot::Drifarkaden(p) => *p,
+        }
+    }
+
+    pub fn get_name(&self) -> String {
+        match self {
+            KeybindSlot::Solo => "P1 SOLO".to_string(),
+            KeybindSlot::Versus(p) => format!("P{} VERSUS", p + 1),
+            KeybindSlot::Drifarkaden(p) => format!("P{} ARCADE", p + 1),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebindResult {
+    Bound,
+    Swapped(GameAction), // The key was taken by another action in the same map, which got the old key instead
+    Conflict(KeybindSlot, GameAction, KeyCode), // The key is used by the other player, nothing was changed
+}
+
+impl KeybindConfig {
+    pub fn get_map(&self, slot: KeybindSlot) -> &HashMap<GameAction, KeyCode> {
+        match slot {
+            KeybindSlot::Solo => &self.solo,
+            KeybindSlot::Versus(p) => &self.versus[p],
+            KeybindSlot::Drifarkaden(p) => &self.drifarkaden[p],
+        }
+    }
+
+    fn get_map_mut(&mut self, slot: KeybindSlot) -> &mut HashMap<GameAction, KeyCode> {
+        match slot {
+            KeybindSlot::Solo => &mut self.solo,
+            KeybindSlot::Versus(p) => &mut self.versus[p],
+            KeybindSlot::Drifarkaden(p) => &mut self.drifarkaden[p],
+        }
+    }
+
+    // Returns the action in `slot` that is bound to `key`
+    pub fn find_action(&self, slot: KeybindSlot, key: KeyCode) -> Option<GameAction> {
+        ALL_GAME_ACTIONS.into_iter().find(|action| self.get_map(slot).get(action) == Some(&key))
+    }
+
+    pub fn rebind(&mut self, slot: KeybindSlot, action: GameAction, key: KeyCode) -> RebindResult {
+        if let Some(opponent) = slot.get_opponent() {
+            if let Some(other_action) = self.find_action(opponent, key) {
+                return RebindResult::Conflict(opponent, other_action, key);
+            }
+        }
+
+        let map = self.get_map_mut(slot);
+        let old_key = map.insert(action, key);
+
+        let other_action = ALL_GAME_ACTIONS.into_iter()
+            .find(|a| *a != action && map.get(a) == Some(&key));
+        match (other_action, old_key) {
+            (Some(other_action), Some(old_key)) => {
+                map.insert(other_action, old_key);
+                RebindResult::Swapped(other_action)
+            }
+            _ => RebindResult::Bound,
+        }
+    }
+
+    // Gamepads are never shared, so the only conflicts are within the player's own bindings
+    pub fn rebind_button(&mut self, player: usize, action: GameAction, button: Button) -> RebindResult {
+        let map = &mut self.gamepad[player];
+        let old_button = map.insert(action, button);
+
+        let other_action = ALL_GAME_ACTIONS.into_iter()
+            .find(|a| *a != action && map.get(a) == Some(&button));
+        match (other_action, old_button) {
+            (Some(other_action), Some(old_button)) => {
+                map.insert(other_action, old_button);
+                RebindResult::Swapped(other_action)
+            }
+            _ => RebindResult::Bound,
+        }
+    }
+
+    // Restores both the keys and the gamepad buttons of the slot
+    pub fn restore_defaults(&mut self, slot: KeybindSlot) {
+        *self.get_map_mut(slot) = KeybindConfig::default().get_map(slot).clone();
+        self.gamepad[slot.get_player()] = default_gamepad_bindings();
+    }
+
+    // Actions missing from a map get their default key
+    fn validate(&mut self) {
+        let defaults = KeybindConfig::default();
+        let maps = std::iter::once(&mut self.solo)
+            .chain(self.versus.iter_mut())
+            .chain(self.drifarkaden.iter_mut());
+        let default_maps = std::iter::once(&defaults.solo)
+            .chain(defaults.versus.iter())
+            .chain(defaults.drifarkaden.iter());
+
+        for (map, default_map) in maps.zip(default_maps) {
+            for action in ALL_GAME_ACTIONS {
+                map.entry(action).or_insert(default_map[&action]);
+            }
+        }
+
+        let default_buttons = default_gamepad_bindings();
+        for map in self.gamepad.iter_mut() {
+            for action in ALL_GAME_ACTIONS {
+                map.entry(action).or_insert(default_buttons[&action]);
+            }
+        }
+    }
+}
+
+/*
+    Settings that are kept between restarts.
+    Missing fields are filled in with defaults and a file that can't be read
+    gives the default config, so an old or broken file never stops the game.
+*/
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub handling: [HandlingConfig; 2],
+    pub keybinds: KeybindConfig,
+    pub randomizer: RandomizerType, // Shared by both players so versus stays fair
+}
+
+impl Config {
+    pub fn load() -> Config {
+        Config::load_from(Path::new(CONFIG_PATH))
+    }
+
+    pub fn load_from(path: &Path) -> Config {
+        match fs::read_to_string(path) {
+            Ok(json) => Config::from_json(&json),
+            Err(_) => Config::default(),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Config {
+        match serde_json::from_str::<Config>(json) {
+            Ok(mut config) => {
+                config.validate();
+                config
+            }
+            Err(e) => {
+                eprintln!("Couldn't parse config, using defaults: {}", e);
+                Config::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        self.save_to(Path::new(CONFIG_PATH))
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn validate(&mut self) {
+        self.handling.iter_mut().for_each(|h| h.validate());
+        self.keybinds.validate();
+    }
+
+    // Keybinds of both players for the current layout
+    pub fn get_controls(&self, drifarkaden: bool, versus: bool) -> [HashMap<GameAction, KeyCode>; 2] {
+        if drifarkaden {
+            self.keybinds.drifarkaden.clone()
+        } else if versus {
+            self.keybinds.versus.clone()
+        } else {
+            [self.keybinds.solo.clone(), self.keybinds.versus[1].clone()]
+        }
+    }
+}
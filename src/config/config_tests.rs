@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use crate::{
+        config::config_file::*,
+        consts::{GAME_1_SOLO_POS, GAME_1_SOLO_SCL},
+        default_keyboard_keybindings,
+        randomizer::RandomizerType,
+        Game, GameAction, KeyCode,
+    };
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("tetris_config_{}_{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_missing_file_gives_defaults() {
+        let config = Config::load_from(&temp_path("missing"));
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_malformed_file_gives_defaults() {
+        assert_eq!(Config::from_json("{ not json"), Config::default());
+        assert_eq!(Config::from_json(r#"{"handling": "fast"}"#), Config::default());
+    }
+
+    #[test]
+    fn test_partial_file_is_filled_with_defaults() {
+        let config = Config::from_json(r#"{
+            "handling": [{"das": 100}, {}],
+            "keybinds": {"solo": {"HardDrop": "Return"}}
+        }"#);
+
+        assert_eq!(config.handling[0].das, 100);
+        assert_eq!(config.handling[0].arr, HandlingConfig::default().arr);
+        assert_eq!(config.handling[1], HandlingConfig::default());
+
+        assert_eq!(config.keybinds.solo[&GameAction::HardDrop], KeyCode::Return);
+        assert_eq!(config.keybinds.solo[&GameAction::Hold], default_keyboard_keybindings()[&GameAction::Hold]);
+        assert_eq!(config.keybinds.versus, KeybindConfig::default().versus);
+        assert_eq!(config.randomizer, RandomizerType::SevenBag);
+    }
+
+    #[test]
+    fn test_out_of_range_handling_is_clamped() {
+        let config = Config::from_json(r#"{"handling": [{"das": 1, "arr": 999, "sds": 1000.0}, {"sds": null}]}"#);
+
+        assert_eq!(config.handling[0].das, DAS_RANGE.0);
+        assert_eq!(config.handling[0].arr, ARR_RANGE.1);
+        assert_eq!(config.handling[0].sds, Some(SDS_RANGE.1));
+        assert_eq!(config.handling[1].sds, None);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = temp_path("roundtrip");
+        let mut config = Config::default();
+        config.handling[1] = HandlingConfig { das: 120, arr: 0, sds: None };
+        config.keybinds.drifarkaden[0].insert(GameAction::Rotate180, KeyCode::X);
+        config.randomizer = RandomizerType::History4;
+
+        config.save_to(&path).unwrap();
+        let loaded = Config::load_from(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(config, loaded);
+    }
+
+    #[test]
+    fn test_handling_applies_to_game() {
+        let mut game = Game::new(GAME_1_SOLO_POS, GAME_1_SOLO_SCL, 0);
+        let handling = HandlingConfig { das: 90, arr: 10, sds: None };
+        handling.apply(&mut game);
+
+        assert_eq!(game.das, Duration::from_millis(90));
+        assert_eq!(game.arr, Duration::from_millis(10));
+        assert!(game.sds.is_infinite());
+        assert_eq!(HandlingConfig::from_game(&game), handling);
+    }
+
+    #[test]
+    fn test_rebind_to_free_key() {
+        let mut keybinds = KeybindConfig::default();
+        let result = keybinds.rebind(KeybindSlot::Solo, GameAction::Hold, KeyCode::C);
+
+        assert_eq!(result, RebindResult::Bound);
+        assert_eq!(keybinds.solo[&GameAction::Hold], KeyCode::C);
+    }
+
+    #[test]
+    fn test_rebind_swaps_keys_within_map() {
+        let mut keybinds = KeybindConfig::default();
+        let hard_drop = keybinds.solo[&GameAction::HardDrop];
+        let hold = keybinds.solo[&GameAction::Hold];
+
+        let result = keybinds.rebind(KeybindSlot::Solo, GameAction::Hold, hard_drop);
+
+        assert_eq!(result, RebindResult::Swapped(GameAction::HardDrop));
+        assert_eq!(keybinds.solo[&GameAction::Hold], hard_drop);
+        assert_eq!(keybinds.solo[&GameAction::HardDrop], hold);
+    }
+
+    #[test]
+    fn test_rebind_conflict_with_other_player() {
+        let mut keybinds = KeybindConfig::default();
+        let before = keybinds.clone();
+        let p2_hard_drop = keybinds.versus[1][&GameAction::HardDrop];
+
+        let result = keybinds.rebind(KeybindSlot::Versus(0), GameAction::Hold, p2_hard_drop);
+
+        assert_eq!(result, RebindResult::Conflict(KeybindSlot::Versus(1), GameAction::HardDrop, p2_hard_drop));
+        assert_eq!(keybinds, before);
+
+        // The solo layout is never used at the same time as player two
+        let result = keybinds.rebind(KeybindSlot::Solo, GameAction::Hold, p2_hard_drop);
+        assert_eq!(result, RebindResult::Bound);
+    }
+
+    #[test]
+    fn test_restore_defaults() {
+        let mut keybinds = KeybindConfig::default();
+        keybinds.rebind(KeybindSlot::Drifarkaden(1), GameAction::MoveLeft, KeyCode::P);
+        keybinds.rebind(KeybindSlot::Drifarkaden(0), GameAction::MoveLeft, KeyCode::O);
+
+        keybinds.restore_defaults(KeybindSlot::Drifarkaden(1));
+
+        assert_eq!(keybinds.drifarkaden[1], KeybindConfig::default().drifarkaden[1]);
+        assert_eq!(keybinds.drifarkaden[0][&GameAction::MoveLeft], KeyCode::O);
+    }
+}
@@ -1,7 +1,9 @@
+use ggez::event::Button;
 use ggez::input::keyboard::KeyCode;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum GameAction {
     MoveRight,
     MoveLeft,
@@ -13,6 +15,17 @@ pub enum GameAction {
     Hold,
 }
 
+pub const ALL_GAME_ACTIONS: [GameAction; 8] = [
+    GameAction::MoveRight,
+    GameAction::MoveLeft,
+    GameAction::SoftDrop,
+    GameAction::HardDrop,
+    GameAction::RotateCw,
+    GameAction::RotateCcw,
+    GameAction::Rotate180,
+    GameAction::Hold,
+];
+
 
 // Returns default_keyboard_keybindings
 pub fn default_keyboard_keybindings() -> HashMap<GameAction, KeyCode> {
@@ -92,4 +105,20 @@ pub fn default_drivarkaden_keybindings() -> Vec<HashMap<GameAction, KeyCode>>{
 
 
     vec![player1, player2]
-}
\ No newline at end of file
+}
+
+// Returns the default gamepad bindings, the same for both players
+pub fn default_gamepad_bindings() -> HashMap<GameAction, Button> {
+    use GameAction::*;
+
+    let mut map = HashMap::new();
+    map.insert(MoveLeft, Button::DPadLeft);
+    map.insert(MoveRight, Button::DPadRight);
+    map.insert(SoftDrop, Button::DPadDown);
+    map.insert(HardDrop, Button::DPadUp);
+    map.insert(RotateCw, Button::East);
+    map.insert(RotateCcw, Button::South);
+    map.insert(Rotate180, Button::North);
+    map.insert(Hold, Button::LeftTrigger);
+    map
+}
@@ -1 +1,3 @@
+pub mod config_file;
 pub mod input_config;
+mod config_tests;
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 // All of these consts should probably be relative to window size
 pub const WINDOW_WIDTH: f32 = 1920.;
 pub const WINDOW_HEIGHT: f32 = 1080.;
@@ -12,22 +14,104 @@ pub const GAME_2_VS_POS: (f32, f32) = (1028., 200.);
 pub const GAME_2_VS_SCL: f32 = 1.;
 
 pub const SETTINGS_TICK_SPEED: u128 = 150; // in millis
+pub const KEYBIND_TIMEOUT: u64 = 5; // Seconds to press a key when rebinding before it is cancelled
 
 pub const DEFAULT_DAS: u64 = 165; // in millis
 pub const DEFAULT_ARR: u64 = 35; // in millis
 pub const DEFAULT_SDS: f32 = 15.; // in cells per seconds
+pub const DEFAULT_LOCK_DELAY: u64 = 500; // in millis
+pub const DEFAULT_MAX_ACTIONS: usize = 15; // Moves and rotations on the ground before the piece locks
+pub const DEFAULT_ARE: u64 = 0; // in millis
+pub const DEFAULT_LINE_CLEAR_DELAY: u64 = 0; // in millis
 
 pub const GARBAGE_CAP: usize = 8;
 pub const GARBAGE_DELAY: u128 = 1000; // time in milliseconds before garbage can appear on board
 
+pub const REPLAY_SEEK_TICKS: f64 = 5000.; // Ticks skipped when seeking in the replay viewer
+
 pub const SURVIVAL_TIMER: u64 = 1000; // time in millis between each line spawn
+pub const SPRINT_LINE_GOALS: [usize; 4] = [20, 40, 100, 1000]; // Picked in the Sprint prompt
+pub const SPRINT_HIGHSCORE_PATHS: [&str; 4] = [
+    "res/highscores/highscore_sprint_20.csv",
+    "res/highscores/highscore_sprint_40.csv",
+    "res/highscores/highscore_sprint_100.csv",
+    "res/highscores/highscore_sprint_1000.csv",
+]; // One per line goal
+pub const SPRINT_SPLIT_LINES: usize = 10; // A split time is taken every this many lines
+pub const ULTRA_TIME_LIMITS: [u64; 2] = [120, 180]; // in seconds, picked in the Ultra prompt
+pub const ULTRA_HIGHSCORE_PATHS: [&str; 2] = ["res/highscores/highscore_ultra_2.csv", "res/highscores/highscore_ultra_3.csv"]; // One per time limit
+pub const DIG_LINE_GOALS: [usize; 3] = [10, 18, 100]; // Garbage lines to dig out, picked in the Dig prompt
+pub const DIG_GARBAGE_ROWS: usize = 10; // Garbage rows kept on the board until the last ones of the goal
+pub const DIG_HIGHSCORE_PATHS: [[&str; 3]; 2] = [
+    [
+        "res/highscores/highscore_dig_10.csv",
+        "res/highscores/highscore_dig_18.csv",
+        "res/highscores/highscore_dig_100.csv",
+    ],
+    [
+        "res/highscores/highscore_dig_messy_10.csv",
+        "res/highscores/highscore_dig_messy_18.csv",
+        "res/highscores/highscore_dig_messy_100.csv",
+    ],
+]; // [messy][line goal]
+pub const MASTER_MAX_LEVEL: usize = 999; // Master is completed here
+// (from level, gravity in cells per second, ARE, line clear delay, lock delay, max actions) in Master, delays in millis.
+// Gravity goes up to 20G first, after that the delays get shorter
+pub const MASTER_TIMINGS: [(usize, f32, u64, u64, u64, usize); 16] = [
+    (0, 1., 400, 400, 500, 15),
+    (30, 1.5, 400, 400, 500, 15),
+    (50, 2., 400, 400, 500, 15),
+    (70, 3., 400, 400, 500, 15),
+    (90, 4., 400, 400, 500, 15),
+    (100, 6., 400, 400, 500, 15),
+    (150, 10., 400, 400, 500, 15),
+    (200, 15., 400, 400, 500, 15),
+    (250, 30., 400, 400, 500, 15),
+    (300, 60., 400, 400, 500, 15),
+    (400, 150., 400, 400, 500, 15),
+    (500, f32::INFINITY, 400, 400, 500, 15), // 20G, pieces are on the ground as soon as they spawn
+    (600, f32::INFINITY, 350, 300, 450, 12),
+    (700, f32::INFINITY, 300, 200, 400, 10),
+    (800, f32::INFINITY, 250, 150, 350, 8),
+    (900, f32::INFINITY, 200, 100, 300, 6),
+];
+// (grade, grade points needed), worst first
+pub const MASTER_GRADES: [(&str, usize); 18] = [
+    ("9", 0), ("8", 20), ("7", 40), ("6", 70), ("5", 110), ("4", 160), ("3", 220), ("2", 300), ("1", 400),
+    ("S1", 520), ("S2", 660), ("S3", 820), ("S4", 1000), ("S5", 1200), ("S6", 1400), ("S7", 1600), ("S8", 1800), ("S9", 2000),
+];
+pub const MASTER_GM_TIME: u64 = 810; // in seconds. Completing Master as S9 within this time gives GM
+pub const MASTER_HIGHSCORE_PATH: &str = "res/highscores/highscore_master.csv"; // Holds the level reached
+pub const PC_TRAINING_HEIGHT: usize = 4; // Rows the stack may reach in PC training before the board is cleared
+
+// (title, file, timed) in the order of the high score screen. Timed tables hold times instead of scores
+pub const HIGHSCORE_TABLES: [(&str, &str, bool); 15] = [
+    ("SURVIVAL", "res/highscores/highscore_survival.csv", true),
+    ("MARATHON", "res/highscores/highscore_marathon.csv", false),
+    ("20L", SPRINT_HIGHSCORE_PATHS[0], true),
+    ("40L", SPRINT_HIGHSCORE_PATHS[1], true),
+    ("100L", SPRINT_HIGHSCORE_PATHS[2], true),
+    ("1000L", SPRINT_HIGHSCORE_PATHS[3], true),
+    ("ULTRA 2M", ULTRA_HIGHSCORE_PATHS[0], false),
+    ("ULTRA 3M", ULTRA_HIGHSCORE_PATHS[1], false),
+    ("DIG 10L", DIG_HIGHSCORE_PATHS[0][0], true),
+    ("DIG 18L", DIG_HIGHSCORE_PATHS[0][1], true),
+    ("DIG 100L", DIG_HIGHSCORE_PATHS[0][2], true),
+    ("MESSY 10L", DIG_HIGHSCORE_PATHS[1][0], true),
+    ("MESSY 18L", DIG_HIGHSCORE_PATHS[1][1], true),
+    ("MESSY 100L", DIG_HIGHSCORE_PATHS[1][2], true),
+    ("MASTER", MASTER_HIGHSCORE_PATH, false),
+];
 
 pub const BOARD_AMOUNT_COLUMNS: usize = 10;
 pub const BOARD_AMOUNT_ROWS: usize = 40;
+pub const NEXT_QUEUE_SIZE: usize = 5; // Pieces shown in the next queue
 
 //Delays (seconds)
 pub const DEFAULT_GRAVITY: f32 = 1.; // Cells per second
-pub const BOT_DIFFICULTY_SPEEDS: [f32 ; 3] = [0.5, 3., 5.];
+pub const BOT_BEAM_WIDTH: usize = 8;
+pub const BOT_MISTAKE_POOL: usize = 4; // A mistake picks one of this many placements after the best one
+pub const BOT_PC_MAX_NODES: usize = 200; // Perfect clear search of the in-game bot, it runs on the UI thread every piece
 
 #[derive(PartialEq)]
 pub enum ScreenState {
@@ -37,7 +121,10 @@ pub enum ScreenState {
     SingleplayerSelector,
     Singleplayer,
     MarathonPrompt,
-    FourtyLinesReset,
+    SprintPrompt,
+    UltraPrompt,
+    DigPrompt,
+    RetryPrompt,
     VersusReady,
     Versus,
     VersusRematch,
@@ -46,12 +133,19 @@ pub enum ScreenState {
     Settings,
     BotSelector,
     HighScore,
+    ReplayBrowser,
+    ReplayViewer,
+    Keybinds,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum GameMode {
     Marathon,
-    FourtyLines,
+    Sprint, // Clear line_goal lines as fast as possible
     Survival,
     Versus,
+    PcTraining,
+    Ultra,
+    Dig, // Dig out dig_goal garbage lines as fast as possible
+    Master, // Levels up to MASTER_MAX_LEVEL with 20G and a grade at the end
 }
@@ -1,11 +1,14 @@
 use std::collections::{HashMap, VecDeque};
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use ggez::Context;
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use crate::board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS};
-use crate::consts::{GameMode, DEFAULT_ARR, DEFAULT_DAS, DEFAULT_GRAVITY, DEFAULT_SDS};
+use crate::board::{Board, BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS};
+use crate::consts::{GameMode, DEFAULT_ARE, DEFAULT_ARR, DEFAULT_DAS, DEFAULT_GRAVITY, DEFAULT_LINE_CLEAR_DELAY, DEFAULT_LOCK_DELAY, DEFAULT_MAX_ACTIONS, DEFAULT_SDS, DIG_LINE_GOALS, SPRINT_LINE_GOALS, ULTRA_TIME_LIMITS};
+use crate::clock::{Clock, SimInstant};
+use crate::randomizer::{Randomizer, RandomizerType};
+use crate::game_inputs::InputFrame;
 use crate::{default_keyboard_keybindings, GameAction, KeyCode, Piece, PieceType};
 
 #[derive(Clone)]
@@ -24,20 +27,30 @@ pub struct Game {
     pub objective_completed: bool,
     pub controls: HashMap<GameAction, KeyCode>,
     pub continue_to_highscore: bool,
+    pub clock: Clock,
+    pub reset_time: SimInstant, // Clock time of the latest reset, replays are timed from here
 
     //Misc
-    pub countdown_start : Option<Instant>,
+    pub countdown_start : Option<SimInstant>,
     pub countdown_duration : Duration,
 
     // Pieces
+    pub randomizer_type: RandomizerType,
+    pub randomizer: Box<dyn Randomizer>,
     pub piece_queue: VecDeque<PieceType>,
     pub active_piece: Piece,
     pub held_piece: Option<PieceType>,
     pub can_hold: bool,
 
     // Garbage
-    pub garbage_outbound: VecDeque<(usize, usize, Option<Instant>)>, // (column, amount, Some(cooldown))
-    pub garbage_inbound: VecDeque<(usize, usize, Option<Instant>)>, // (column, amount, Some(cooldown))
+    pub garbage_outbound: VecDeque<(usize, usize, Option<SimInstant>)>, // (column, amount, Some(cooldown))
+    pub garbage_inbound: VecDeque<(usize, usize, Option<SimInstant>)>, // (column, amount, Some(cooldown))
+    pub garbage_rng: StdRng, // Picks garbage holes. Seeded from id so games can be reproduced
+    pub garbage_timer: Option<SimInstant>, // Timer for survival garbage
+    pub time_limit: Duration, // Length of an Ultra game
+    pub line_goal: usize, // Lines to clear in a sprint
+    pub dig_goal: usize, // Garbage lines to clear in a dig game
+    pub dig_messy: bool, // Dig garbage holes change column every row
 
     // Timing/movement tomfoolery
     pub moving_right: bool,
@@ -45,18 +58,26 @@ pub struct Game {
     pub soft_dropping: bool,
 
     pub das: Duration,
-    pub das_start: Option<Instant>,
+    pub das_start: Option<SimInstant>,
     pub das_charged: bool,
     pub arr: Duration,
-    pub arr_start: Option<Instant>,
+    pub arr_start: Option<SimInstant>,
     pub sds: f32, // Soft drop speed (cells per second)
     pub gravity: f32, // (cells per second)
-    pub last_drop: Instant,
+    pub last_drop: SimInstant,
     pub fall_timing: Duration, // (time per cell)
     pub on_ground: bool,
-    pub on_ground_start: Option<Instant>, // Timer for lock delay
+    pub on_ground_start: Option<SimInstant>, // Timer for lock delay
+    pub lock_delay: Duration, // Time on the ground before the piece locks
     pub lowest_row: isize, // Lowest row that piece has touched
-    pub action_count: usize, // Action counter. If it reaches 15, the piece will automatically lock in place
+    pub action_count: usize, // Action counter. If it reaches max_actions, the piece will automatically lock in place
+    pub max_actions: usize,
+    pub are: Duration, // Entry delay, time from a piece locking to the next one spawning
+    pub line_clear_delay: Duration, // Added to the entry delay when lines are cleared
+    pub spawn_at: Option<SimInstant>, // When the next piece spawns, Some during the entry delay
+    pub line_clear: Option<(SimInstant, Vec<usize>, Board)>, // (start, cleared rows, board before the clear), shown during the line clear delay
+    pub buffered_rotation: Option<usize>, // Initial rotation (IRS), pressed during the entry delay
+    pub buffered_hold: bool, // Initial hold (IHS), pressed during the entry delay
 
     // Stats
     pub score: usize,
@@ -65,8 +86,10 @@ pub struct Game {
     pub pieces: usize,
     pub garbage_sent: usize,
     pub garbage_received: usize,
-    pub start_time: Instant,
+    pub start_time: SimInstant,
     pub final_time: Duration,
+    pub splits: Vec<Duration>, // Sprint time at every SPRINT_SPLIT_LINES lines
+    pub grade_points: usize, // Master grade, see get_master_grade
 
     // Scoring checks
     pub t_spin: bool,
@@ -76,6 +99,15 @@ pub struct Game {
     pub latest_clear_difficult: bool, // true if latest line clear was a tetris or t-spin
     pub back_to_back: bool,
     pub all_clear: bool,
+    pub last_placed: Option<(Piece, bool, bool)>, // (piece, t_spin, t_spin_mini) of the latest locked piece
+
+    // Coach
+    pub hint_piece: Option<Piece>, // Drawn as a second ghost, see Coach
+
+    // PC training
+    pub perfect_clears: usize,
+    pub failed_clears: usize, // Boards that could no longer be perfect cleared
+    pub pc_hint: Option<Piece>, // Next piece of a perfect clear with the visible pieces, if there is one
 }
 
 impl Game {
@@ -86,22 +118,32 @@ impl Game {
             canvas_scl: scl,
 
             board: [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS],
-            gamemode: GameMode::FourtyLines,
+            gamemode: GameMode::Sprint,
 
-            countdown_start: Some(Instant::now()),
+            countdown_start: Some(SimInstant::default()),
             countdown_duration: Duration::from_millis(3500),
 
             game_over: false,
             objective_completed: false,
             active_piece: Piece::new(PieceType::Z, 0),
+            randomizer_type: RandomizerType::SevenBag,
+            randomizer: RandomizerType::SevenBag.create(id),
             held_piece: None,
             piece_queue: VecDeque::new(),
             can_hold: true,
             controls: default_keyboard_keybindings(),
             continue_to_highscore: false,
+            clock: Clock::realtime(),
+            reset_time: SimInstant::default(),
 
             garbage_inbound: VecDeque::new(),
             garbage_outbound: VecDeque::new(),
+            garbage_rng: StdRng::seed_from_u64(id),
+            garbage_timer: None,
+            time_limit: Duration::from_secs(ULTRA_TIME_LIMITS[0]),
+            line_goal: SPRINT_LINE_GOALS[1],
+            dig_goal: DIG_LINE_GOALS[0],
+            dig_messy: false,
 
             moving_right: false,
             moving_left: false,
@@ -114,12 +156,20 @@ impl Game {
             arr_start: None,
             sds: DEFAULT_SDS,
             gravity: DEFAULT_GRAVITY,
-            last_drop: Instant::now(),
+            last_drop: SimInstant::default(),
             fall_timing: Duration::from_millis((1000. / DEFAULT_GRAVITY) as u64),
             on_ground: false,
             on_ground_start: None,
+            lock_delay: Duration::from_millis(DEFAULT_LOCK_DELAY),
             lowest_row: 21,
             action_count: 0,
+            max_actions: DEFAULT_MAX_ACTIONS,
+            are: Duration::from_millis(DEFAULT_ARE),
+            line_clear_delay: Duration::from_millis(DEFAULT_LINE_CLEAR_DELAY),
+            spawn_at: None,
+            line_clear: None,
+            buffered_rotation: None,
+            buffered_hold: false,
 
             score: 0,
             lines: 0,
@@ -127,8 +177,10 @@ impl Game {
             pieces: 0,
             garbage_sent: 0,
             garbage_received: 0,
-            start_time: Instant::now(),
+            start_time: SimInstant::default(),
             final_time: Duration::from_secs(0),
+            splits: Vec::new(),
+            grade_points: 0,
 
             t_spin: false,
             t_spin_mini: false,
@@ -137,6 +189,13 @@ impl Game {
             latest_clear_difficult: false,
             back_to_back: false,
             all_clear: false,
+            last_placed: None,
+
+            hint_piece: None,
+
+            perfect_clears: 0,
+            failed_clears: 0,
+            pc_hint: None,
         }
     }
 
@@ -150,35 +209,69 @@ impl Game {
         }
         self.game_over = false;
         self.held_piece = None;
+        self.randomizer = self.randomizer_type.create(self.id);
+        self.garbage_rng = StdRng::seed_from_u64(self.id);
+
+        // Time that passed before the reset should not be simulated
+        self.clock.discard_pending();
+        self.reset_time = self.clock.now();
         self.piece_queue = VecDeque::new();
+        self.spawn_at = None;
+        self.line_clear = None;
+        self.buffered_rotation = None;
+        self.buffered_hold = false;
         self.spawn_piece_from_queue();
         self.continue_to_highscore  = false;
 
         self.garbage_inbound = VecDeque::new();
         self.garbage_outbound = VecDeque::new();
+        self.garbage_timer = None;
 
         self.moving_right = false;
         self.moving_left = false;
-        self.last_drop = Instant::now();
+        self.last_drop = self.clock.now();
 
-        self.countdown_start = Some(Instant::now());
+        self.countdown_start = Some(self.clock.now());
 
         self.score = 0;
         self.lines = 0;
         self.set_level(1);
+        // Master changes these with the level
+        self.lock_delay = Duration::from_millis(DEFAULT_LOCK_DELAY);
+        self.max_actions = DEFAULT_MAX_ACTIONS;
+        self.are = Duration::from_millis(DEFAULT_ARE);
+        self.line_clear_delay = Duration::from_millis(DEFAULT_LINE_CLEAR_DELAY);
         self.pieces = 0;
         self.garbage_sent = 0;
         self.garbage_received = 0;
-        self.start_time = Instant::now();
-        
+        self.start_time = self.clock.now();
+        self.splits = Vec::new();
+        self.grade_points = 0;
 
         self.latest_clear_difficult = false;
         self.back_to_back = false;
+        self.last_placed = None;
+        self.hint_piece = None;
+
+        self.perfect_clears = 0;
+        self.failed_clears = 0;
+        self.pc_hint = None;
+
+        self.start_gamemode();
+    }
+
+    // Mode specific setup at the end of every reset, so the gamemode and its options have to be set before resetting
+    fn start_gamemode(&mut self) {
+        match self.gamemode {
+            GameMode::Dig => self.fill_dig_garbage(),
+            GameMode::Master => self.start_master(),
+            _ => (),
+        }
     }
 
     pub fn end_game(&mut self, objective_completed: bool) {
         self.game_over = true;
-        self.final_time = self.start_time.elapsed();
+        self.final_time = self.clock.elapsed(self.start_time);
         self.objective_completed = objective_completed;
     }
 
@@ -195,7 +288,7 @@ impl Game {
 
     pub fn spawn_piece(&mut self, piece_type: PieceType) {
         self.active_piece = Piece::new(piece_type, 0);
-        self.last_drop = Instant::now();
+        self.last_drop = self.clock.now();
 
         // Check if spawn location is valid
         if !self.is_valid_position(0, 0) {
@@ -206,15 +299,15 @@ impl Game {
         self.on_ground = false;
         if !self.is_valid_position(0, -1) {
             self.on_ground = true;
-            self.on_ground_start = Some(Instant::now());
+            self.on_ground_start = Some(self.clock.now());
         }
     }
 
     pub fn spawn_piece_from_queue(&mut self) {
-        // Generate new bag if piece queue is shorter than 7 pieces
-        if self.piece_queue.len() < 7 {
-            let mut l = PieceType::get_random_as_list(&self.id);
-            self.piece_queue.append(&mut l);
+        // Top up the piece queue so it never gets shorter than 7 pieces
+        while self.piece_queue.len() < 7 {
+            let next = self.randomizer.next_piece();
+            self.piece_queue.push_back(next);
         }
 
         let next_piece_type = self.piece_queue.pop_front().unwrap();
@@ -222,23 +315,58 @@ impl Game {
         self.can_hold = true;
     }
 
+    // Ends the entry delay. Hold and rotation pressed during it are applied to the new piece
+    pub fn spawn_next_piece(&mut self) {
+        self.spawn_at = None;
+        self.line_clear = None;
+        self.spawn_piece_from_queue();
+        self.last_drop = self.clock.now();
 
-    pub fn update(&mut self, ctx: &mut Context) {
-        if let Some(start) = self.countdown_start{
-            let elapsed = start.elapsed(); 
-            if elapsed >= self.countdown_duration {
-                self.countdown_start = None
+        if std::mem::take(&mut self.buffered_hold) && !self.game_over {
+            self.hold();
+        }
+        if let Some(rotation) = self.buffered_rotation.take() {
+            if !self.game_over {
+                self.rotate(rotation);
             }
-            self.last_drop = Instant::now();
-            self.start_time = Instant::now();
-            return;
         }
 
+        if self.gamemode == GameMode::PcTraining {
+            self.update_pc_training();
+        }
+    }
+
+
+    // Keyboard driven update. Reads the inputs through `controls`, adds the gamepad inputs
+    // and steps the game. Returns the inputs the game was stepped with
+    pub fn update(&mut self, ctx: &mut Context, pad_inputs: InputFrame) -> InputFrame {
+        self.clock.sync();
+
         // Reset button. Remove before release
+        if ctx.keyboard.is_key_just_pressed(KeyCode::R)
+        && self.countdown_start.is_none()
+        && (self.game_over || self.gamemode != GameMode::Versus) {
+            self.reset_game(None);
+            return InputFrame::new();
+        }
+
+        let mut inputs = InputFrame::from_keyboard(&ctx.keyboard, &self.controls);
+        inputs.events.extend(pad_inputs.events);
+        self.step(&inputs);
+        inputs
+    }
+
+    // Simulates up to the current clock time and then applies the inputs
+    pub fn step(&mut self, inputs: &InputFrame) {
+        self.run_pending_ticks();
+
+        if self.countdown_start.is_some() {
+            return;
+        }
+
         if self.game_over {
-            if ctx.keyboard.is_key_just_pressed(KeyCode::R) {self.reset_game(None);}
-            if ctx.keyboard.is_key_just_pressed(*self.controls.get(&GameAction::HardDrop).unwrap()) {
-                if (self.gamemode == GameMode::FourtyLines
+            if inputs.is_pressed(GameAction::HardDrop) {
+                if ((self.gamemode == GameMode::Sprint || self.gamemode == GameMode::Dig)
                 && !self.objective_completed)
                 || self.gamemode == GameMode::Versus {}
                 else {self.continue_to_highscore = true}
@@ -246,8 +374,56 @@ impl Game {
             return;
         }
 
+        //Handle inputs
+        self.handle_game_inputs(inputs);
+    }
+
+    // Simulates every tick the clock has been advanced by since the last call
+    pub fn run_pending_ticks(&mut self) {
+        while self.clock.next_tick() {
+            self.update_tick();
+        }
+    }
+
+    // Time based logic for a single tick
+    fn update_tick(&mut self) {
+        if let Some(start) = self.countdown_start{
+            let elapsed = self.clock.elapsed(start);
+            if elapsed >= self.countdown_duration {
+                self.countdown_start = None
+            }
+            self.last_drop = self.clock.now();
+            self.start_time = self.clock.now();
+            return;
+        }
+
+        if self.gamemode == GameMode::Ultra {
+            self.update_ultra_timer();
+        }
+
+        if self.game_over {
+            return;
+        }
+
+        // The next piece spawns after the entry delay, there is nothing to move until then
+        if self.spawn_at.is_some_and(|t| self.clock.now() >= t) {
+            self.spawn_next_piece();
+        }
+        if self.spawn_at.is_none() {
+            self.update_active_piece();
+        }
+
+        self.update_garbage();
+
+        if self.gamemode == GameMode::Survival {
+            self.update_survival_garbage();
+        }
+    }
+
+    // Gravity, DAS and lock delay of the active piece
+    fn update_active_piece(&mut self) {
         // Downward movement (soft drop or natural fall)
-        while !self.on_ground && self.last_drop.elapsed() >= self.fall_timing {
+        while !self.on_ground && self.clock.elapsed(self.last_drop) >= self.fall_timing {
             self.last_drop += self.fall_timing;
             if self.move_piece(0, -1) {
                 if self.soft_dropping {
@@ -261,20 +437,17 @@ impl Game {
             self.on_ground_check();
         }
 
-        //Handle inputs
-        self.handle_game_inputs(ctx);
-
         // Horizontal movement
         if let Some(das_start) = self.das_start {
             // Check if DAS is charged
-            if !self.das_charged && das_start.elapsed() >= self.das {
+            if !self.das_charged && self.clock.elapsed(das_start) >= self.das {
                 self.das_charged = true;
                 self.arr_start = Some(das_start + self.das - self.arr);
             }
 
             if self.das_charged {
                 // Move if ARR allows
-                while self.arr_start.unwrap().elapsed() >= self.arr {
+                while self.clock.elapsed(self.arr_start.unwrap()) >= self.arr {
                     if self.moving_left {
                         if !self.move_piece(-1, 0) && self.arr.is_zero() {break}
                         else {self.add_action()}
@@ -288,20 +461,18 @@ impl Game {
             }
         }
         
-        // Place piece if it has been stationary for .5 seconds
+        // Place piece if it has been stationary for the lock delay
         if let Some(t) = self.on_ground_start {
-            if self.on_ground && t.elapsed() >= Duration::from_millis(500) {
+            if self.on_ground && self.clock.elapsed(t) >= self.lock_delay {
                 self.place_piece();
             }
         }
-
-        self.update_garbage();
     }
 
     pub fn on_ground_check(&mut self) {
         if !self.is_valid_position(0, -1) {
             self.on_ground = true;
-            self.on_ground_start = Some(Instant::now());
+            self.on_ground_start = Some(self.clock.now());
         } else {
             self.on_ground = false;
             self.on_ground_start = None;
@@ -319,7 +490,7 @@ impl Game {
             }
         }
 
-        if self.action_count >= 15 && self.on_ground {
+        if self.action_count >= self.max_actions && self.on_ground {
             self.place_piece();
         }
     }
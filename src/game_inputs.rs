@@ -1,24 +1,85 @@
-use std::time::Instant;
+use std::collections::HashMap;
 
-use crate::consts::GameMode;
-use crate::{Game, ROTATION_180, ROTATION_CCW, ROTATION_CW};
+use ggez::input::keyboard::KeyboardContext;
+use serde::{Deserialize, Serialize};
+
+use crate::{Game, KeyCode, ROTATION_180, ROTATION_CCW, ROTATION_CW};
 
 use crate::config::input_config::*;
 
-impl Game {
-    pub fn handle_game_inputs(&mut self, ctx: &ggez::Context) {
-        let keyboard = &ctx.keyboard;
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputEvent {
+    Pressed(GameAction),
+    Released(GameAction),
+}
+
+// All press and release events that happened during one frame
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputFrame {
+    pub events: Vec<InputEvent>,
+}
+
+impl InputFrame {
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    // Reads the keyboard state of this frame through a set of keybindings
+    pub fn from_keyboard(keyboard: &KeyboardContext, controls: &HashMap<GameAction, KeyCode>) -> Self {
+        let mut frame = InputFrame::new();
+        for action in ALL_GAME_ACTIONS {
+            if let Some(key) = controls.get(&action) {
+                if keyboard.is_key_just_pressed(*key) {
+                    frame.press(action);
+                }
+                if keyboard.is_key_just_released(*key) {
+                    frame.release(action);
+                }
+            }
+        }
+        frame
+    }
+
+    pub fn press(&mut self, action: GameAction) {
+        self.events.push(InputEvent::Pressed(action));
+    }
+
+    pub fn release(&mut self, action: GameAction) {
+        self.events.push(InputEvent::Released(action));
+    }
+
+    // Press and release within the same frame
+    pub fn tap(&mut self, action: GameAction) {
+        self.press(action);
+        self.release(action);
+    }
+
+    pub fn is_pressed(&self, action: GameAction) -> bool {
+        self.events.contains(&InputEvent::Pressed(action))
+    }
+
+    pub fn is_released(&self, action: GameAction) -> bool {
+        self.events.contains(&InputEvent::Released(action))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
 
+impl Game {
+    pub fn handle_game_inputs(&mut self, inputs: &InputFrame) {
         // Move left
-        if keyboard.is_key_just_pressed(*self.controls.get(&GameAction::MoveLeft).unwrap()) {
-            if self.move_piece(-1, 0) {self.add_action()}
+        if inputs.is_pressed(GameAction::MoveLeft) {
+            // DAS still charges during the entry delay
+            if self.spawn_at.is_none() && self.move_piece(-1, 0) {self.add_action()}
             self.moving_left = true;
             self.moving_right = false;
             self.das_charged = false;
-            self.das_start = Some(Instant::now());
+            self.das_start = Some(self.clock.now());
             self.arr_start = None;
         }
-        if keyboard.is_key_just_released(*self.controls.get(&GameAction::MoveLeft).unwrap()) {
+        if inputs.is_released(GameAction::MoveLeft) {
             if !self.moving_right {
                 self.moving_left = false;
                 self.das_charged = false;
@@ -28,15 +89,15 @@ impl Game {
         }
 
         // Move right
-        if keyboard.is_key_just_pressed(*self.controls.get(&GameAction::MoveRight).unwrap()) {
-            if self.move_piece(1, 0) {self.add_action()}
+        if inputs.is_pressed(GameAction::MoveRight) {
+            if self.spawn_at.is_none() && self.move_piece(1, 0) {self.add_action()}
             self.moving_right = true;
             self.moving_left = false;
             self.das_charged = false;
-            self.das_start = Some(Instant::now());
+            self.das_start = Some(self.clock.now());
             self.arr_start = None;
         }
-        if keyboard.is_key_just_released(*self.controls.get(&GameAction::MoveRight).unwrap()) {
+        if inputs.is_released(GameAction::MoveRight) {
             if !self.moving_left {
                 self.moving_right = false;
                 self.das_charged = false;
@@ -46,55 +107,64 @@ impl Game {
         }
 
         // Soft drop
-        if keyboard.is_key_just_pressed(*self.controls.get(&GameAction::SoftDrop).unwrap()) {
+        if inputs.is_pressed(GameAction::SoftDrop) {
             self.soft_dropping = true;
-            self.last_drop = Instant::now();
+            self.last_drop = self.clock.now();
             self.set_gravity_soft(self.gravity + self.sds);
         }
-        if keyboard.is_key_just_released(*self.controls.get(&GameAction::SoftDrop).unwrap()) {
+        if inputs.is_released(GameAction::SoftDrop) {
             self.soft_dropping = false;
             self.set_gravity_soft(self.gravity);
         }
 
         // Hard drop
-        if keyboard.is_key_just_pressed(*self.controls.get(&GameAction::HardDrop).unwrap()) {
+        if inputs.is_pressed(GameAction::HardDrop) && self.spawn_at.is_none() {
             self.hard_drop();
         }
 
         // Rotation handling
-        if keyboard.is_key_just_pressed(*self.controls.get(&GameAction::RotateCw).unwrap()) {
-            self.rotate(ROTATION_CW);
+        if inputs.is_pressed(GameAction::RotateCw) {
+            self.rotate_or_buffer(ROTATION_CW);
         }
 
-        if keyboard.is_key_just_pressed(*self.controls.get(&GameAction::RotateCcw).unwrap()) {
-            self.rotate(ROTATION_CCW);
+        if inputs.is_pressed(GameAction::RotateCcw) {
+            self.rotate_or_buffer(ROTATION_CCW);
         }
 
-        if keyboard.is_key_just_pressed(*self.controls.get(&GameAction::Rotate180).unwrap()) {
-            self.rotate(ROTATION_180);
+        if inputs.is_pressed(GameAction::Rotate180) {
+            self.rotate_or_buffer(ROTATION_180);
         }
 
-        // Hold
-        if keyboard.is_key_just_pressed(*self.controls.get(&GameAction::Hold).unwrap())
-            && self.can_hold
-        {
-            let held_piece = self.active_piece.piece_type;
-
-            if let Some(current_held) = self.held_piece {
-                self.spawn_piece(current_held);
-            } else {
-                self.spawn_piece_from_queue();
+        // Hold, buffered for the next piece during the entry delay (IHS)
+        if inputs.is_pressed(GameAction::Hold) {
+            if self.spawn_at.is_some() {
+                self.buffered_hold = true;
+            } else if self.can_hold {
+                self.hold();
             }
+        }
+    }
 
-            self.held_piece = Some(held_piece);
-            self.can_hold = false;
+    // During the entry delay the rotation is buffered for the next piece instead (IRS)
+    fn rotate_or_buffer(&mut self, rotation_type: usize) {
+        if self.spawn_at.is_some() {
+            self.buffered_rotation = Some(rotation_type);
+        } else {
+            self.rotate(rotation_type);
         }
+    }
 
-        // Reset
-        if keyboard.is_key_just_pressed(ggez::input::keyboard::KeyCode::R) {
-            if self.gamemode != GameMode::Versus {
-                self.reset_game(None);
-            }
+    // Swaps the active piece with the held one, or the next piece if nothing is held
+    pub fn hold(&mut self) {
+        let held_piece = self.active_piece.piece_type;
+
+        if let Some(current_held) = self.held_piece {
+            self.spawn_piece(current_held);
+        } else {
+            self.spawn_piece_from_queue();
         }
+
+        self.held_piece = Some(held_piece);
+        self.can_hold = false;
     }
 }
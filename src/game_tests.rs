@@ -0,0 +1,521 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, time::Duration};
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        clock::Clock,
+        consts::{GameMode, DEFAULT_LOCK_DELAY, DIG_GARBAGE_ROWS, DIG_HIGHSCORE_PATHS, GAME_1_SOLO_POS, GAME_1_SOLO_SCL, SPRINT_HIGHSCORE_PATHS, ULTRA_HIGHSCORE_PATHS},
+        game_inputs::InputFrame,
+        gamemodes::{dig::get_dig_highscore_path, sprint::get_sprint_highscore_path, ultra::get_ultra_highscore_path},
+        randomizer::RandomizerType,
+        bots::{bot::Bot, pc_solver::PcSolver},
+        replay::{Replay, ReplayPlayer, ReplayRecorder},
+        Game, GameAction, Piece, PieceType,
+    };
+
+    // Game driven by a manual clock, with the countdown already finished
+    fn headless_game(seed: u64) -> Game {
+        let mut game = Game::new(GAME_1_SOLO_POS, GAME_1_SOLO_SCL, seed);
+        game.clock = Clock::manual();
+        game.reset_game(Some(seed));
+        let countdown = game.countdown_duration;
+        advance(&mut game, countdown);
+        game
+    }
+
+    fn advance(game: &mut Game, duration: Duration) {
+        game.clock.advance(duration);
+        game.run_pending_ticks();
+    }
+
+    fn take_pieces(game: &mut Game, amount: usize) -> Vec<PieceType> {
+        (0..amount).map(|_| game.randomizer.next_piece()).collect()
+    }
+
+    #[test]
+    fn test_seven_bag_contains_every_piece() {
+        let mut randomizer = RandomizerType::SevenBag.create(42);
+
+        for _ in 0..10 {
+            let bag: HashSet<PieceType> = (0..7).map(|_| randomizer.next_piece()).collect();
+            assert_eq!(bag.len(), 7);
+        }
+    }
+
+    #[test]
+    fn test_bags_are_not_repeated() {
+        let mut randomizer = RandomizerType::SevenBag.create(42);
+
+        let bags: Vec<Vec<PieceType>> = (0..10)
+            .map(|_| (0..7).map(|_| randomizer.next_piece()).collect())
+            .collect();
+
+        assert!(bags.iter().any(|bag| *bag != bags[0]));
+    }
+
+    #[test]
+    fn test_shared_seed_gives_identical_sequences() {
+        let types = [
+            RandomizerType::SevenBag,
+            RandomizerType::FourteenBag,
+            RandomizerType::Random,
+            RandomizerType::History4,
+            RandomizerType::Nes,
+        ];
+
+        for randomizer_type in types {
+            let mut game_one = Game::new(GAME_1_SOLO_POS, GAME_1_SOLO_SCL, 0);
+            let mut game_two = Game::new(GAME_1_SOLO_POS, GAME_1_SOLO_SCL, 0);
+            game_one.randomizer_type = randomizer_type;
+            game_two.randomizer_type = randomizer_type;
+            game_one.reset_game(Some(1234));
+            game_two.reset_game(Some(1234));
+
+            assert_eq!(game_one.piece_queue, game_two.piece_queue);
+            assert_eq!(take_pieces(&mut game_one, 100), take_pieces(&mut game_two, 100));
+        }
+    }
+
+    #[test]
+    fn test_history_randomizer_first_piece() {
+        for seed in 0..50 {
+            let first = RandomizerType::History4.create(seed).next_piece();
+            assert!(![PieceType::S, PieceType::Z, PieceType::O].contains(&first));
+        }
+    }
+
+    #[test]
+    fn test_countdown_uses_clock() {
+        let mut game = Game::new(GAME_1_SOLO_POS, GAME_1_SOLO_SCL, 0);
+        game.clock = Clock::manual();
+        game.reset_game(Some(0));
+
+        advance(&mut game, Duration::from_millis(3499));
+        assert!(game.countdown_start.is_some());
+
+        advance(&mut game, Duration::from_millis(1));
+        assert!(game.countdown_start.is_none());
+    }
+
+    #[test]
+    fn test_gravity_and_lock_delay() {
+        let mut game = headless_game(0);
+        let start_row = game.active_piece.midpoint.0;
+
+        // Level 1 gravity is one cell per second
+        advance(&mut game, Duration::from_millis(999));
+        assert_eq!(game.active_piece.midpoint.0, start_row);
+        advance(&mut game, Duration::from_millis(1));
+        assert_eq!(game.active_piece.midpoint.0, start_row - 1);
+
+        // Let the piece reach the floor, it should lock exactly 500ms later
+        while !game.on_ground {
+            advance(&mut game, Duration::from_millis(1));
+        }
+        advance(&mut game, Duration::from_millis(499));
+        assert_eq!(game.pieces, 0);
+        advance(&mut game, Duration::from_millis(1));
+        assert_eq!(game.pieces, 1);
+    }
+
+    #[test]
+    fn test_simulation_is_deterministic() {
+        let run = |step: u64| {
+            let mut game = headless_game(99);
+            game.gamemode = GameMode::Survival;
+            for _ in 0..(120_000 / step) {
+                advance(&mut game, Duration::from_millis(step));
+            }
+            (game.board, game.pieces, game.garbage_received, game.game_over)
+        };
+
+        // The result must not depend on how the time is split up
+        let reference = run(1);
+        assert_eq!(reference, run(1));
+        assert_eq!(reference, run(16));
+        assert_eq!(reference, run(250));
+        assert!(reference.2 > 0);
+    }
+
+    #[test]
+    fn test_step_applies_inputs() {
+        let mut game = headless_game(0);
+        let start_column = game.active_piece.midpoint.1;
+
+        let mut inputs = InputFrame::new();
+        inputs.tap(GameAction::MoveLeft);
+        game.step(&inputs);
+        assert_eq!(game.active_piece.midpoint.1, start_column - 1);
+
+        let mut inputs = InputFrame::new();
+        inputs.press(GameAction::HardDrop);
+        game.step(&inputs);
+        assert_eq!(game.pieces, 1);
+    }
+
+    #[test]
+    fn test_das_moves_piece_to_wall() {
+        let mut game = headless_game(0);
+
+        let mut inputs = InputFrame::new();
+        inputs.press(GameAction::MoveRight);
+        game.step(&inputs);
+
+        game.clock.advance(game.das + game.arr * 10);
+        game.step(&InputFrame::new());
+        assert!(!game.is_valid_position(1, 0));
+
+        let mut inputs = InputFrame::new();
+        inputs.release(GameAction::MoveRight);
+        game.step(&inputs);
+        assert!(game.das_start.is_none());
+    }
+
+    #[test]
+    fn test_inputs_ignored_during_countdown() {
+        let mut game = Game::new(GAME_1_SOLO_POS, GAME_1_SOLO_SCL, 0);
+        game.clock = Clock::manual();
+        game.reset_game(Some(0));
+
+        let mut inputs = InputFrame::new();
+        inputs.press(GameAction::HardDrop);
+        game.step(&inputs);
+        assert_eq!(game.pieces, 0);
+    }
+
+    // Lets bots play with uneven frame times and idle frames while recording their inputs
+    fn play_recorded(bots: &mut [Bot], frames: usize) -> Replay {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut recorder = ReplayRecorder::start(&bots.iter().map(|b| &b.game).collect::<Vec<_>>());
+
+        for _ in 0..frames {
+            let mut all_inputs = Vec::new();
+            for bot in bots.iter_mut() {
+                bot.game.clock.advance(Duration::from_millis(rng.random_range(1..40)));
+
+                let mut inputs = InputFrame::new();
+                if bot.game.countdown_start.is_none() && rng.random_bool(0.5) {
+                    if bot.inputs.is_empty() {
+                        bot.inputs = bot.get_best_move_sequence();
+                        bot.inputs.reverse();
+                    }
+                    if let Some(action) = bot.inputs.pop().and_then(|i| i.get_game_action()) {
+                        inputs.tap(action);
+                    }
+                }
+                bot.game.step(&inputs);
+                all_inputs.push(inputs);
+            }
+
+            let mut sent = false;
+            if let [bot_one, bot_two] = bots {
+                sent |= bot_one.game.send_outbound_garbage(&mut bot_two.game);
+                sent |= bot_two.game.send_outbound_garbage(&mut bot_one.game);
+            }
+
+            let games: Vec<&Game> = bots.iter().map(|b| &b.game).collect();
+            recorder.record(&games, &all_inputs, sent);
+            if games.iter().any(|game| game.game_over) {
+                break;
+            }
+        }
+        recorder.finish(&bots.iter().map(|b| &b.game).collect::<Vec<_>>()).unwrap()
+    }
+
+    fn replay_bot(seed: u64, gamemode: GameMode) -> Bot {
+        let mut bot = Bot::new(0, seed);
+        bot.game.clock = Clock::manual();
+        bot.game.gamemode = gamemode;
+        bot.game.reset_game(Some(seed));
+        bot
+    }
+
+    fn assert_same_state(a: &Game, b: &Game) {
+        assert_eq!(a.board, b.board);
+        assert_eq!(a.active_piece.piece_type, b.active_piece.piece_type);
+        assert_eq!(a.active_piece.midpoint, b.active_piece.midpoint);
+        assert_eq!(a.piece_queue, b.piece_queue);
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.pieces, b.pieces);
+        assert_eq!(a.garbage_received, b.garbage_received);
+        assert_eq!(a.game_over, b.game_over);
+    }
+
+    #[test]
+    fn test_replay_json_roundtrip() {
+        let mut bots = [replay_bot(3, GameMode::Sprint)];
+        bots[0].game.sds = f32::INFINITY;
+        let replay = play_recorded(&mut bots, 300);
+
+        let loaded = Replay::from_json(&replay.to_json().unwrap()).unwrap();
+        assert_eq!(replay, loaded);
+        assert_eq!(loaded.players[0].sds, None);
+
+        let mut outdated = replay.clone();
+        outdated.version += 1;
+        assert!(Replay::from_json(&outdated.to_json().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_replay_reproduces_game() {
+        let mut bots = [replay_bot(11, GameMode::Sprint)];
+        bots[0].game.das = Duration::from_millis(100);
+        let replay = play_recorded(&mut bots, 2000);
+        assert!(bots[0].game.lines > 0);
+
+        let mut player = ReplayPlayer::new(replay.clone());
+        player.seek(replay.length as f64);
+        assert_same_state(&bots[0].game, &player.games[0]);
+    }
+
+    #[test]
+    fn test_versus_replay_reproduces_garbage() {
+        let mut bots = [replay_bot(21, GameMode::Versus), replay_bot(21, GameMode::Versus)];
+        let replay = play_recorded(&mut bots, 5000);
+        assert!(bots.iter().any(|bot| bot.game.garbage_received > 0));
+
+        // Seeking back and forth must end up in the same state as playing straight through
+        let mut player = ReplayPlayer::new(replay.clone());
+        player.seek(replay.length as f64 / 2.);
+        player.seek(replay.length as f64 / 4.);
+        for _ in 0..(replay.length / 16 + 1) {
+            player.update(Duration::from_millis(16));
+        }
+        assert!(player.is_finished());
+        assert_same_state(&bots[0].game, &player.games[0]);
+        assert_same_state(&bots[1].game, &player.games[1]);
+    }
+
+    #[test]
+    fn test_pc_training() {
+        let mut game = headless_game(0);
+        game.gamemode = GameMode::PcTraining;
+        for row in game.board.iter_mut().take(2) {
+            for cell in row.iter_mut().skip(4) {
+                *cell = Some(PieceType::X);
+            }
+        }
+        game.active_piece = Piece::new(PieceType::T, 0);
+        game.piece_queue = [PieceType::I, PieceType::I, PieceType::O, PieceType::O, PieceType::O].into();
+
+        let steps = PcSolver::default().solve(&game).unwrap();
+        game.hold();
+        steps[0].placement.apply(&mut game);
+        assert_eq!(game.pc_hint.as_ref().map(|piece| piece.piece_type), Some(PieceType::I));
+        steps[1].placement.apply(&mut game);
+        assert_eq!(game.perfect_clears, 1);
+        assert!(game.board[0].iter().all(|cell| cell.is_none()));
+
+        // Above the PC height the board is cleared and the attempt counts as failed
+        game.active_piece.midpoint.0 = 6;
+        game.place_piece();
+        assert_eq!(game.failed_clears, 1);
+        assert!(game.board.iter().all(|row| row.iter().all(|cell| cell.is_none())));
+        assert_eq!(game.perfect_clears, 1);
+    }
+
+    #[test]
+    fn test_ultra_ends_at_time_limit() {
+        let mut game = headless_game(0);
+        game.gamemode = GameMode::Ultra;
+        game.time_limit = Duration::from_secs(180);
+        // Slow enough that no piece tops out before the time is up
+        game.set_gravity_hard(0.01);
+
+        advance(&mut game, Duration::from_millis(179_999));
+        assert!(!game.game_over);
+        assert_eq!(game.get_time_left(), Duration::from_millis(1));
+
+        advance(&mut game, Duration::from_millis(1));
+        assert!(game.game_over && game.objective_completed);
+        assert_eq!(game.final_time, Duration::from_secs(180));
+        assert_eq!(game.get_time_left(), Duration::ZERO);
+        assert_eq!(game.level, 1);
+        assert_eq!(get_ultra_highscore_path(game.time_limit), ULTRA_HIGHSCORE_PATHS[1]);
+    }
+
+    // Drops an I piece into a row that is full except for where it lands
+    fn clear_line_with_i(game: &mut Game) {
+        game.active_piece = Piece::new(PieceType::I, 0);
+        let ghost = game.get_ghost_piece();
+        let row = (ghost.midpoint.0 + ghost.block_positions[0].0) as usize;
+        for c in 0..game.board[row].len() {
+            if !ghost.block_positions.iter().any(|(_, dc)| ghost.midpoint.1 + dc == c as isize) {
+                game.board[row][c] = Some(PieceType::X);
+            }
+        }
+        game.hard_drop();
+    }
+
+    #[test]
+    fn test_sprint_splits_and_goal() {
+        let mut game = headless_game(0);
+        game.gamemode = GameMode::Sprint;
+        game.line_goal = 20;
+
+        game.lines = 9;
+        advance(&mut game, Duration::from_secs(1));
+        clear_line_with_i(&mut game);
+        assert_eq!(game.splits, vec![Duration::from_secs(1)]);
+
+        game.lines = 19;
+        advance(&mut game, Duration::from_secs(1));
+        clear_line_with_i(&mut game);
+        assert!(game.game_over && game.objective_completed);
+        assert_eq!(game.splits, vec![Duration::from_secs(1), Duration::from_secs(2)]);
+        assert_eq!(game.final_time, Duration::from_secs(2));
+        assert_eq!(get_sprint_highscore_path(game.line_goal), SPRINT_HIGHSCORE_PATHS[0]);
+
+        // A new game starts without splits
+        game.reset_game(None);
+        assert!(game.splits.is_empty());
+    }
+
+    fn dig_game(goal: usize, messy: bool) -> Game {
+        let mut game = Game::new(GAME_1_SOLO_POS, GAME_1_SOLO_SCL, 0);
+        game.clock = Clock::manual();
+        game.gamemode = GameMode::Dig;
+        game.dig_goal = goal;
+        game.dig_messy = messy;
+        game.reset_game(Some(0));
+        // The garbage is there from the reset, before the countdown ends
+        assert_eq!(game.garbage_received, DIG_GARBAGE_ROWS.min(goal));
+        let countdown = game.countdown_duration;
+        advance(&mut game, countdown);
+        game
+    }
+
+    fn get_holes(game: &Game) -> Vec<usize> {
+        (0..game.get_garbage_left())
+            .map(|r| game.board[r].iter().position(|cell| cell.is_none()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_dig_garbage() {
+        // Clean garbage has every hole in the same column
+        let game = dig_game(10, false);
+        let holes = get_holes(&game);
+        assert_eq!(holes.len(), 10);
+        assert!(holes.iter().all(|c| *c == holes[0]));
+
+        let mut game = dig_game(18, true);
+        let holes = get_holes(&game);
+        assert_eq!(holes.len(), DIG_GARBAGE_ROWS);
+        assert!(holes.windows(2).all(|w| w[0] != w[1]));
+
+        for dug in 1..=18 {
+            // Fill the lowest hole and clear the row with the next piece
+            let hole = get_holes(&game)[0];
+            game.board[0][hole] = Some(PieceType::I);
+            game.hard_drop();
+            assert_eq!(game.get_garbage_dug(), dug);
+            assert_eq!(game.get_garbage_left(), DIG_GARBAGE_ROWS.min(18 - dug));
+
+            for row in game.board.iter_mut().filter(|row| !row.contains(&Some(PieceType::X))) {
+                *row = [None; 10];
+            }
+        }
+        assert!(game.game_over && game.objective_completed);
+        assert_eq!(get_dig_highscore_path(game.dig_goal, game.dig_messy), DIG_HIGHSCORE_PATHS[1][1]);
+    }
+
+    #[test]
+    fn test_master_levels_and_timing() {
+        let mut game = Game::new(GAME_1_SOLO_POS, GAME_1_SOLO_SCL, 0);
+        game.clock = Clock::manual();
+        game.gamemode = GameMode::Master;
+        game.reset_game(Some(0));
+        assert_eq!(game.level, 0);
+        let countdown = game.countdown_duration;
+        advance(&mut game, countdown);
+        assert_eq!(game.level, 0);
+        assert_eq!(game.lock_delay, Duration::from_millis(DEFAULT_LOCK_DELAY));
+
+        // Pieces can't get past a section stop, lines can
+        game.level = 498;
+        game.hard_drop();
+        wait_for_spawn(&mut game);
+        game.hard_drop();
+        wait_for_spawn(&mut game);
+        assert_eq!(game.level, 499);
+        assert!(game.gravity.is_finite());
+        clear_line_with_i(&mut game);
+        wait_for_spawn(&mut game);
+        assert_eq!(game.level, 500);
+        assert!(game.gravity.is_infinite());
+
+        // 20G puts the piece on the ground right away, and it locks after the shorter lock delay
+        game.level = 900;
+        game.hard_drop();
+        wait_for_spawn(&mut game);
+        let pieces = game.pieces;
+        assert!(game.on_ground);
+        let lock_delay = game.lock_delay;
+        assert!(lock_delay < Duration::from_millis(DEFAULT_LOCK_DELAY));
+        advance(&mut game, lock_delay);
+        assert_eq!(game.pieces, pieces + 1);
+        wait_for_spawn(&mut game);
+
+        game.level = 998;
+        game.grade_points = 2000;
+        clear_line_with_i(&mut game);
+        assert_eq!(game.level, 999);
+        assert!(game.game_over && game.objective_completed);
+        assert_eq!(game.get_master_grade(), "GM");
+
+        // Too slow for GM
+        game.final_time = Duration::from_secs(3600);
+        assert_eq!(game.get_master_grade(), "S9");
+        game.grade_points = 0;
+        assert_eq!(game.get_master_grade(), "9");
+    }
+
+    fn wait_for_spawn(game: &mut Game) {
+        if let Some(spawn_at) = game.spawn_at {
+            let now = game.clock.now();
+            advance(game, spawn_at.duration_since(now));
+        }
+    }
+
+    #[test]
+    fn test_entry_and_line_clear_delay() {
+        let mut game = headless_game(0);
+        game.are = Duration::from_millis(100);
+        game.line_clear_delay = Duration::from_millis(200);
+
+        let active = game.active_piece.piece_type;
+        game.hard_drop();
+        assert_eq!(game.active_piece.piece_type, active);
+        advance(&mut game, Duration::from_millis(99));
+        assert!(game.spawn_at.is_some());
+        advance(&mut game, Duration::from_millis(1));
+        assert!(game.spawn_at.is_none());
+
+        // Hold and rotation during the entry delay are applied to the next piece
+        let next = [game.piece_queue[0], game.piece_queue[1]];
+        game.hard_drop();
+        let mut inputs = InputFrame::new();
+        inputs.tap(GameAction::Hold);
+        inputs.tap(GameAction::RotateCw);
+        game.step(&inputs);
+        assert!(game.held_piece.is_none());
+        wait_for_spawn(&mut game);
+        assert_eq!(game.held_piece, Some(next[0]));
+        assert_eq!(game.active_piece.piece_type, next[1]);
+        assert_eq!(game.active_piece.rotation, 1);
+
+        // The cleared row is kept for the renderer until the line clear delay is over
+        clear_line_with_i(&mut game);
+        let (_, rows, board) = game.line_clear.clone().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert!(board[rows[0]].iter().all(|cell| cell.is_some()));
+        assert!(game.board.iter().all(|row| row.iter().any(|cell| cell.is_none())));
+        advance(&mut game, Duration::from_millis(299));
+        assert!(game.spawn_at.is_some());
+        advance(&mut game, Duration::from_millis(1));
+        assert!(game.spawn_at.is_none() && game.line_clear.is_none());
+    }
+}
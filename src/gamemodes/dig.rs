@@ -0,0 +1,100 @@
+use rand::Rng;
+use ggez::graphics::Canvas;
+
+use crate::{
+    board::BOARD_AMOUNT_COLUMNS,
+    consts::{DIG_GARBAGE_ROWS, DIG_HIGHSCORE_PATHS, DIG_LINE_GOALS},
+    ui_components::stat_formatting::*,
+    Game, PieceType,
+};
+
+/*
+    Dig starts with DIG_GARBAGE_ROWS garbage rows and adds a new one below
+    the stack for every one that is cleared, until dig_goal rows have been
+    added. Clean garbage keeps the hole in the same column, messy garbage
+    moves it to another column every row.
+*/
+impl Game {
+    // Called when the game is reset
+    pub fn fill_dig_garbage(&mut self) {
+        while self.garbage_received < DIG_GARBAGE_ROWS.min(self.dig_goal) {
+            self.add_dig_row();
+        }
+    }
+
+    // Called after every placed piece. Refills the garbage and ends the game at the goal
+    pub fn update_dig(&mut self) {
+        let dug = self.get_garbage_dug();
+        if dug >= self.dig_goal {
+            self.end_game(true);
+            return;
+        }
+
+        while self.garbage_received - dug < DIG_GARBAGE_ROWS && self.garbage_received < self.dig_goal {
+            self.add_dig_row();
+        }
+    }
+
+    fn add_dig_row(&mut self) {
+        // The hole of the garbage row below the new one, if there is one
+        let previous = if self.board[0].contains(&Some(PieceType::X)) {
+            self.board[0].iter().position(|cell| cell.is_none())
+        } else {
+            None
+        };
+
+        let column = match previous {
+            Some(column) if !self.dig_messy => column,
+            Some(column) => {
+                // Any column except the previous one
+                let c = self.garbage_rng.random_range(0..BOARD_AMOUNT_COLUMNS - 1);
+                if c >= column { c + 1 } else { c }
+            }
+            None => self.garbage_rng.random_range(0..BOARD_AMOUNT_COLUMNS),
+        };
+        self.add_garbage_row(column);
+    }
+
+    // Garbage rows are the only ones with X cells
+    pub fn get_garbage_left(&self) -> usize {
+        self.board
+            .iter()
+            .filter(|row| row.contains(&Some(PieceType::X)))
+            .count()
+    }
+
+    pub fn get_garbage_dug(&self) -> usize {
+        self.garbage_received - self.get_garbage_left()
+    }
+
+    pub fn render_dig_stats(&mut self, canvas: &mut Canvas, pos: (f32, f32), scl: f32) {
+        let elapsed = if self.game_over {
+            self.final_time
+        } else {
+            self.clock.elapsed(self.start_time)
+        };
+        let dug = self.get_garbage_dug();
+        let pieces_per_line = if dug > 0 {
+            format!("{:.2}", self.pieces as f32 / dug as f32)
+        } else {
+            "-".to_string()
+        };
+
+        let stats = [
+            ("Pieces/Line", pieces_per_line, 368.),
+            ("Garbage", format!("{}/{}", dug, self.dig_goal), 496.),
+            ("Time", get_formatted_time(elapsed), 592.),
+        ];
+
+        draw_stat_column(canvas, pos, scl, &stats);
+    }
+}
+
+// Highscore file of a Dig game, every line goal and messiness has its own
+pub fn get_dig_highscore_path(dig_goal: usize, dig_messy: bool) -> &'static str {
+    let index = DIG_LINE_GOALS
+        .iter()
+        .position(|goal| *goal == dig_goal)
+        .unwrap_or(0);
+    DIG_HIGHSCORE_PATHS[dig_messy as usize][index]
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use ggez::graphics::Canvas;
+
+use crate::{
+    consts::{MASTER_GM_TIME, MASTER_GRADES, MASTER_MAX_LEVEL, MASTER_TIMINGS},
+    ui_components::stat_formatting::*,
+    Game,
+};
+
+// Grade points of a single, double, triple and tetris, multiplied by the level bonus
+const CLEAR_GRADE_POINTS: [usize; 4] = [1, 3, 6, 10];
+
+/*
+    Master levels go from 0 to MASTER_MAX_LEVEL. Every piece raises the
+    level by one, except on the last level of a section (x99), which only
+    line clears can get past. Every cleared line raises it by one.
+*/
+impl Game {
+    // Called when the game is reset, Master starts at level 0
+    pub fn start_master(&mut self) {
+        self.level = 0;
+        self.set_master_timing();
+    }
+
+    fn set_master_timing(&mut self) {
+        let (_, gravity, are, line_clear_delay, lock_delay, max_actions) = MASTER_TIMINGS
+            .iter()
+            .rev()
+            .find(|(level, ..)| *level <= self.level)
+            .unwrap();
+        self.set_gravity_hard(*gravity);
+        self.are = Duration::from_millis(*are);
+        self.line_clear_delay = Duration::from_millis(*line_clear_delay);
+        self.lock_delay = Duration::from_millis(*lock_delay);
+        self.max_actions = *max_actions;
+    }
+
+    // Called after every placed piece
+    pub fn update_master(&mut self, lines_cleared: usize) {
+        if lines_cleared > 0 {
+            // Clears are worth more the higher the level
+            let bonus = 1 + self.level / 250;
+            self.grade_points += CLEAR_GRADE_POINTS[lines_cleared.min(4) - 1] * bonus;
+            self.level += lines_cleared;
+        } else if self.level % 100 != 99 && self.level < MASTER_MAX_LEVEL - 1 {
+            self.level += 1;
+        }
+
+        if self.level >= MASTER_MAX_LEVEL {
+            self.level = MASTER_MAX_LEVEL;
+            self.end_game(true);
+        }
+        self.set_master_timing();
+    }
+
+    pub fn get_master_grade(&self) -> &'static str {
+        let (grade, _) = MASTER_GRADES
+            .iter()
+            .rev()
+            .find(|(_, points)| *points <= self.grade_points)
+            .unwrap();
+
+        if *grade == MASTER_GRADES[MASTER_GRADES.len() - 1].0
+            && self.objective_completed
+            && self.final_time <= Duration::from_secs(MASTER_GM_TIME)
+        {
+            return "GM";
+        }
+        grade
+    }
+
+    pub fn render_master_stats(&mut self, canvas: &mut Canvas, pos: (f32, f32), scl: f32) {
+        let elapsed = if self.game_over {
+            self.final_time
+        } else {
+            self.clock.elapsed(self.start_time)
+        };
+        // The section stop, or the last level
+        let section_end = (self.level / 100 * 100 + 100).min(MASTER_MAX_LEVEL);
+
+        let mut stats = vec![
+            ("Level", format!("{}/{}", self.level, section_end), 368.),
+            ("Time", get_formatted_time(elapsed), 592.),
+        ];
+        // The grade is only given at the end
+        if self.game_over {
+            stats.push(("Grade", self.get_master_grade().to_string(), 496.));
+        }
+
+        draw_stat_column(canvas, pos, scl, &stats);
+    }
+}
@@ -1,4 +1,8 @@
 pub mod marathon;
-pub mod fourty_lines;
+pub mod sprint;
 pub mod versus;
-pub mod survival;
\ No newline at end of file
+pub mod survival;
+pub mod pc_training;
+pub mod ultra;
+pub mod dig;
+pub mod master;
\ No newline at end of file
@@ -0,0 +1,36 @@
+use ggez::graphics::Canvas;
+
+use crate::{
+    board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS},
+    bots::{bitboard::BitBoard, pc_solver::PcSolver},
+    ui_components::stat_formatting::draw_stat_column,
+    Game,
+};
+
+impl Game {
+    // Runs after every placed piece. Counts perfect clears and starts over when one is no longer possible
+    pub fn update_pc_training(&mut self) {
+        let solver = PcSolver::default();
+
+        if self.board.iter().all(|row| row.iter().all(|c| c.is_none())) {
+            self.perfect_clears += 1;
+        } else if !solver.is_possible(&BitBoard::from_board(&self.board)) {
+            // The queue goes on, only the board is cleared
+            self.failed_clears += 1;
+            self.board = [[None; BOARD_AMOUNT_COLUMNS]; BOARD_AMOUNT_ROWS];
+        }
+
+        self.pc_hint = solver
+            .solve(self)
+            .map(|steps| steps[0].placement.piece.clone());
+    }
+
+    pub fn render_pc_training_stats(&mut self, canvas: &mut Canvas, pos: (f32, f32), scl: f32) {
+        let stats = [
+            ("Perfect clears", self.perfect_clears.to_string(), 496.),
+            ("Failed", self.failed_clears.to_string(), 592.),
+        ];
+
+        draw_stat_column(canvas, pos, scl, &stats);
+    }
+}
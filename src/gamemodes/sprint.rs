@@ -1,13 +1,28 @@
 use ggez::{glam, graphics::{self, Canvas, Color, PxScale, Text, TextAlign, TextFragment, TextLayout}};
 
-use crate::{ui_components::stat_formatting::*, Game};
+use crate::{consts::{SPRINT_HIGHSCORE_PATHS, SPRINT_LINE_GOALS, SPRINT_SPLIT_LINES}, ui_components::stat_formatting::*, Game};
+
+// Splits shown next to the queue, the latest ones
+const SHOWN_SPLITS: usize = 10;
 
 impl Game {
-    pub fn render_40l_stats(&mut self, canvas: &mut Canvas, pos: (f32, f32), scl: f32) {
+    // Called after every placed piece. Takes a split for every SPRINT_SPLIT_LINES lines and ends the game at the goal
+    pub fn update_sprint(&mut self) {
+        let elapsed = self.clock.elapsed(self.start_time);
+        while self.splits.len() < self.lines.min(self.line_goal) / SPRINT_SPLIT_LINES {
+            self.splits.push(elapsed);
+        }
+
+        if self.lines >= self.line_goal {
+            self.end_game(true);
+        }
+    }
+
+    pub fn render_sprint_stats(&mut self, canvas: &mut Canvas, pos: (f32, f32), scl: f32) {
         let elapsed = if self.game_over {
             self.final_time
         } else {
-            self.start_time.elapsed()
+            self.clock.elapsed(self.start_time)
         };
 
         // Pieces
@@ -78,7 +93,7 @@ impl Game {
         );
 
         let mut line_count = Text::new(TextFragment{
-            text: format!("{}/40", self.lines),
+            text: format!("{}/{}", self.lines, self.line_goal),
             font: Some("Tetris font".to_string()),
             color: Some(Color::WHITE), 
             scale: Some(PxScale::from(24.))
@@ -131,5 +146,37 @@ impl Game {
                 .dest(glam::Vec2::new(pos.0 + 156. * scl, pos.1 + 624. * scl))
                 .scale(glam::Vec2::new(scl, scl))
         );
+
+        self.render_sprint_splits(canvas, pos, scl);
+    }
+
+    fn render_sprint_splits(&mut self, canvas: &mut Canvas, pos: (f32, f32), scl: f32) {
+        let first = self.splits.len().saturating_sub(SHOWN_SPLITS);
+        for (i, split) in self.splits.iter().enumerate().skip(first) {
+            let mut text = Text::new(TextFragment{
+                text: format!("{}L {}", (i + 1) * SPRINT_SPLIT_LINES, get_formatted_time(*split)),
+                font: Some("Tetris font".to_string()),
+                color: Some(Color::WHITE),
+                scale: Some(PxScale::from(16.))
+            });
+            text.set_layout(TextLayout{
+                h_align: TextAlign::Begin,
+                v_align: TextAlign::Middle
+            });
+            canvas.draw(&text,
+                graphics::DrawParam::new()
+                    .dest(glam::Vec2::new(pos.0 + 712. * scl, pos.1 + (96. + (i - first) as f32 * 32.) * scl))
+                    .scale(glam::Vec2::new(scl, scl))
+            );
+        }
     }
 }
+
+// Highscore file of a sprint, every line goal has its own
+pub fn get_sprint_highscore_path(line_goal: usize) -> &'static str {
+    let index = SPRINT_LINE_GOALS
+        .iter()
+        .position(|goal| *goal == line_goal)
+        .unwrap_or(1);
+    SPRINT_HIGHSCORE_PATHS[index]
+}
@@ -1,13 +1,29 @@
+use std::time::Duration;
+
 use ggez::{glam, graphics::{self, Canvas, Color, PxScale, Text, TextAlign, TextFragment, TextLayout}};
+use rand::Rng;
 
-use crate::{ui_components::stat_formatting::*, Game};
+use crate::{consts::SURVIVAL_TIMER, ui_components::stat_formatting::*, Game};
 
 impl Game {
+    // Adds a garbage row every SURVIVAL_TIMER millis
+    pub fn update_survival_garbage(&mut self) {
+        if let Some(t) = self.garbage_timer {
+            if self.clock.elapsed(t) >= Duration::from_millis(SURVIVAL_TIMER) {
+                self.garbage_timer = Some(t + Duration::from_millis(SURVIVAL_TIMER));
+                let column = self.garbage_rng.random_range(0..9);
+                self.add_garbage_row(column);
+            }
+        } else {
+            self.garbage_timer = Some(self.clock.now());
+        }
+    }
+
     pub fn render_survival_stats(&mut self, canvas: &mut Canvas, pos: (f32, f32), scl: f32) {
         let elapsed = if self.game_over {
             self.final_time
         } else {
-            self.start_time.elapsed()
+            self.clock.elapsed(self.start_time)
         };
 
         // Garbage lines
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use ggez::{glam, graphics::{self, Canvas, Color, PxScale, Text, TextAlign, TextFragment, TextLayout}};
+
+use crate::{consts::{ULTRA_HIGHSCORE_PATHS, ULTRA_TIME_LIMITS}, ui_components::stat_formatting::*, Game};
+
+// Ultra is scored like marathon but stays at level 1, lines never level it up
+impl Game {
+    // Ends the game once the time limit is up
+    pub fn update_ultra_timer(&mut self) {
+        if !self.game_over && self.clock.elapsed(self.start_time) >= self.time_limit {
+            self.end_game(true);
+        }
+    }
+
+    pub fn get_time_left(&self) -> Duration {
+        if self.countdown_start.is_some() {
+            return self.time_limit;
+        }
+        let elapsed = if self.game_over {
+            self.final_time
+        } else {
+            self.clock.elapsed(self.start_time)
+        };
+        self.time_limit.saturating_sub(elapsed)
+    }
+
+    pub fn render_ultra_stats(&mut self, canvas: &mut Canvas, pos: (f32, f32), scl: f32) {
+        // Score
+        let formatted_score = get_formatted_score(self.score);
+        let mut score = Text::new(TextFragment{
+            text: formatted_score,
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(24.))
+        });
+        score.set_layout(TextLayout::center());
+        canvas.draw(&score,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(pos.0 + 328. * scl, pos.1 + 668. * scl))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+
+        // Lines
+        let mut lines = Text::new(TextFragment{
+            text: "Lines".to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(16.))
+        });
+        lines.set_layout(TextLayout{
+            h_align: TextAlign::End,
+            v_align: TextAlign::Middle
+        });
+        canvas.draw(&lines,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(pos.0 + 156. * scl, pos.1 + 496. * scl))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+
+        let mut line_count = Text::new(TextFragment{
+            text: self.lines.to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(24.))
+        });
+        line_count.set_layout(TextLayout{
+            h_align: TextAlign::End,
+            v_align: TextAlign::Middle
+        });
+        canvas.draw(&line_count,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(pos.0 + 156. * scl, pos.1 + 528. * scl))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+
+        // Time left
+        let mut time = Text::new(TextFragment{
+            text: "Time left".to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(16.))
+        });
+        time.set_layout(TextLayout{
+            h_align: TextAlign::End,
+            v_align: TextAlign::Middle
+        });
+        canvas.draw(&time,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(pos.0 + 156. * scl, pos.1 + 592. * scl))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+
+        let mut time_left = Text::new(TextFragment{
+            text: get_formatted_time(self.get_time_left()),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(24.))
+        });
+        time_left.set_layout(TextLayout{
+            h_align: TextAlign::End,
+            v_align: TextAlign::Middle
+        });
+        canvas.draw(&time_left,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(pos.0 + 156. * scl, pos.1 + 624. * scl))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+    }
+}
+
+// Highscore file of an Ultra game, every time limit has its own
+pub fn get_ultra_highscore_path(time_limit: Duration) -> &'static str {
+    let index = ULTRA_TIME_LIMITS
+        .iter()
+        .position(|secs| Duration::from_secs(*secs) == time_limit)
+        .unwrap_or(0);
+    ULTRA_HIGHSCORE_PATHS[index]
+}
@@ -1,7 +1,6 @@
-use std::time::Instant;
-use rand::random_range;
+use rand::Rng;
 
-use crate::{board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS}, consts::{GARBAGE_CAP, GARBAGE_DELAY}, Game, PieceType};
+use crate::{board::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS}, clock::SimInstant, consts::{GARBAGE_CAP, GARBAGE_DELAY}, Game, PieceType};
 
 impl Game {
     pub fn send_garbage(&mut self, mut amount: usize) {
@@ -10,14 +9,26 @@ impl Game {
         amount = self.negate_garbage(amount);
 
         if amount > 0 {
-            let column: usize = random_range(0..10);
-            let garbage = (column, amount, Some(Instant::now()));
+            let column: usize = self.garbage_rng.random_range(0..10);
+            let garbage = (column, amount, Some(self.clock.now()));
             self.garbage_outbound.push_back(garbage);
         }
     }
 
-    pub fn receive_garbage(&mut self, garbage: (usize, usize, Option<Instant>)) {
-        self.garbage_inbound.push_back(garbage);
+    pub fn receive_garbage(&mut self, garbage: (usize, usize, Option<SimInstant>)) {
+        // The cooldown is restarted on our own clock since the sender's clock may differ
+        let (column, amount, cooldown) = garbage;
+        let cooldown = cooldown.map(|_| self.clock.now());
+        self.garbage_inbound.push_back((column, amount, cooldown));
+    }
+
+    // Moves all outbound garbage to `other`. Returns true if anything was sent
+    pub fn send_outbound_garbage(&mut self, other: &mut Game) -> bool {
+        let sent = !self.garbage_outbound.is_empty();
+        while let Some(garbage) = self.garbage_outbound.pop_front() {
+            other.receive_garbage(garbage);
+        }
+        sent
     }
 
     pub fn recieve_ready_garbage(&mut self) {
@@ -62,9 +73,10 @@ impl Game {
     }
 
     pub fn update_garbage(&mut self) {
-        self.garbage_inbound.iter_mut().for_each(|garbage: &mut (usize, usize, Option<Instant>)| {
+        let now = self.clock.now();
+        self.garbage_inbound.iter_mut().for_each(|garbage: &mut (usize, usize, Option<SimInstant>)| {
             if let Some(t) = garbage.2 {
-                if t.elapsed().as_millis() >= GARBAGE_DELAY {
+                if now.duration_since(t).as_millis() >= GARBAGE_DELAY {
                     garbage.2 = None
                 }
             }
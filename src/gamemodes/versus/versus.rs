@@ -66,7 +66,7 @@ impl Game {
         let elapsed = if self.game_over {
             self.final_time
         } else {
-            self.start_time.elapsed()
+            self.clock.elapsed(self.start_time)
         };
 
         // Pieces
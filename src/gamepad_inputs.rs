@@ -0,0 +1,166 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use ggez::event::{Axis, Button, GamepadId};
+
+use crate::config::input_config::*;
+use crate::game_inputs::InputFrame;
+
+pub const STICK_DEADZONE: f32 = 0.5;
+
+// Gamepads are numbered in the order they were first seen
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GamepadEvent {
+    ButtonDown(usize, Button),
+    ButtonUp(usize, Button),
+    Axis(usize, Axis, f32),
+}
+
+/*
+    Anything that gamepad events can be read from. The ggez callbacks push
+    their events into a VecDeque, tests use a fake source instead.
+*/
+pub trait GamepadEventSource {
+    fn next_event(&mut self) -> Option<GamepadEvent>;
+}
+
+impl GamepadEventSource for VecDeque<GamepadEvent> {
+    fn next_event(&mut self) -> Option<GamepadEvent> {
+        self.pop_front()
+    }
+}
+
+// Button state of every gamepad, updated once per frame
+#[derive(Default)]
+pub struct GamepadState {
+    pad_ids: Vec<GamepadId>,
+    pub players: [Option<usize>; 2], // Gamepad of each player
+    pressed: HashSet<(usize, Button)>,
+    just_pressed: HashSet<(usize, Button)>,
+    just_released: HashSet<(usize, Button)>,
+    sticks: HashMap<(usize, Axis), i8>, // Direction each stick axis is pushed in (-1, 0 or 1)
+}
+
+impl GamepadState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_pad_index(&mut self, id: GamepadId) -> usize {
+        match self.pad_ids.iter().position(|p| *p == id) {
+            Some(i) => i,
+            None => {
+                self.pad_ids.push(id);
+                self.pad_ids.len() - 1
+            }
+        }
+    }
+
+    // Reads every pending event. Call once per frame before the inputs are used
+    pub fn poll(&mut self, source: &mut impl GamepadEventSource) {
+        while let Some(event) = source.next_event() {
+            match event {
+                GamepadEvent::ButtonDown(pad, button) => self.button_down(pad, button),
+                GamepadEvent::ButtonUp(pad, button) => self.button_up(pad, button),
+                GamepadEvent::Axis(pad, axis, value) => self.axis_changed(pad, axis, value),
+            }
+        }
+    }
+
+    // Clears the just pressed and released buttons. Call at the end of every frame
+    pub fn end_frame(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+
+    fn button_down(&mut self, pad: usize, button: Button) {
+        // The first pads to press something become player one and two
+        if !self.players.contains(&Some(pad)) {
+            if let Some(slot) = self.players.iter_mut().find(|p| p.is_none()) {
+                *slot = Some(pad);
+            }
+        }
+
+        if self.pressed.insert((pad, button)) {
+            self.just_pressed.insert((pad, button));
+        }
+    }
+
+    fn button_up(&mut self, pad: usize, button: Button) {
+        if self.pressed.remove(&(pad, button)) {
+            self.just_released.insert((pad, button));
+        }
+    }
+
+    // Sticks and analog D-pads are turned into D-pad button presses
+    fn axis_changed(&mut self, pad: usize, axis: Axis, value: f32) {
+        let (negative, positive) = match axis {
+            Axis::LeftStickX | Axis::DPadX => (Button::DPadLeft, Button::DPadRight),
+            Axis::LeftStickY | Axis::DPadY => (Button::DPadDown, Button::DPadUp),
+            _ => return,
+        };
+        let direction = if value <= -STICK_DEADZONE {-1} else if value >= STICK_DEADZONE {1} else {0};
+        let previous = self.sticks.insert((pad, axis), direction).unwrap_or(0);
+        if direction == previous {
+            return;
+        }
+
+        match previous {
+            -1 => self.button_up(pad, negative),
+            1 => self.button_up(pad, positive),
+            _ => (),
+        }
+        match direction {
+            -1 => self.button_down(pad, negative),
+            1 => self.button_down(pad, positive),
+            _ => (),
+        }
+    }
+
+    pub fn is_button_just_pressed(&self, pad: usize, button: Button) -> bool {
+        self.just_pressed.contains(&(pad, button))
+    }
+
+    pub fn is_button_just_released(&self, pad: usize, button: Button) -> bool {
+        self.just_released.contains(&(pad, button))
+    }
+
+    // Buttons pressed this frame on the gamepad of `player`
+    pub fn get_just_pressed_buttons(&self, player: usize) -> Vec<Button> {
+        match self.players[player] {
+            Some(pad) => self.just_pressed.iter().filter(|(p, _)| *p == pad).map(|(_, b)| *b).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Same as InputFrame::from_keyboard but for the gamepad of `player`
+    pub fn get_player_frame(&self, player: usize, bindings: &HashMap<GameAction, Button>) -> InputFrame {
+        let mut frame = InputFrame::new();
+        let Some(pad) = self.players[player] else {
+            return frame;
+        };
+
+        for action in ALL_GAME_ACTIONS {
+            if let Some(button) = bindings.get(&action) {
+                if self.is_button_just_pressed(pad, *button) {
+                    frame.press(action);
+                }
+                if self.is_button_just_released(pad, *button) {
+                    frame.release(action);
+                }
+            }
+        }
+        frame
+    }
+
+    pub fn any_pressed(&self, button: Button) -> bool {
+        self.pressed.iter().any(|(_, b)| *b == button)
+    }
+
+    pub fn any_just_pressed(&self, button: Button) -> bool {
+        self.just_pressed.iter().any(|(_, b)| *b == button)
+    }
+
+    pub fn any_just_released(&self, button: Button) -> bool {
+        self.just_released.iter().any(|(_, b)| *b == button)
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use ggez::event::{Axis, Button};
+
+    use crate::{
+        config::config_file::{KeybindConfig, RebindResult},
+        gamepad_inputs::{GamepadEvent, GamepadEventSource, GamepadState},
+        menu_inputs::{MenuAction, MenuInputs},
+        GameAction,
+    };
+
+    // Scripted gamepad events, used instead of real hardware
+    #[derive(Default)]
+    struct FakeGamepad {
+        events: VecDeque<GamepadEvent>,
+    }
+
+    impl FakeGamepad {
+        fn press(&mut self, pad: usize, button: Button) {
+            self.events.push_back(GamepadEvent::ButtonDown(pad, button));
+        }
+
+        fn release(&mut self, pad: usize, button: Button) {
+            self.events.push_back(GamepadEvent::ButtonUp(pad, button));
+        }
+
+        fn stick(&mut self, pad: usize, axis: Axis, value: f32) {
+            self.events.push_back(GamepadEvent::Axis(pad, axis, value));
+        }
+    }
+
+    impl GamepadEventSource for FakeGamepad {
+        fn next_event(&mut self) -> Option<GamepadEvent> {
+            self.events.pop_front()
+        }
+    }
+
+    #[test]
+    fn test_pads_are_assigned_in_order_of_first_press() {
+        let mut fake = FakeGamepad::default();
+        let mut gamepads = GamepadState::new();
+
+        fake.press(3, Button::South);
+        fake.press(1, Button::South);
+        fake.press(3, Button::East);
+        fake.press(5, Button::South);
+        gamepads.poll(&mut fake);
+
+        assert_eq!(gamepads.players, [Some(3), Some(1)]);
+    }
+
+    #[test]
+    fn test_player_frame_uses_bindings() {
+        let mut fake = FakeGamepad::default();
+        let mut gamepads = GamepadState::new();
+        let bindings = KeybindConfig::default().gamepad;
+
+        fake.press(0, Button::South);
+        fake.press(1, Button::DPadUp);
+        gamepads.poll(&mut fake);
+
+        let frame_one = gamepads.get_player_frame(0, &bindings[0]);
+        let frame_two = gamepads.get_player_frame(1, &bindings[1]);
+        assert!(frame_one.is_pressed(GameAction::RotateCcw));
+        assert!(!frame_one.is_pressed(GameAction::HardDrop));
+        assert!(frame_two.is_pressed(GameAction::HardDrop));
+
+        // Held buttons are only reported in the frame they were pressed
+        gamepads.end_frame();
+        fake.release(0, Button::South);
+        gamepads.poll(&mut fake);
+
+        let frame_one = gamepads.get_player_frame(0, &bindings[0]);
+        assert!(frame_one.is_released(GameAction::RotateCcw));
+        assert!(gamepads.get_player_frame(1, &bindings[1]).is_empty());
+    }
+
+    #[test]
+    fn test_stick_acts_as_dpad() {
+        let mut fake = FakeGamepad::default();
+        let mut gamepads = GamepadState::new();
+        let bindings = KeybindConfig::default().gamepad;
+
+        fake.stick(0, Axis::LeftStickX, 0.2);
+        gamepads.poll(&mut fake);
+        assert!(gamepads.get_player_frame(0, &bindings[0]).is_empty());
+
+        fake.stick(0, Axis::LeftStickX, -0.9);
+        gamepads.poll(&mut fake);
+        assert!(gamepads.get_player_frame(0, &bindings[0]).is_pressed(GameAction::MoveLeft));
+        gamepads.end_frame();
+
+        // Flicking straight to the other side releases left and presses right
+        fake.stick(0, Axis::LeftStickX, 0.9);
+        gamepads.poll(&mut fake);
+        let frame = gamepads.get_player_frame(0, &bindings[0]);
+        assert!(frame.is_released(GameAction::MoveLeft));
+        assert!(frame.is_pressed(GameAction::MoveRight));
+    }
+
+    #[test]
+    fn test_menu_navigation_with_gamepad() {
+        let mut fake = FakeGamepad::default();
+        let mut gamepads = GamepadState::new();
+        let mut menuinputs = MenuInputs::pc_inputs();
+        let keyboard = ggez::input::keyboard::KeyboardContext::default();
+
+        fake.press(2, Button::DPadDown);
+        fake.press(2, Button::South);
+        gamepads.poll(&mut fake);
+        menuinputs.update_gamepads(&gamepads);
+
+        assert!(menuinputs.is_just_pressed(&keyboard, MenuAction::Down));
+        assert!(menuinputs.is_just_pressed(&keyboard, MenuAction::Select));
+        assert!(!menuinputs.is_just_pressed(&keyboard, MenuAction::Up));
+
+        gamepads.end_frame();
+        menuinputs.update_gamepads(&gamepads);
+        assert!(!menuinputs.is_just_pressed(&keyboard, MenuAction::Down));
+        assert!(menuinputs.is_pressed(&keyboard, MenuAction::Down));
+    }
+
+    #[test]
+    fn test_rebind_button_swaps() {
+        let mut keybinds = KeybindConfig::default();
+        let hold = keybinds.gamepad[1][&GameAction::Hold];
+
+        assert_eq!(keybinds.rebind_button(1, GameAction::Hold, Button::West), RebindResult::Bound);
+        assert_eq!(
+            keybinds.rebind_button(1, GameAction::HardDrop, Button::West),
+            RebindResult::Swapped(GameAction::Hold)
+        );
+        assert_eq!(keybinds.gamepad[1][&GameAction::HardDrop], Button::West);
+        assert_ne!(keybinds.gamepad[1][&GameAction::Hold], hold);
+        assert_eq!(keybinds.gamepad[0], KeybindConfig::default().gamepad[0]);
+    }
+}
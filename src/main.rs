@@ -1,25 +1,42 @@
 mod animation_state;
 mod board;
+mod clock;
 mod bots;
 mod config;
 mod consts;
 mod game;
 mod game_inputs;
+mod gamepad_inputs;
 mod menu_inputs;
 mod piece;
+mod randomizer;
+mod replay;
 mod rotation;
 mod scoring;
 mod ui_components;
 mod gamemodes;
+mod game_tests;
+mod gamepad_tests;
 
 use animation_state::AnimationState;
+use bots::arena::{print_arena_report, run_arena, ArenaConfig};
 use bots::bot::Bot;
-use bots::train_bot::train_ai;
+use bots::coach::Coach;
+use bots::weights::Weights;
+use bots::tbp_client::TbpClient;
+use bots::tbp_server::run_tbp_server;
+use bots::train_bot::{train_ai, TrainConfig};
+use config::config_file::Config;
 use consts::*;
+use gamemodes::dig::get_dig_highscore_path;
+use gamemodes::sprint::get_sprint_highscore_path;
+use gamemodes::ultra::get_ultra_highscore_path;
+use gamepad_inputs::{GamepadEvent, GamepadState};
 use csv::{Reader, Writer};
 use menu_inputs::*;
-use rand::{random_range, Rng};
-use std::collections::HashMap;
+use rand::Rng;
+use replay::{update_recorder, ReplayPlayer, ReplayRecorder};
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
 use std::path;
 use std::str::FromStr;
@@ -33,6 +50,7 @@ pub use crate::rotation::{ROTATION_180, ROTATION_CCW, ROTATION_CW};
 
 use ggez::graphics::Image;
 use ggez::input::keyboard::KeyCode;
+use ggez::event::{Axis, Button, GamepadId};
 use ggez::{conf, event, graphics, Context, ContextBuilder, GameResult};
 
 struct AppState {
@@ -55,20 +73,32 @@ struct AppState {
 
     // Bots,
     bot: Bot,
+    tbp_command: Option<String>, // External bot that plays instead of ours, see --tbp-opponent
+    tbp_opponent: Option<TbpClient>,
+    coach: Coach,
+
+    config: Config,
+
+    // Replays
+    replay_recorder: Option<ReplayRecorder>,
+    replay_player: Option<ReplayPlayer>,
 
     menuinputs : MenuInputs,
+    gamepads: GamepadState,
+    gamepad_events: VecDeque<GamepadEvent>, // Filled by the ggez gamepad callbacks, read every update
 }
 
 impl AppState {
     fn new(
         ctx: &mut Context,
-        args: Option<Vec<HashMap<GameAction, KeyCode>>>,
+        drifarkaden: bool,
+        tbp_command: Option<String>,
     ) -> GameResult<AppState> {
         let mut rng = rand::rng();
         let id = Some(rng.random());
 
         let mut state = AppState {
-            animation_state: AnimationState::new(get_scores_from_file("res/highscores/highscore_survival.csv")),
+            animation_state: AnimationState::new(get_scores_from_file(HIGHSCORE_TABLES[0].1)),
             screen_state: ScreenState::StartScreen,
             drifarkaden: false,
 
@@ -83,21 +113,37 @@ impl AppState {
             game_two: Game::new(GAME_2_VS_POS, GAME_2_VS_SCL, id.unwrap()),
 
             bot: Bot::new(0, id.unwrap()),
-            menuinputs : MenuInputs::pc_inputs()
+            tbp_command,
+            tbp_opponent: None,
+            coach: Coach::new(Weights::load()),
+
+            config: Config::load(),
+
+            replay_recorder: None,
+            replay_player: None,
+            menuinputs : MenuInputs::pc_inputs(),
+            gamepads: GamepadState::new(),
+            gamepad_events: VecDeque::new(),
         };
 
         state.bot.game.canvas_pos = GAME_2_VS_POS; 
         state.bot.game.canvas_scl = GAME_2_VS_SCL;
 
+        state.game_one.randomizer_type = state.config.randomizer;
+        state.game_two.randomizer_type = state.config.randomizer;
         state.game_one.reset_game(id);
         state.game_two.reset_game(id);
 
-        if let Some(keybinds) = args {
-            state.game_one.controls = keybinds[0].clone();
-            state.game_two.controls = keybinds[1].clone();
+        state.config.handling[0].apply(&mut state.game_one);
+        state.config.handling[1].apply(&mut state.game_two);
+
+        if drifarkaden {
             state.drifarkaden = true;
             state.menuinputs = MenuInputs::drifarkaden_inputs();
         }
+        let [controls_one, controls_two] = state.config.get_controls(state.drifarkaden, false);
+        state.game_one.controls = controls_one;
+        state.game_two.controls = controls_two;
 
         Ok(state)
     }
@@ -250,6 +296,9 @@ pub fn save_scores_to_file(path: &str, scores: Vec<(String, usize)>) -> bool {
 
 impl event::EventHandler<ggez::GameError> for AppState {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
+        self.gamepads.poll(&mut self.gamepad_events);
+        self.menuinputs.update_gamepads(&self.gamepads);
+        let pad_inputs = [0, 1].map(|p| self.gamepads.get_player_frame(p, &self.config.keybinds.gamepad[p]));
 
         if self.game_one.game_over && self.game_one.continue_to_highscore {
 
@@ -259,13 +308,18 @@ impl event::EventHandler<ggez::GameError> for AppState {
                 let name = &self.animation_state.name_input;
                 let path = match self.game_one.gamemode {
                     GameMode::Marathon => "res/highscores/highscore_marathon.csv",
-                    GameMode::FourtyLines => "res/highscores/highscore_fourty_lines.csv",
+                    GameMode::Sprint => get_sprint_highscore_path(self.game_one.line_goal),
+                    GameMode::Ultra => get_ultra_highscore_path(self.game_one.time_limit),
+                    GameMode::Dig => get_dig_highscore_path(self.game_one.dig_goal, self.game_one.dig_messy),
+                    GameMode::Master => MASTER_HIGHSCORE_PATH,
                     _ => "res/highscores/highscore_survival.csv"
                 };
-                if self.game_one.gamemode == GameMode::FourtyLines{
+                if self.game_one.gamemode == GameMode::Sprint || self.game_one.gamemode == GameMode::Dig {
                     let _ = Self::save_score(name.to_string(), self.game_one.final_time.as_millis() as usize, path, false);
                 }else if self.game_one.gamemode == GameMode::Survival {
                     let _ = Self::save_score(name.to_string(), self.game_one.final_time.as_millis() as usize, path, true);
+                }else if self.game_one.gamemode == GameMode::Master {
+                    let _ = Self::save_score(name.to_string(), self.game_one.level, path, true);
                 }else{
                     let _ = Self::save_score(name.to_string(), self.game_one.score, path,true);
                 }
@@ -279,26 +333,27 @@ impl event::EventHandler<ggez::GameError> for AppState {
         }
         match self.screen_state {
             ScreenState::Singleplayer => {
-                self.game_one.update(ctx);
+                if self.menuinputs.is_just_pressed(&ctx.keyboard, MenuAction::Coach) {
+                    self.coach.toggle(&mut self.game_one);
+                }
+                let inputs = self.game_one.update(ctx, pad_inputs[0].clone());
+                update_recorder(&mut self.replay_recorder, &[&self.game_one], &[inputs], false);
+                if self.game_one.gamemode == GameMode::PcTraining {
+                    // The coach shows the next piece of the perfect clear instead
+                    self.game_one.hint_piece = self.game_one.pc_hint.clone().filter(|_| self.coach.enabled);
+                } else if self.game_one.gamemode == GameMode::Master {
+                    // The placement search ignores 20G and the lowered action limit, so its hints can't be reached
+                    self.game_one.hint_piece = None;
+                } else {
+                    self.coach.update(&mut self.game_one);
+                }
 
-                // 40L top-out check
-                if self.game_one.gamemode == GameMode::FourtyLines
+                // Sprint and dig top-out check
+                if (self.game_one.gamemode == GameMode::Sprint || self.game_one.gamemode == GameMode::Dig)
                 && self.game_one.game_over
                 && !self.game_one.objective_completed {
                     self.animation_state.selected_item_reset_selector = 0;
-                    self.screen_state = ScreenState::FourtyLinesReset
-                }
-
-                // Survival garbage
-                if self.game_one.gamemode == GameMode::Survival && !self.game_one.game_over && self.game_one.countdown_start.is_none() {
-                    if let Some(t) = self.timer {
-                        if t.elapsed() >= Duration::from_millis(SURVIVAL_TIMER) {
-                            self.timer = Some(t + Duration::from_secs(1));
-                            self.game_one.add_garbage_row(random_range(0..9));
-                        }
-                    } else {
-                        self.timer = Some(Instant::now())
-                    }
+                    self.screen_state = ScreenState::RetryPrompt
                 }
             }
             ScreenState::StartScreen => {
@@ -316,7 +371,16 @@ impl event::EventHandler<ggez::GameError> for AppState {
             ScreenState::MarathonPrompt => {
                 handle_marathon_prompt_inputs(ctx, &mut self.screen_state, &mut self.animation_state, &mut self.game_one, &self.menuinputs);
             }
-            ScreenState::FourtyLinesReset => {
+            ScreenState::SprintPrompt => {
+                handle_sprint_prompt_inputs(ctx, &mut self.screen_state, &mut self.animation_state, &mut self.game_one, &self.menuinputs);
+            }
+            ScreenState::UltraPrompt => {
+                handle_ultra_prompt_inputs(ctx, &mut self.screen_state, &mut self.animation_state, &mut self.game_one, &self.menuinputs);
+            }
+            ScreenState::DigPrompt => {
+                handle_dig_prompt_inputs(ctx, &mut self.screen_state, &mut self.animation_state, &mut self.game_one, &self.menuinputs);
+            }
+            ScreenState::RetryPrompt => {
                 handle_reset_screen_inputs(ctx, &mut self.screen_state, &mut self.animation_state, &mut self.game_one,&self.menuinputs);
             }
             ScreenState::Settings => {
@@ -328,9 +392,9 @@ impl event::EventHandler<ggez::GameError> for AppState {
                 if self.game_one.gamemode != GameMode::Versus
                 || self.game_two.gamemode != GameMode::Versus {
                     if !self.drifarkaden {
-                        let vs_controls = multi_controller_keyboard_keybindings();
-                        self.game_one.controls = vs_controls[0].clone();
-                        self.game_two.controls = vs_controls[1].clone();
+                        let [controls_one, controls_two] = self.config.get_controls(false, true);
+                        self.game_one.controls = controls_one;
+                        self.game_two.controls = controls_two;
                     }
 
                     self.game_one.canvas_pos = GAME_1_VS_POS;
@@ -343,7 +407,7 @@ impl event::EventHandler<ggez::GameError> for AppState {
                 if let Some(t) = self.timer {
                     if t.elapsed() >= Duration::from_secs(10) {
                         if !self.drifarkaden {
-                            self.game_one.controls = default_keyboard_keybindings();
+                            self.game_one.controls = self.config.keybinds.solo.clone();
                         }
 
                         self.game_one.canvas_pos = GAME_1_SOLO_POS;
@@ -362,23 +426,23 @@ impl event::EventHandler<ggez::GameError> for AppState {
                     &mut self.animation_state,
                     &mut self.game_one,
                     &mut self.game_two,
+                    &pad_inputs,
                 )
             }
             ScreenState::Versus => {
-                self.game_one.update(ctx);
-                self.game_two.update(ctx);
+                let inputs_one = self.game_one.update(ctx, pad_inputs[0].clone());
+                let inputs_two = self.game_two.update(ctx, pad_inputs[1].clone());
 
                 // Garbage handling
-                while self.game_one.garbage_outbound.len() > 0 {
-                    self.game_two.receive_garbage(
-                        self.game_one.garbage_outbound.pop_front().unwrap()
-                    );
-                }
-                while self.game_two.garbage_outbound.len() > 0 {
-                    self.game_one.receive_garbage(
-                        self.game_two.garbage_outbound.pop_front().unwrap()
-                    );
-                }
+                let sent_one = self.game_one.send_outbound_garbage(&mut self.game_two);
+                let sent_two = self.game_two.send_outbound_garbage(&mut self.game_one);
+
+                update_recorder(
+                    &mut self.replay_recorder,
+                    &[&self.game_one, &self.game_two],
+                    &[inputs_one, inputs_two],
+                    sent_one || sent_two,
+                );
 
                 if self.game_one.game_over || self.game_two.game_over {
                     self.animation_state.players_ready = (false, false);
@@ -390,7 +454,7 @@ impl event::EventHandler<ggez::GameError> for AppState {
                 if let Some(t) = self.timer {
                     if t.elapsed() >= Duration::from_secs(10) {
                         if !self.drifarkaden {
-                            self.game_one.controls = default_keyboard_keybindings();
+                            self.game_one.controls = self.config.keybinds.solo.clone();
                         }
 
                         self.game_one.canvas_pos = GAME_1_SOLO_POS;
@@ -406,6 +470,7 @@ impl event::EventHandler<ggez::GameError> for AppState {
                     &mut self.animation_state,
                     &mut self.game_one,
                     &mut self.game_two,
+                    &pad_inputs,
                 );
             }
 
@@ -415,7 +480,7 @@ impl event::EventHandler<ggez::GameError> for AppState {
             ScreenState::VsBots => {
                 if self.game_one.gamemode != GameMode::Versus {
                     if !self.drifarkaden {
-                        self.game_one.controls = default_keyboard_keybindings();
+                        self.game_one.controls = self.config.keybinds.solo.clone();
                     }
 
                     self.game_one.canvas_pos = GAME_1_VS_POS;
@@ -426,28 +491,26 @@ impl event::EventHandler<ggez::GameError> for AppState {
                 }
 
                 if self.game_one.game_over || self.bot.game.game_over {
-                    if ctx.keyboard.is_key_just_pressed(*self.game_one.controls.get(&GameAction::HardDrop).unwrap()) {
+                    if ctx.keyboard.is_key_just_pressed(*self.game_one.controls.get(&GameAction::HardDrop).unwrap())
+                    || pad_inputs[0].is_pressed(GameAction::HardDrop) {
                         self.game_one.canvas_pos = GAME_1_SOLO_POS;
                         self.game_one.canvas_scl = GAME_1_SOLO_SCL;
 
                         self.screen_state = ScreenState::MainMenu;
                     }
                 } else {
-                    self.game_one.update(ctx);
-                    self.bot.render_bot_game(ctx);
+                    self.game_one.update(ctx, pad_inputs[0].clone());
+                    self.bot.observe_opponent(&self.game_one);
+                    // Our own bot takes over if the external one stops working
+                    match &mut self.tbp_opponent {
+                        Some(client) if !client.has_failed() => self.bot.render_tbp_bot_game(ctx, client),
+                        _ => self.bot.render_bot_game(ctx),
+                    }
                 }
 
                 // Garbage handling
-                while self.game_one.garbage_outbound.len() > 0 {
-                    self.bot.game.receive_garbage(
-                        self.game_one.garbage_outbound.pop_front().unwrap()
-                    );
-                }
-                while self.bot.game.garbage_outbound.len() > 0 {
-                    self.game_one.receive_garbage(
-                        self.bot.game.garbage_outbound.pop_front().unwrap()
-                    );
-                }
+                self.game_one.send_outbound_garbage(&mut self.bot.game);
+                self.bot.game.send_outbound_garbage(&mut self.game_one);
 
             }
             ScreenState::HighScore => {
@@ -456,8 +519,18 @@ impl event::EventHandler<ggez::GameError> for AppState {
             ScreenState::HighscoreInput => {
                 handle_name_inputs(ctx, &mut self.screen_state, &mut self.animation_state, &self.menuinputs);
             }
+            ScreenState::Keybinds => {
+                handle_keybinds_input(ctx, self);
+            }
+            ScreenState::ReplayBrowser => {
+                handle_replay_browser_inputs(ctx, self);
+            }
+            ScreenState::ReplayViewer => {
+                handle_replay_viewer_inputs(ctx, self);
+            }
         }
 
+        self.gamepads.end_frame();
         Ok(())
     }
 
@@ -473,6 +546,9 @@ impl event::EventHandler<ggez::GameError> for AppState {
                     .render_pieces(&self.piece_assets, &mut canvas)
                     .render_stats(&mut canvas)
                     .render_misc(&self.misc_assets, &mut canvas);
+                if self.game_one.gamemode != GameMode::PcTraining && self.game_one.gamemode != GameMode::Master {
+                    coach_overlay::render_coach_overlay(&self.coach, &self.game_one, &mut canvas);
+                }
             }
             ScreenState::StartScreen => {
                 start_screen::render_start_screen(
@@ -515,7 +591,43 @@ impl event::EventHandler<ggez::GameError> for AppState {
                     &mut self.animation_state,
                 );
             }
-            ScreenState::FourtyLinesReset => {
+            ScreenState::SprintPrompt => {
+                let (goal, selected) = self.animation_state.selected_item_sprint_prompt;
+                option_prompt::render_option_prompt(
+                    &self.menu_assets,
+                    &mut canvas,
+                    1.,
+                    "Sprint",
+                    &[("Line Goal", format!("{} lines", SPRINT_LINE_GOALS[goal]))],
+                    selected,
+                );
+            }
+            ScreenState::UltraPrompt => {
+                let (time_limit, selected) = self.animation_state.selected_item_ultra_prompt;
+                option_prompt::render_option_prompt(
+                    &self.menu_assets,
+                    &mut canvas,
+                    1.,
+                    "Ultra",
+                    &[("Time Limit", format!("{} min", ULTRA_TIME_LIMITS[time_limit] / 60))],
+                    selected,
+                );
+            }
+            ScreenState::DigPrompt => {
+                let ([goal, messiness], selected) = self.animation_state.selected_item_dig_prompt;
+                option_prompt::render_option_prompt(
+                    &self.menu_assets,
+                    &mut canvas,
+                    1.,
+                    "Dig",
+                    &[
+                        ("Line Goal", format!("{} lines", DIG_LINE_GOALS[goal])),
+                        ("Garbage", ["Clean", "Messy"][messiness].to_string()),
+                    ],
+                    selected,
+                );
+            }
+            ScreenState::RetryPrompt => {
                 reset_screen::render_reset_screen(
                     &self.menu_assets,
                     &mut canvas,
@@ -590,6 +702,28 @@ impl event::EventHandler<ggez::GameError> for AppState {
                     1.,
                     );
             }
+            ScreenState::Keybinds => {
+                keybinds::render_keybinds(
+                    &mut canvas,
+                    1.,
+                    self
+                );
+            }
+            ScreenState::ReplayBrowser => {
+                replay_browser::render_replay_browser(
+                    &self.menu_assets,
+                    &mut canvas,
+                    1.,
+                    &mut self.animation_state,
+                );
+            }
+            ScreenState::ReplayViewer => {
+                replay_viewer::render_replay_viewer(
+                    self,
+                    &mut canvas,
+                    1.,
+                );
+            }
             ScreenState::VsBots => {
                 //Render game
                 self.bot.game
@@ -610,20 +744,79 @@ impl event::EventHandler<ggez::GameError> for AppState {
         canvas.finish(ctx)?;
         Ok(())
     }
+
+    fn gamepad_button_down_event(&mut self, _ctx: &mut Context, btn: Button, id: GamepadId) -> GameResult {
+        let pad = self.gamepads.get_pad_index(id);
+        self.gamepad_events.push_back(GamepadEvent::ButtonDown(pad, btn));
+        Ok(())
+    }
+
+    fn gamepad_button_up_event(&mut self, _ctx: &mut Context, btn: Button, id: GamepadId) -> GameResult {
+        let pad = self.gamepads.get_pad_index(id);
+        self.gamepad_events.push_back(GamepadEvent::ButtonUp(pad, btn));
+        Ok(())
+    }
+
+    fn gamepad_axis_event(&mut self, _ctx: &mut Context, axis: Axis, value: f32, id: GamepadId) -> GameResult {
+        let pad = self.gamepads.get_pad_index(id);
+        self.gamepad_events.push_back(GamepadEvent::Axis(pad, axis, value));
+        Ok(())
+    }
 }
 
-pub fn check_args() -> Option<Vec<HashMap<GameAction, KeyCode>>> {
+// Returns true if the game should use the --drifarkaden layout
+pub fn check_args() -> bool {
     let args: Vec<String> = std::env::args().collect();
 
     //Sets controls to that of --drifarkaden
     if args.contains(&"--drifarkaden".to_string()) {
-        return Some(default_drivarkaden_keybindings());
+        return true;
     }
     //Runs the program in train ai mode.
-    else if args.contains(&"--train".to_string()) {
-        train_ai();
+    //Everything after --train are training options, see TrainConfig::from_args
+    else if let Some(i) = args.iter().position(|arg| arg == "--train") {
+        match TrainConfig::from_args(&args[i + 1..]) {
+            Ok(config) => {
+                if let Err(e) = train_ai(&config) {
+                    eprintln!("Training failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        std::process::exit(0);
+    }
+    //Runs headless matches between two bots and prints how they did.
+    //Everything after --arena are arena options, see ArenaConfig::from_args
+    else if let Some(i) = args.iter().position(|arg| arg == "--arena") {
+        match ArenaConfig::from_args(&args[i + 1..]) {
+            Ok(config) => print_arena_report(&config, &run_arena(&config)),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        std::process::exit(0);
     }
-    None
+    //Runs our bot as a Tetris Bot Protocol bot on stdin and stdout
+    else if args.contains(&"--tbp".to_string()) {
+        if let Err(e) = run_tbp_server(std::io::stdin().lock(), std::io::stdout().lock()) {
+            eprintln!("TBP bot failed: {}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+    false
+}
+
+// Command of the external TBP bot given with --tbp-opponent
+pub fn get_tbp_command() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let i = args.iter().position(|arg| arg == "--tbp-opponent")?;
+    args.get(i + 1).cloned()
 }
 
 pub fn main() -> GameResult {
@@ -641,7 +834,7 @@ pub fn main() -> GameResult {
     let args = check_args();
 
     let (mut context, event_loop) = context_builder.build().expect("Failed to build context.");
-    let state = AppState::new(&mut context, args).expect("Failed to create state.");
+    let state = AppState::new(&mut context, args, get_tbp_command()).expect("Failed to create state.");
 
     context.gfx.add_font(
         "Tetris font",
@@ -1,8 +1,39 @@
-use std::{f32::INFINITY, time::{Duration, Instant}};
+use std::{collections::HashSet, f32::INFINITY, path::PathBuf, time::{Duration, Instant}};
+
+use ggez::{event::Button, input::keyboard::KeyboardContext};
 
 use rand::Rng;
 
-use crate::{animation_state::AnimationState, consts::{GameMode, SETTINGS_TICK_SPEED}, get_scores_from_file, AppState, Game, GameAction, KeyCode, ScreenState};
+use crate::{animation_state::AnimationState, consts::{GameMode, HIGHSCORE_TABLES, KEYBIND_TIMEOUT, REPLAY_SEEK_TICKS, SETTINGS_TICK_SPEED, DIG_LINE_GOALS, SPRINT_LINE_GOALS, ULTRA_TIME_LIMITS}, get_scores_from_file, AppState, Game, GameAction, KeyCode, ScreenState};
+use crate::config::config_file::{HandlingConfig, KeybindSlot, RebindResult};
+use crate::game_inputs::InputFrame;
+use crate::gamepad_inputs::GamepadState;
+use crate::ALL_GAME_ACTIONS;
+use crate::randomizer::ALL_RANDOMIZERS;
+use crate::replay::{list_replays, Replay, ReplayPlayer, PLAYBACK_SPEEDS};
+use crate::bots::difficulty::BOT_DIFFICULTIES;
+use crate::bots::tbp_client::TbpClient;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MenuAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Back,
+    Coach, // Shows the bot's placement in singleplayer
+}
+
+const ALL_MENU_ACTIONS: [MenuAction; 7] = [
+    MenuAction::Up,
+    MenuAction::Down,
+    MenuAction::Left,
+    MenuAction::Right,
+    MenuAction::Select,
+    MenuAction::Back,
+    MenuAction::Coach,
+];
 
 #[allow(non_snake_case)]
 pub struct MenuInputs {
@@ -10,7 +41,14 @@ pub struct MenuInputs {
     DOWN : KeyCode,
     LEFT : KeyCode,
     RIGHT : KeyCode,
-    SELECT : KeyCode
+    SELECT : KeyCode,
+    BACK : KeyCode,
+    COACH : KeyCode,
+
+    // Menu actions from any gamepad this frame
+    pad_pressed: HashSet<MenuAction>,
+    pad_just_pressed: HashSet<MenuAction>,
+    pad_just_released: HashSet<MenuAction>,
 }
 
 impl MenuInputs {
@@ -21,6 +59,11 @@ impl MenuInputs {
             LEFT: KeyCode::Left, 
             RIGHT: KeyCode::Right, 
             SELECT:KeyCode::Space,
+            BACK: KeyCode::Back,
+            COACH: KeyCode::H,
+            pad_pressed: HashSet::new(),
+            pad_just_pressed: HashSet::new(),
+            pad_just_released: HashSet::new(),
         }
     }
 
@@ -31,24 +74,85 @@ impl MenuInputs {
             LEFT: KeyCode::Left, 
             RIGHT: KeyCode::Right, 
             SELECT:KeyCode::LAlt,
+            BACK: KeyCode::LControl,
+            COACH: KeyCode::H,
+            pad_pressed: HashSet::new(),
+            pad_just_pressed: HashSet::new(),
+            pad_just_released: HashSet::new(),
+        }
+    }
+
+    fn get_key(&self, action: MenuAction) -> KeyCode {
+        match action {
+            MenuAction::Up => self.UP,
+            MenuAction::Down => self.DOWN,
+            MenuAction::Left => self.LEFT,
+            MenuAction::Right => self.RIGHT,
+            MenuAction::Select => self.SELECT,
+            MenuAction::Back => self.BACK,
+            MenuAction::Coach => self.COACH,
+        }
+    }
+
+    fn get_buttons(action: MenuAction) -> &'static [Button] {
+        match action {
+            MenuAction::Up => &[Button::DPadUp],
+            MenuAction::Down => &[Button::DPadDown],
+            MenuAction::Left => &[Button::DPadLeft],
+            MenuAction::Right => &[Button::DPadRight],
+            MenuAction::Select => &[Button::South, Button::Start],
+            MenuAction::Back => &[Button::East],
+            MenuAction::Coach => &[Button::Select],
+        }
+    }
+
+    // Reads the menu actions of all gamepads. Call once per frame after the gamepads are polled
+    pub fn update_gamepads(&mut self, gamepads: &GamepadState) {
+        self.pad_pressed.clear();
+        self.pad_just_pressed.clear();
+        self.pad_just_released.clear();
+
+        for action in ALL_MENU_ACTIONS {
+            let buttons = MenuInputs::get_buttons(action);
+            if buttons.iter().any(|b| gamepads.any_pressed(*b)) {
+                self.pad_pressed.insert(action);
+            }
+            if buttons.iter().any(|b| gamepads.any_just_pressed(*b)) {
+                self.pad_just_pressed.insert(action);
+            }
+            if buttons.iter().any(|b| gamepads.any_just_released(*b)) {
+                self.pad_just_released.insert(action);
+            }
         }
     }
+
+    pub fn is_pressed(&self, keyboard: &KeyboardContext, action: MenuAction) -> bool {
+        keyboard.is_key_pressed(self.get_key(action)) || self.pad_pressed.contains(&action)
+    }
+
+    pub fn is_just_pressed(&self, keyboard: &KeyboardContext, action: MenuAction) -> bool {
+        keyboard.is_key_just_pressed(self.get_key(action)) || self.pad_just_pressed.contains(&action)
+    }
+
+    pub fn is_just_released(&self, keyboard: &KeyboardContext, action: MenuAction) -> bool {
+        keyboard.is_key_just_released(self.get_key(action)) || self.pad_just_released.contains(&action)
+    }
 }
 
 pub fn handle_start_screen_inputs(ctx: &ggez::Context, screen_state: &mut ScreenState, menuinputs : &MenuInputs) {
     let keyboard = &ctx.keyboard;
-    if keyboard.is_key_just_pressed(menuinputs.SELECT) {
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
         *screen_state = ScreenState::MainMenu;
     }
 }
 
 pub fn handle_main_menu_inputs(ctx: &ggez::Context, screen_state: &mut ScreenState, animation_state: &mut AnimationState, menuinputs: &MenuInputs) {
     let keyboard = &ctx.keyboard;
-    if keyboard.is_key_just_pressed(menuinputs.DOWN) {
-        animation_state.selected_item_main_menu = (animation_state.selected_item_main_menu + 1) % 3;
-    } else if keyboard.is_key_just_pressed(menuinputs.UP) {
-        animation_state.selected_item_main_menu = (animation_state.selected_item_main_menu + 2) % 3;
-    } else if keyboard.is_key_just_pressed(menuinputs.SELECT) {
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
+        animation_state.selected_item_main_menu = (animation_state.selected_item_main_menu + 1) % 4;
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
+        animation_state.selected_item_main_menu = (animation_state.selected_item_main_menu + 3) % 4;
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
         *screen_state = match animation_state.selected_item_main_menu {
             0 => {
                 animation_state.selected_item_gamemode_selector = 0;
@@ -58,6 +162,11 @@ pub fn handle_main_menu_inputs(ctx: &ggez::Context, screen_state: &mut ScreenSta
                 animation_state.selected_item_high_score = (0, 0);
                 ScreenState::HighScore
             }
+            2 => {
+                animation_state.selected_item_replay_browser = 0;
+                animation_state.replay_list = get_replay_list();
+                ScreenState::ReplayBrowser
+            }
             _=> {
                 animation_state.selected_item_settings = (0, 0);
                 ScreenState::Settings
@@ -68,11 +177,11 @@ pub fn handle_main_menu_inputs(ctx: &ggez::Context, screen_state: &mut ScreenSta
 
 pub fn handle_gamemode_selector_inputs(ctx: &ggez::Context, screen_state: &mut ScreenState, animation_state: &mut AnimationState, menuinputs: &MenuInputs) {
     let keyboard = &ctx.keyboard;
-    if keyboard.is_key_just_pressed(menuinputs.DOWN) {
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
         animation_state.selected_item_gamemode_selector = (animation_state.selected_item_gamemode_selector + 1) % 4;
-    } else if keyboard.is_key_just_pressed(menuinputs.UP) {
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
         animation_state.selected_item_gamemode_selector = (animation_state.selected_item_gamemode_selector + 3) % 4;
-    } else if keyboard.is_key_just_pressed(menuinputs.SELECT) {
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
         *screen_state = match animation_state.selected_item_gamemode_selector {
             0 => ScreenState::VersusReady,
             1 => {
@@ -90,24 +199,37 @@ pub fn handle_gamemode_selector_inputs(ctx: &ggez::Context, screen_state: &mut S
 
 pub fn handle_singleplayer_selector_inputs(ctx: &ggez::Context, screen_state: &mut ScreenState, animation_state: &mut AnimationState, game: &mut Game, menuinputs: &MenuInputs) {
     let keyboard = &ctx.keyboard;
-    if keyboard.is_key_just_pressed(menuinputs.DOWN) {
-        animation_state.selected_item_singleplayer_selector = (animation_state.selected_item_singleplayer_selector + 1) % 4;
-    } else if keyboard.is_key_just_pressed(menuinputs.UP) {
-        animation_state.selected_item_singleplayer_selector = (animation_state.selected_item_singleplayer_selector + 3) % 4;
-    } else if keyboard.is_key_just_pressed(menuinputs.SELECT) {
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
+        animation_state.selected_item_singleplayer_selector = (animation_state.selected_item_singleplayer_selector + 1) % 8;
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
+        animation_state.selected_item_singleplayer_selector = (animation_state.selected_item_singleplayer_selector + 7) % 8;
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
         let selected = animation_state.selected_item_singleplayer_selector;
         *screen_state = match selected {
             0 => {
                 animation_state.selected_item_marathon_prompt = (1, 0);
                 ScreenState::MarathonPrompt
             }
-            3 => ScreenState::GameModeSelector,
+            1 => {
+                animation_state.selected_item_sprint_prompt = (1, 0);
+                ScreenState::SprintPrompt
+            }
+            3 => {
+                animation_state.selected_item_ultra_prompt = (0, 0);
+                ScreenState::UltraPrompt
+            }
+            4 => {
+                animation_state.selected_item_dig_prompt = ([0, 0], 0);
+                ScreenState::DigPrompt
+            }
+            7 => ScreenState::GameModeSelector,
             _ => {
-                game.reset_game(None);
                 match selected {
-                    1 => game.gamemode = GameMode::FourtyLines,
-                    _ => game.gamemode = GameMode::Survival
+                    2 => game.gamemode = GameMode::Survival,
+                    5 => game.gamemode = GameMode::Master,
+                    _ => game.gamemode = GameMode::PcTraining
                 }
+                game.reset_game(None);
                 ScreenState::Singleplayer
             }
         }
@@ -116,43 +238,112 @@ pub fn handle_singleplayer_selector_inputs(ctx: &ggez::Context, screen_state: &m
 
 pub fn handle_marathon_prompt_inputs(ctx: &ggez::Context, screen_state: &mut ScreenState, animation_state: &mut AnimationState, game: &mut Game, menuinputs: &MenuInputs) {
     let keyboard = &ctx.keyboard;
-    if keyboard.is_key_just_pressed(menuinputs.DOWN) {
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
         animation_state.selected_item_marathon_prompt.1 = (animation_state.selected_item_marathon_prompt.1 + 1) % 3;
     }
-    if keyboard.is_key_just_pressed(menuinputs.UP) {
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
         animation_state.selected_item_marathon_prompt.1 = (animation_state.selected_item_marathon_prompt.1 + 2) % 3;
     }
     
     if animation_state.selected_item_marathon_prompt.1 == 0 {
-        if keyboard.is_key_just_pressed(menuinputs.LEFT) { // Decrease starting level
+        if menuinputs.is_just_pressed(keyboard, MenuAction::Left) { // Decrease starting level
             let mut new_level = animation_state.selected_item_marathon_prompt.0 - 1;
             if new_level == 0 {new_level = 1}
             animation_state.selected_item_marathon_prompt.0 = new_level;
         }
-        if keyboard.is_key_just_pressed(menuinputs.RIGHT) { // Increase starting level
+        if menuinputs.is_just_pressed(keyboard, MenuAction::Right) { // Increase starting level
             let mut new_level = animation_state.selected_item_marathon_prompt.0 + 1;
             if new_level == 16 {new_level = 15}
             animation_state.selected_item_marathon_prompt.0 = new_level;
         }
     }
-    if keyboard.is_key_just_pressed(menuinputs.SELECT) {
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
         if animation_state.selected_item_marathon_prompt.1 == 2 {
             *screen_state = ScreenState::SingleplayerSelector
         } else {
+            game.gamemode = GameMode::Marathon;
             game.reset_game(None);
             game.set_level(animation_state.selected_item_marathon_prompt.0);
-            game.gamemode = GameMode::Marathon;
             *screen_state = ScreenState::Singleplayer
         }    
     }
 }
 
+// Moves the arrow of a prompt with one or more settings, see option_prompt.rs. `values` and `options` have an entry per setting,
+// `row` is the row of the arrow. Returns Some(true) on confirm and Some(false) on back
+fn handle_option_prompt_inputs(ctx: &ggez::Context, values: &mut [usize], row: &mut usize, options: &[usize], menuinputs: &MenuInputs) -> Option<bool> {
+    let keyboard = &ctx.keyboard;
+    let rows = values.len() + 2;
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
+        *row = (*row + 1) % rows;
+    }
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
+        *row = (*row + rows - 1) % rows;
+    }
+
+    if *row < values.len() {
+        if menuinputs.is_just_pressed(keyboard, MenuAction::Left) && values[*row] > 0 {
+            values[*row] -= 1;
+        }
+        if menuinputs.is_just_pressed(keyboard, MenuAction::Right) && values[*row] + 1 < options[*row] {
+            values[*row] += 1;
+        }
+    }
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
+        return Some(*row != rows - 1);
+    }
+    None
+}
+
+pub fn handle_sprint_prompt_inputs(ctx: &ggez::Context, screen_state: &mut ScreenState, animation_state: &mut AnimationState, game: &mut Game, menuinputs: &MenuInputs) {
+    let prompt = &mut animation_state.selected_item_sprint_prompt;
+    match handle_option_prompt_inputs(ctx, std::slice::from_mut(&mut prompt.0), &mut prompt.1, &[SPRINT_LINE_GOALS.len()], menuinputs) {
+        Some(true) => {
+            game.gamemode = GameMode::Sprint;
+            game.line_goal = SPRINT_LINE_GOALS[prompt.0];
+            game.reset_game(None);
+            *screen_state = ScreenState::Singleplayer
+        }
+        Some(false) => *screen_state = ScreenState::SingleplayerSelector,
+        None => (),
+    }
+}
+
+pub fn handle_ultra_prompt_inputs(ctx: &ggez::Context, screen_state: &mut ScreenState, animation_state: &mut AnimationState, game: &mut Game, menuinputs: &MenuInputs) {
+    let prompt = &mut animation_state.selected_item_ultra_prompt;
+    match handle_option_prompt_inputs(ctx, std::slice::from_mut(&mut prompt.0), &mut prompt.1, &[ULTRA_TIME_LIMITS.len()], menuinputs) {
+        Some(true) => {
+            game.gamemode = GameMode::Ultra;
+            game.time_limit = Duration::from_secs(ULTRA_TIME_LIMITS[prompt.0]);
+            game.reset_game(None);
+            *screen_state = ScreenState::Singleplayer
+        }
+        Some(false) => *screen_state = ScreenState::SingleplayerSelector,
+        None => (),
+    }
+}
+
+pub fn handle_dig_prompt_inputs(ctx: &ggez::Context, screen_state: &mut ScreenState, animation_state: &mut AnimationState, game: &mut Game, menuinputs: &MenuInputs) {
+    let prompt = &mut animation_state.selected_item_dig_prompt;
+    match handle_option_prompt_inputs(ctx, &mut prompt.0, &mut prompt.1, &[DIG_LINE_GOALS.len(), 2], menuinputs) {
+        Some(true) => {
+            game.gamemode = GameMode::Dig;
+            game.dig_goal = DIG_LINE_GOALS[prompt.0[0]];
+            game.dig_messy = prompt.0[1] == 1;
+            game.reset_game(None);
+            *screen_state = ScreenState::Singleplayer
+        }
+        Some(false) => *screen_state = ScreenState::SingleplayerSelector,
+        None => (),
+    }
+}
+
 pub fn handle_reset_screen_inputs(ctx: &ggez::Context, screen_state: &mut ScreenState, animation_state: &mut AnimationState, game: &mut Game, menuinputs: &MenuInputs) {
     let keyboard = &ctx.keyboard;
-    if keyboard.is_key_just_pressed(menuinputs.DOWN)
-    || keyboard.is_key_just_pressed(menuinputs.UP) {
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down)
+    || menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
         animation_state.selected_item_reset_selector = (animation_state.selected_item_reset_selector + 1) % 2;
-    } else if keyboard.is_key_just_pressed(menuinputs.SELECT) {
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
         let selected = animation_state.selected_item_reset_selector;
         *screen_state = match selected {
             0 => {
@@ -169,13 +360,16 @@ pub fn handle_versus_prepost_inputs(
     screen_state: &mut ScreenState,
     animation_state: &mut AnimationState,
     game_one: &mut Game,
-    game_two: &mut Game
+    game_two: &mut Game,
+    pad_inputs: &[InputFrame; 2],
 ) {
     let keyboard = &ctx.keyboard;
-    if keyboard.is_key_just_pressed(*game_one.controls.get(&GameAction::HardDrop).unwrap()) {
+    if keyboard.is_key_just_pressed(*game_one.controls.get(&GameAction::HardDrop).unwrap())
+    || pad_inputs[0].is_pressed(GameAction::HardDrop) {
         animation_state.players_ready.0 = !animation_state.players_ready.0;
     }
-    if keyboard.is_key_just_pressed(*game_two.controls.get(&GameAction::HardDrop).unwrap()) {
+    if keyboard.is_key_just_pressed(*game_two.controls.get(&GameAction::HardDrop).unwrap())
+    || pad_inputs[1].is_pressed(GameAction::HardDrop) {
         animation_state.players_ready.1 = !animation_state.players_ready.1;
     }
 
@@ -197,32 +391,33 @@ pub fn handle_bot_selector_inputs(ctx: &ggez::Context, state: &mut AppState) {
     let mut rng = rand::rng(); 
     let id = Some(rng.random());
 
+    // One item per difficulty and Back
+    let item_count = BOT_DIFFICULTIES.len() as i32 + 1;
+
     let keyboard = &ctx.keyboard;
-    if keyboard.is_key_just_pressed(menuinputs.DOWN) {
-        animation_state.selected_item_bot_selector = (animation_state.selected_item_bot_selector + 1) % 4;
-    } else if keyboard.is_key_just_pressed(menuinputs.UP) {
-        animation_state.selected_item_bot_selector = (animation_state.selected_item_bot_selector + 3) % 4;
-    } else if keyboard.is_key_just_pressed(menuinputs.SELECT) {
-        *screen_state = match animation_state.selected_item_bot_selector {
-            0 => {
-                bot.difficulty = 0;
-                bot.game.reset_game(id);
-                state.game_one.reset_game(id);
-                ScreenState::VsBots
-            },
-            1 => {
-                bot.difficulty = 1;
-                bot.game.reset_game(id);
-                state.game_one.reset_game(id);
-                ScreenState::VsBots
-            },
-            2 => {
-                bot.difficulty = 2;
-                bot.game.reset_game(id);
-                state.game_one.reset_game(id);
-                ScreenState::VsBots
-            },
-            _ => ScreenState::GameModeSelector
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
+        animation_state.selected_item_bot_selector = (animation_state.selected_item_bot_selector + 1) % item_count;
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
+        animation_state.selected_item_bot_selector = (animation_state.selected_item_bot_selector + item_count - 1) % item_count;
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
+        let selected = animation_state.selected_item_bot_selector as usize;
+        *screen_state = if selected < BOT_DIFFICULTIES.len() {
+            bot.set_difficulty(selected);
+            bot.inputs.clear();
+            bot.game.randomizer_type = state.game_one.randomizer_type;
+            bot.game.reset_game(id);
+            state.game_one.reset_game(id);
+            // Every match gets a fresh external bot, the old one is closed when dropped
+            state.tbp_opponent = None;
+            if let Some(command) = &state.tbp_command {
+                match TbpClient::spawn(command) {
+                    Ok(client) => state.tbp_opponent = Some(client),
+                    Err(e) => eprintln!("Couldn't start the TBP bot: {}", e),
+                }
+            }
+            ScreenState::VsBots
+        } else {
+            ScreenState::GameModeSelector
         }
     }
 }
@@ -230,20 +425,22 @@ pub fn handle_bot_selector_inputs(ctx: &ggez::Context, state: &mut AppState) {
 pub fn handle_highscore_inputs(ctx: &ggez::Context, screen_state: &mut ScreenState, animation_state: &mut AnimationState, menuinputs : &MenuInputs){
 
     let keyboard = &ctx.keyboard;
-    if keyboard.is_key_just_pressed(menuinputs.DOWN) || keyboard.is_key_just_pressed(menuinputs.UP){
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) || menuinputs.is_just_pressed(keyboard, MenuAction::Up){
         animation_state.selected_item_high_score.1 = (animation_state.selected_item_high_score.1 + 1) % 2;
-    }else if keyboard.is_key_just_pressed(menuinputs.SELECT){
+    }else if menuinputs.is_just_pressed(keyboard, MenuAction::Select){
         if animation_state.selected_item_high_score.1  == 1{
             *screen_state = ScreenState::MainMenu;
         }
     }
 
     if animation_state.selected_item_high_score.1 == 0 {
-        if keyboard.is_key_just_pressed(menuinputs.RIGHT) {
-            animation_state.selected_item_high_score.0 = (animation_state.selected_item_high_score.0 + 1) % 3;
+        if menuinputs.is_just_pressed(keyboard, MenuAction::Right) {
+            let tables = HIGHSCORE_TABLES.len() as i32;
+            animation_state.selected_item_high_score.0 = (animation_state.selected_item_high_score.0 + 1) % tables;
             animation_state.highscore_list = get_highscore_list(animation_state)
-        }else if keyboard.is_key_just_pressed(menuinputs.LEFT) {
-            animation_state.selected_item_high_score.0 = (animation_state.selected_item_high_score.0 + 2) % 3;
+        }else if menuinputs.is_just_pressed(keyboard, MenuAction::Left) {
+            let tables = HIGHSCORE_TABLES.len() as i32;
+            animation_state.selected_item_high_score.0 = (animation_state.selected_item_high_score.0 + tables - 1) % tables;
             animation_state.highscore_list = get_highscore_list(animation_state);
     
         }
@@ -251,14 +448,87 @@ pub fn handle_highscore_inputs(ctx: &ggez::Context, screen_state: &mut ScreenSta
 }
 
 pub fn get_highscore_list(animation_state: &mut AnimationState) -> Vec<(String, usize)>{
-    match animation_state.selected_item_high_score.0 {
-        1 => get_scores_from_file("res/highscores/highscore_marathon.csv"),
-        2 => get_scores_from_file("res/highscores/highscore_fourty_lines.csv"),
-        0 => get_scores_from_file("res/highscores/highscore_survival.csv"),
-        _ => Vec::new(),
+    match HIGHSCORE_TABLES.get(animation_state.selected_item_high_score.0 as usize) {
+        Some((_, path, _)) => get_scores_from_file(path),
+        None => Vec::new(),
+    }
+}
+
+// Loads the label of every saved replay. Replays that can't be read are left out
+pub fn get_replay_list() -> Vec<(PathBuf, String)> {
+    list_replays().into_iter()
+        .filter_map(|path| Replay::load(&path).ok().map(|r| (path, r.get_label())))
+        .collect()
+}
+
+pub fn handle_replay_browser_inputs(ctx: &ggez::Context, state: &mut AppState) {
+    let keyboard = &ctx.keyboard;
+    let animation_state = &mut state.animation_state;
+    let menuinputs = &state.menuinputs;
+    let item_count = animation_state.replay_list.len() + 1; // Replays and BACK
+
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
+        animation_state.selected_item_replay_browser = (animation_state.selected_item_replay_browser + 1) % item_count;
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
+        animation_state.selected_item_replay_browser = (animation_state.selected_item_replay_browser + item_count - 1) % item_count;
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Back) {
+        state.screen_state = ScreenState::MainMenu;
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
+        match animation_state.replay_list.get(animation_state.selected_item_replay_browser) {
+            Some((path, _)) => {
+                if let Ok(replay) = Replay::load(path) {
+                    state.replay_player = Some(ReplayPlayer::new(replay));
+                    state.screen_state = ScreenState::ReplayViewer;
+                }
+            }
+            None => state.screen_state = ScreenState::MainMenu,
+        }
     }
 }
 
+pub fn handle_replay_viewer_inputs(ctx: &ggez::Context, state: &mut AppState) {
+    let keyboard = &ctx.keyboard;
+    let menuinputs = &state.menuinputs;
+    let Some(player) = &mut state.replay_player else {
+        state.screen_state = ScreenState::ReplayBrowser;
+        return;
+    };
+
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Back) {
+        state.replay_player = None;
+        state.screen_state = ScreenState::ReplayBrowser;
+        return;
+    }
+
+    // Pause, restarts the replay if it has ended
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
+        if player.is_finished() {
+            player.seek(0.);
+            player.paused = false;
+        } else {
+            player.paused = !player.paused;
+        }
+    }
+
+    // Seek 5 seconds
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Left) {
+        player.seek(player.position - REPLAY_SEEK_TICKS);
+    }
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Right) {
+        player.seek(player.position + REPLAY_SEEK_TICKS);
+    }
+
+    // Playback speed
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
+        player.speed_index = (player.speed_index + 1).min(PLAYBACK_SPEEDS.len() - 1);
+    }
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
+        player.speed_index = player.speed_index.saturating_sub(1);
+    }
+
+    player.update(ctx.time.delta());
+}
+
 pub fn handle_name_inputs(
     ctx: &ggez::Context,
     _screen_state: &mut ScreenState,
@@ -269,7 +539,7 @@ pub fn handle_name_inputs(
     let keyboard = &ctx.keyboard;
 
     // Move cursor vertically
-    if keyboard.is_key_just_pressed(menuinputs.DOWN) {
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
         if animation_state.selected_item_high_score.1 == 0 {
             // Move down in keyboard or go to CONTINUE
             if animation_state.selected_key.0 + 1 < 3 {
@@ -281,7 +551,7 @@ pub fn handle_name_inputs(
                 animation_state.selected_item_high_score.1 = 1; // CONTINUE
             }
         }
-    } else if keyboard.is_key_just_pressed(menuinputs.UP) {
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
         if animation_state.selected_item_high_score.1 == 1 {
             animation_state.selected_item_high_score.1 = 0; 
         } else if animation_state.selected_key.0 > 0 {
@@ -295,15 +565,15 @@ pub fn handle_name_inputs(
     // Move cursor horizontally
     if animation_state.selected_item_high_score.1 == 0 {
         let row_len = get_keyboard_row(animation_state.selected_key.0).len();
-        if keyboard.is_key_just_pressed(menuinputs.RIGHT) {
+        if menuinputs.is_just_pressed(keyboard, MenuAction::Right) {
             animation_state.selected_key.1 = (animation_state.selected_key.1 + 1) % row_len;
-        } else if keyboard.is_key_just_pressed(menuinputs.LEFT) {
+        } else if menuinputs.is_just_pressed(keyboard, MenuAction::Left) {
             animation_state.selected_key.1 = (animation_state.selected_key.1 + row_len - 1) % row_len;
         }
     }
 
     // Select key or activate continue
-    if keyboard.is_key_just_pressed(menuinputs.SELECT) {
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
         if animation_state.selected_item_high_score.1 == 1 {
             if animation_state.name_input != ""{
                 animation_state.name_ready = true;
@@ -343,31 +613,32 @@ pub fn handle_settings_input(
     let menuinputs = &state.menuinputs;
 
     // Move pointer
-    if keyboard.is_key_just_pressed(menuinputs.DOWN) {
-        animation_state.selected_item_settings.1 = (animation_state.selected_item_settings.1 + 1) % 4;
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
+        animation_state.selected_item_settings.1 = (animation_state.selected_item_settings.1 + 1) % 6;
     }
-    if keyboard.is_key_just_pressed(menuinputs.UP) {
-        animation_state.selected_item_settings.1 = (animation_state.selected_item_settings.1 + 3) % 4;
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
+        animation_state.selected_item_settings.1 = (animation_state.selected_item_settings.1 + 5) % 6;
     }
-    if animation_state.selected_item_settings.1 == 3 {animation_state.edit_setting_value = false}
+    if animation_state.selected_item_settings.1 >= 3 {animation_state.edit_setting_value = false}
 
     if !animation_state.edit_setting_value 
-    && (keyboard.is_key_just_pressed(menuinputs.LEFT) 
-    || keyboard.is_key_just_pressed(menuinputs.RIGHT)) {
+    && animation_state.selected_item_settings.1 != 4
+    && (menuinputs.is_just_pressed(keyboard, MenuAction::Left) 
+    || menuinputs.is_just_pressed(keyboard, MenuAction::Right)) {
         animation_state.selected_item_settings.0 = (animation_state.selected_item_settings.0 + 1) % 2;
     }
 
     // Adjust selected setting
-    if keyboard.is_key_just_released(menuinputs.LEFT)
-    || keyboard.is_key_just_released(menuinputs.RIGHT) {
+    if menuinputs.is_just_released(keyboard, MenuAction::Left)
+    || menuinputs.is_just_released(keyboard, MenuAction::Right) {
         animation_state.last_setting_tick = None;
     }
 
-    if keyboard.is_key_just_pressed(menuinputs.SELECT)
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Select)
     && animation_state.selected_item_settings.1 < 3 {
         animation_state.edit_setting_value = true
     }
-    if keyboard.is_key_just_released(menuinputs.SELECT)
+    if menuinputs.is_just_released(keyboard, MenuAction::Select)
     && animation_state.selected_item_settings.1 < 3 {
         animation_state.edit_setting_value = false
     }
@@ -375,7 +646,7 @@ pub fn handle_settings_input(
     let game_one = &mut state.game_one;
     let game_two = &mut state.game_two;
 
-    if keyboard.is_key_pressed(menuinputs.LEFT) && animation_state.edit_setting_value {
+    if menuinputs.is_pressed(keyboard, MenuAction::Left) && animation_state.edit_setting_value {
         if animation_state.selected_item_settings.0 == 0 {
             if let Some(t) = animation_state.last_setting_tick {
                 if t.elapsed().as_millis() >= SETTINGS_TICK_SPEED {
@@ -407,7 +678,7 @@ pub fn handle_settings_input(
         }
     }
 
-    if keyboard.is_key_pressed(menuinputs.RIGHT) && animation_state.edit_setting_value {
+    if menuinputs.is_pressed(keyboard, MenuAction::Right) && animation_state.edit_setting_value {
         if animation_state.selected_item_settings.0 == 0 {
             if let Some(t) = animation_state.last_setting_tick {
                 if t.elapsed().as_millis() >= SETTINGS_TICK_SPEED {
@@ -439,13 +710,141 @@ pub fn handle_settings_input(
         }
     }
 
-    // Confirm
-    if keyboard.is_key_just_pressed(menuinputs.SELECT)
+    // Keybinds of the selected player
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Select)
     && animation_state.selected_item_settings.1 == 3 {
+        let player = animation_state.selected_item_settings.0;
+        animation_state.keybind_slot = match (state.drifarkaden, player) {
+            (true, p) => KeybindSlot::Drifarkaden(p),
+            (false, 0) => KeybindSlot::Solo,
+            (false, _) => KeybindSlot::Versus(1),
+        };
+        animation_state.selected_item_keybinds = 1;
+        animation_state.waiting_for_key = false;
+        animation_state.keybind_message = String::new();
+        *screen_state = ScreenState::Keybinds;
+    }
+
+    // The randomizer is shared by both players, left and right cycle through them
+    if animation_state.selected_item_settings.1 == 4 {
+        let count = ALL_RANDOMIZERS.len();
+        let mut index = ALL_RANDOMIZERS
+            .iter()
+            .position(|r| *r == game_one.randomizer_type)
+            .unwrap_or(0);
+        if menuinputs.is_just_pressed(keyboard, MenuAction::Left) {
+            index = (index + count - 1) % count;
+        }
+        if menuinputs.is_just_pressed(keyboard, MenuAction::Right) {
+            index = (index + 1) % count;
+        }
+        game_one.randomizer_type = ALL_RANDOMIZERS[index];
+        game_two.randomizer_type = ALL_RANDOMIZERS[index];
+    }
+
+    // Confirm and save the handling of both players and the randomizer
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Select)
+    && animation_state.selected_item_settings.1 == 5 {
+        state.config.handling = [HandlingConfig::from_game(game_one), HandlingConfig::from_game(game_two)];
+        state.config.randomizer = game_one.randomizer_type;
+        let _ = state.config.save();
         *screen_state = ScreenState::MainMenu;
     }
 }
 
+pub fn handle_keybinds_input(ctx: &ggez::Context, state: &mut AppState) {
+    let keyboard = &ctx.keyboard;
+    let animation_state = &mut state.animation_state;
+    let menuinputs = &state.menuinputs;
+    let slot = animation_state.keybind_slot;
+    let selected = animation_state.selected_item_keybinds;
+
+    // Bind the first key, or button on the player's gamepad, pressed to the selected action
+    if animation_state.waiting_for_key {
+        // Any key can be bound, so waiting too long is the only way to cancel
+        let waited = animation_state.waiting_since.elapsed();
+        if waited >= Duration::from_secs(KEYBIND_TIMEOUT) {
+            animation_state.waiting_for_key = false;
+            animation_state.keybind_message = String::new();
+            return;
+        }
+        animation_state.keybind_message = format!("CANCELS IN {}", KEYBIND_TIMEOUT - waited.as_secs());
+
+        let action = ALL_GAME_ACTIONS[selected - 1];
+        let key = keyboard.pressed_keys().iter().find(|k| keyboard.is_key_just_pressed(**k)).copied();
+        let button = state.gamepads.get_just_pressed_buttons(slot.get_player()).first().copied();
+
+        let result = match (key, button) {
+            (Some(key), _) => state.config.keybinds.rebind(slot, action, key),
+            (None, Some(button)) => state.config.keybinds.rebind_button(slot.get_player(), action, button),
+            (None, None) => return,
+        };
+        animation_state.keybind_message = match result {
+            RebindResult::Bound => String::new(),
+            RebindResult::Swapped(other) => format!("SWAPPED WITH {}", get_action_name(other)),
+            RebindResult::Conflict(other_slot, other, other_key) => format!(
+                "{:?} IS {} FOR {}", other_key, get_action_name(other), other_slot.get_name()
+            ).to_uppercase(),
+        };
+        animation_state.waiting_for_key = false;
+        apply_keybinds(state);
+        return;
+    }
+
+    let item_count = ALL_GAME_ACTIONS.len() + 3; // Layout, actions, restore defaults and back
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Down) {
+        animation_state.selected_item_keybinds = (selected + 1) % item_count;
+    } else if menuinputs.is_just_pressed(keyboard, MenuAction::Up) {
+        animation_state.selected_item_keybinds = (selected + item_count - 1) % item_count;
+    }
+
+    // Player one on keyboard has separate solo and versus keybinds
+    if selected == 0
+    && (menuinputs.is_just_pressed(keyboard, MenuAction::Left) || menuinputs.is_just_pressed(keyboard, MenuAction::Right)) {
+        animation_state.keybind_slot = match slot {
+            KeybindSlot::Solo => KeybindSlot::Versus(0),
+            KeybindSlot::Versus(0) => KeybindSlot::Solo,
+            _ => slot,
+        };
+        animation_state.keybind_message = String::new();
+    }
+
+    if menuinputs.is_just_pressed(keyboard, MenuAction::Select) {
+        animation_state.keybind_message = String::new();
+        if (1..=ALL_GAME_ACTIONS.len()).contains(&selected) {
+            animation_state.waiting_for_key = true;
+            animation_state.waiting_since = Instant::now();
+        } else if selected == ALL_GAME_ACTIONS.len() + 1 {
+            state.config.keybinds.restore_defaults(slot);
+            animation_state.keybind_message = "DEFAULTS RESTORED".to_string();
+            apply_keybinds(state);
+        } else if selected == ALL_GAME_ACTIONS.len() + 2 {
+            state.screen_state = ScreenState::Settings;
+        }
+    }
+}
+
+// Saves the keybinds and gives them to the games
+fn apply_keybinds(state: &mut AppState) {
+    let _ = state.config.save();
+    let [controls_one, controls_two] = state.config.get_controls(state.drifarkaden, false);
+    state.game_one.controls = controls_one;
+    state.game_two.controls = controls_two;
+}
+
+pub fn get_action_name(action: GameAction) -> &'static str {
+    match action {
+        GameAction::MoveLeft => "MOVE LEFT",
+        GameAction::MoveRight => "MOVE RIGHT",
+        GameAction::SoftDrop => "SOFT DROP",
+        GameAction::HardDrop => "HARD DROP",
+        GameAction::RotateCw => "ROTATE CW",
+        GameAction::RotateCcw => "ROTATE CCW",
+        GameAction::Rotate180 => "ROTATE 180",
+        GameAction::Hold => "HOLD",
+    }
+}
+
 // Helper functions
 fn increment_das(game: &mut Game) {
     let das = game.das.as_millis();
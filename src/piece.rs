@@ -1,6 +1,3 @@
-use rand::{seq::SliceRandom, Rng, SeedableRng};
-use std::{collections::VecDeque, vec};
-
 use crate::rotation::{
     RELATIVE_MINOS_I, RELATIVE_MINOS_J, RELATIVE_MINOS_L, RELATIVE_MINOS_O, RELATIVE_MINOS_S,
     RELATIVE_MINOS_T, RELATIVE_MINOS_Z,
@@ -31,12 +28,6 @@ impl PieceType {
         }
     }
 
-    pub fn get_random(id : u64) -> PieceType {
-        let mut rng = rand::rngs::StdRng::seed_from_u64(id);
-        let piece_num = rng.random_range(0..7);
-        PieceType::get_piecetype_from_num(piece_num)
-    }
-
     pub fn get_piecetype_from_num(n: i32) -> PieceType {
         match n {
             0 => PieceType::I,
@@ -49,18 +40,6 @@ impl PieceType {
             _ => PieceType::X,
         }
     }
-
-    pub fn get_random_as_list(id : &u64) -> VecDeque<PieceType> {
-        let mut rng = rand::rngs::StdRng::seed_from_u64(*id);
-        let mut nums: Vec<i32> = vec![0, 1, 2, 3, 4, 5, 6];
-        nums.shuffle(&mut rng);
-
-        let mut out: VecDeque<PieceType> = VecDeque::new();
-        for n in nums {
-            out.push_back(PieceType::get_piecetype_from_num(n));
-        }
-        out
-    }
 }
 
 #[derive(Clone, Hash, PartialEq, Eq)]
@@ -74,15 +53,20 @@ pub struct Piece {
 impl Piece {
     // ALL PIECE TYPE POSITIONS ARE RELATIVE TO THE MIDPOINT
     pub fn get_block_positions(piece_type: PieceType, rotation: usize) -> Vec<(isize, isize)> {
+        Piece::get_minos(piece_type, rotation).to_vec()
+    }
+
+    // Same as get_block_positions without allocating
+    pub fn get_minos(piece_type: PieceType, rotation: usize) -> &'static [(isize, isize)] {
         match piece_type {
-            PieceType::I => RELATIVE_MINOS_I[rotation].to_vec(),
-            PieceType::J => RELATIVE_MINOS_J[rotation].to_vec(),
-            PieceType::L => RELATIVE_MINOS_L[rotation].to_vec(),
-            PieceType::O => RELATIVE_MINOS_O[rotation].to_vec(),
-            PieceType::S => RELATIVE_MINOS_S[rotation].to_vec(),
-            PieceType::T => RELATIVE_MINOS_T[rotation].to_vec(),
-            PieceType::Z => RELATIVE_MINOS_Z[rotation].to_vec(),
-            PieceType::X => Vec::new(),
+            PieceType::I => &RELATIVE_MINOS_I[rotation],
+            PieceType::J => &RELATIVE_MINOS_J[rotation],
+            PieceType::L => &RELATIVE_MINOS_L[rotation],
+            PieceType::O => &RELATIVE_MINOS_O[rotation],
+            PieceType::S => &RELATIVE_MINOS_S[rotation],
+            PieceType::T => &RELATIVE_MINOS_T[rotation],
+            PieceType::Z => &RELATIVE_MINOS_Z[rotation],
+            PieceType::X => &[],
         }
     }
 
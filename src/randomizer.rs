@@ -0,0 +1,209 @@
+use std::collections::VecDeque;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::PieceType;
+
+const ALL_PIECES: [PieceType; 7] = [
+    PieceType::I,
+    PieceType::J,
+    PieceType::L,
+    PieceType::O,
+    PieceType::S,
+    PieceType::T,
+    PieceType::Z,
+];
+
+// Amount of rerolls the TGM randomizer does before accepting a piece from its history
+const HISTORY_ROLLS: usize = 4;
+
+pub trait Randomizer: Send + Sync {
+    // Returns the next piece in the sequence. The RNG state is kept between calls
+    fn next_piece(&mut self) -> PieceType;
+
+    fn box_clone(&self) -> Box<dyn Randomizer>;
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RandomizerType {
+    #[default]
+    SevenBag,
+    FourteenBag,
+    Random,
+    History4,
+    Nes,
+}
+
+// In the order the settings screen cycles through them
+pub const ALL_RANDOMIZERS: [RandomizerType; 5] = [
+    RandomizerType::SevenBag,
+    RandomizerType::FourteenBag,
+    RandomizerType::Random,
+    RandomizerType::History4,
+    RandomizerType::Nes,
+];
+
+impl RandomizerType {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            RandomizerType::SevenBag => "7-BAG",
+            RandomizerType::FourteenBag => "14-BAG",
+            RandomizerType::Random => "RANDOM",
+            RandomizerType::History4 => "TGM",
+            RandomizerType::Nes => "NES",
+        }
+    }
+
+    // Creates a new randomizer of this type. Equal seeds give equal sequences
+    pub fn create(&self, seed: u64) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerType::SevenBag => Box::new(BagRandomizer::new(seed, 1)),
+            RandomizerType::FourteenBag => Box::new(BagRandomizer::new(seed, 2)),
+            RandomizerType::Random => Box::new(RandomRandomizer::new(seed)),
+            RandomizerType::History4 => Box::new(HistoryRandomizer::new(seed)),
+            RandomizerType::Nes => Box::new(NesRandomizer::new(seed)),
+        }
+    }
+}
+
+// Shuffles `copies` sets of all seven pieces and deals them out one by one
+#[derive(Clone)]
+pub struct BagRandomizer {
+    rng: StdRng,
+    bag: VecDeque<PieceType>,
+    copies: usize,
+}
+
+impl BagRandomizer {
+    pub fn new(seed: u64, copies: usize) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            bag: VecDeque::new(),
+            copies,
+        }
+    }
+}
+
+impl Randomizer for BagRandomizer {
+    fn next_piece(&mut self) -> PieceType {
+        if self.bag.is_empty() {
+            let mut pieces: Vec<PieceType> = ALL_PIECES.repeat(self.copies);
+            pieces.shuffle(&mut self.rng);
+            self.bag.extend(pieces);
+        }
+        self.bag.pop_front().unwrap()
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+// Every piece is picked independently
+#[derive(Clone)]
+pub struct RandomRandomizer {
+    rng: StdRng,
+}
+
+impl RandomRandomizer {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Randomizer for RandomRandomizer {
+    fn next_piece(&mut self) -> PieceType {
+        ALL_PIECES[self.rng.random_range(0..7)]
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+// TGM style randomizer. Rerolls pieces found in the last four dealt pieces
+#[derive(Clone)]
+pub struct HistoryRandomizer {
+    rng: StdRng,
+    history: VecDeque<PieceType>,
+    first: bool,
+}
+
+impl HistoryRandomizer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            history: VecDeque::from([PieceType::Z; 4]),
+            first: true,
+        }
+    }
+}
+
+impl Randomizer for HistoryRandomizer {
+    fn next_piece(&mut self) -> PieceType {
+        let piece = if self.first {
+            // The first piece is never S, Z or O
+            self.first = false;
+            [PieceType::I, PieceType::J, PieceType::L, PieceType::T][self.rng.random_range(0..4)]
+        } else {
+            let mut piece = ALL_PIECES[self.rng.random_range(0..7)];
+            for _ in 1..HISTORY_ROLLS {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+                piece = ALL_PIECES[self.rng.random_range(0..7)];
+            }
+            piece
+        };
+
+        self.history.pop_front();
+        self.history.push_back(piece);
+        piece
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
+
+// NES style randomizer. Rerolls once if it lands on the previous piece
+#[derive(Clone)]
+pub struct NesRandomizer {
+    rng: StdRng,
+    previous: Option<PieceType>,
+}
+
+impl NesRandomizer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            previous: None,
+        }
+    }
+}
+
+impl Randomizer for NesRandomizer {
+    fn next_piece(&mut self) -> PieceType {
+        // Roll 0-7 where 7 is a "reroll" slot
+        let roll = self.rng.random_range(0..8);
+        let mut piece = if roll < 7 { Some(ALL_PIECES[roll]) } else { None };
+
+        if piece.is_none() || piece == self.previous {
+            piece = Some(ALL_PIECES[self.rng.random_range(0..7)]);
+        }
+
+        self.previous = piece;
+        piece.unwrap()
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,330 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SimInstant, TICK};
+use crate::config::config_file::HandlingConfig;
+use crate::consts::{GameMode, GAME_1_SOLO_POS, GAME_1_SOLO_SCL, GAME_1_VS_POS, GAME_1_VS_SCL, GAME_2_VS_POS, GAME_2_VS_SCL};
+use crate::game_inputs::{InputEvent, InputFrame};
+use crate::randomizer::RandomizerType;
+use crate::Game;
+
+// Bump when the format changes. Replays with another version are not loaded
+pub const REPLAY_VERSION: u32 = 1;
+pub const REPLAY_DIR: &str = "res/replays";
+pub const MAX_REPLAYS: usize = 50; // Oldest replays are removed when there are more than this
+
+pub const PLAYBACK_SPEEDS: [f64; 5] = [0.25, 0.5, 1., 2., 4.];
+pub const DEFAULT_SPEED_INDEX: usize = 2;
+
+// Input events of one update, stamped with the tick of every player relative to the game start
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub ticks: Vec<u64>,
+    pub events: Vec<Vec<InputEvent>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub gamemode: GameMode,
+    pub seed: u64,
+    pub randomizer: RandomizerType,
+    pub start_level: usize,
+    pub time_limit: u64, // in millis, Ultra only
+    pub line_goal: usize, // Sprint only
+    pub dig_goal: usize, // Dig only
+    pub dig_messy: bool, // Dig only
+    pub players: Vec<HandlingConfig>,
+    pub frames: Vec<ReplayFrame>,
+
+    // Summary, shown in the replay browser
+    pub length: u64, // in ticks
+    pub final_time: u64, // in millis
+    pub score: usize,
+    pub winner: Option<usize>, // Versus only
+}
+
+impl Replay {
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Replay, Box<dyn Error>> {
+        let replay: Replay = serde_json::from_str(json)?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!("Unsupported replay version {}", replay.version).into());
+        }
+        Ok(replay)
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, Box<dyn Error>> {
+        Replay::from_json(&fs::read_to_string(path)?)
+    }
+
+    // Writes the replay to REPLAY_DIR, named after the current time and the gamemode
+    pub fn save(&self) -> Result<PathBuf, Box<dyn Error>> {
+        fs::create_dir_all(REPLAY_DIR)?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let path = Path::new(REPLAY_DIR).join(format!("{}_{:?}.json", timestamp, self.gamemode));
+        fs::write(&path, self.to_json()?)?;
+
+        let old_replays = list_replays();
+        for old in old_replays.iter().skip(MAX_REPLAYS) {
+            let _ = fs::remove_file(old);
+        }
+        Ok(path)
+    }
+
+    pub fn get_label(&self) -> String {
+        let mode = match self.gamemode {
+            GameMode::Marathon => "MARATHON".to_string(),
+            GameMode::Sprint => format!("{}L", self.line_goal),
+            GameMode::Survival => "SURVIVAL".to_string(),
+            GameMode::Versus => "1V1".to_string(),
+            GameMode::PcTraining => "PC".to_string(),
+            GameMode::Ultra => "ULTRA".to_string(),
+            GameMode::Dig if self.dig_messy => format!("MESSY {}L", self.dig_goal),
+            GameMode::Dig => format!("DIG {}L", self.dig_goal),
+            GameMode::Master => "MASTER".to_string(),
+        };
+        let result = match self.gamemode {
+            GameMode::Marathon | GameMode::Ultra => self.score.to_string(),
+            GameMode::Versus => match self.winner {
+                Some(w) => format!("P{} WINS", w + 1),
+                None => "-".to_string(),
+            },
+            _ => crate::ui_components::stat_formatting::get_formatted_time(
+                Duration::from_millis(self.final_time),
+            ),
+        };
+        format!("{} {}", mode, result)
+    }
+}
+
+// Replay files in REPLAY_DIR, newest first
+pub fn list_replays() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(REPLAY_DIR) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths.reverse();
+    paths
+}
+
+// Ticks since the latest reset of the game
+fn get_tick(game: &Game) -> u64 {
+    game.clock.now().as_ticks() - game.reset_time.as_ticks()
+}
+
+fn get_instant(game: &Game, tick: u64) -> SimInstant {
+    game.reset_time + TICK * tick as u32
+}
+
+/*
+    Records the inputs of one or two games from their latest reset.
+    The recorder belongs to the games it was started from, a reset of
+    any of them means a new recording has to be started.
+*/
+pub struct ReplayRecorder {
+    replay: Replay,
+    starts: Vec<(u64, SimInstant)>, // (id, reset_time) of every game
+    finished: bool,
+}
+
+impl ReplayRecorder {
+    pub fn start(games: &[&Game]) -> Self {
+        let players = games.iter().map(|game| HandlingConfig::from_game(game)).collect();
+
+        Self {
+            replay: Replay {
+                version: REPLAY_VERSION,
+                gamemode: games[0].gamemode,
+                seed: games[0].id,
+                randomizer: games[0].randomizer_type,
+                start_level: games[0].level,
+                time_limit: games[0].time_limit.as_millis() as u64,
+                line_goal: games[0].line_goal,
+                dig_goal: games[0].dig_goal,
+                dig_messy: games[0].dig_messy,
+                players,
+                frames: Vec::new(),
+                length: 0,
+                final_time: 0,
+                score: 0,
+                winner: None,
+            },
+            starts: games.iter().map(|game| (game.id, game.reset_time)).collect(),
+            finished: false,
+        }
+    }
+
+    pub fn is_recording(&self, games: &[&Game]) -> bool {
+        self.starts.len() == games.len()
+        && games.iter().zip(&self.starts).all(|(game, start)| (game.id, game.reset_time) == *start)
+    }
+
+    // Stores the inputs the games were stepped with this update. `force` keeps frames without inputs,
+    // used when garbage moved between the games
+    pub fn record(&mut self, games: &[&Game], inputs: &[InputFrame], force: bool) {
+        if self.finished || (!force && inputs.iter().all(|i| i.is_empty())) {
+            return;
+        }
+        self.replay.frames.push(ReplayFrame {
+            ticks: games.iter().map(|game| get_tick(game)).collect(),
+            events: inputs.iter().map(|i| i.events.clone()).collect(),
+        });
+    }
+
+    // Ends the recording and returns the replay. Returns None if it was already finished
+    pub fn finish(&mut self, games: &[&Game]) -> Option<Replay> {
+        if self.finished {
+            return None;
+        }
+        self.finished = true;
+
+        // Frame marking the end so the last ticks are played back as well
+        self.replay.frames.push(ReplayFrame {
+            ticks: games.iter().map(|game| get_tick(game)).collect(),
+            events: vec![Vec::new(); games.len()],
+        });
+
+        self.replay.length = games.iter().map(|game| get_tick(game)).max().unwrap_or(0);
+        self.replay.final_time = games[0].final_time.as_millis() as u64;
+        self.replay.score = games[0].score;
+        if games.len() > 1 {
+            self.replay.winner = games.iter().position(|game| !game.game_over);
+        }
+        Some(self.replay.clone())
+    }
+}
+
+// Starts a new recording when the games have been reset and saves it once a game is over
+pub fn update_recorder(recorder: &mut Option<ReplayRecorder>, games: &[&Game], inputs: &[InputFrame], force: bool) {
+    if !recorder.as_ref().is_some_and(|r| r.is_recording(games)) {
+        *recorder = Some(ReplayRecorder::start(games));
+    }
+    let recorder = recorder.as_mut().unwrap();
+    recorder.record(games, inputs, force);
+
+    if games.iter().any(|game| game.game_over) {
+        if let Some(replay) = recorder.finish(games) {
+            let _ = replay.save();
+        }
+    }
+}
+
+/*
+    Plays a replay back by re-simulating the games on manual clocks.
+    `position` is the playback time in ticks, games are never simulated
+    past it. Seeking backwards rebuilds the games from the start.
+*/
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub games: Vec<Game>,
+    pub position: f64, // in ticks
+    pub paused: bool,
+    pub speed_index: usize,
+    next_frame: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        let mut player = Self {
+            games: Vec::new(),
+            replay,
+            position: 0.,
+            paused: false,
+            speed_index: DEFAULT_SPEED_INDEX,
+            next_frame: 0,
+        };
+        player.rebuild();
+        player
+    }
+
+    fn rebuild(&mut self) {
+        let versus = self.replay.players.len() > 1;
+        self.games = self.replay.players.iter().enumerate().map(|(i, settings)| {
+            let (pos, scl) = match (versus, i) {
+                (false, _) => (GAME_1_SOLO_POS, GAME_1_SOLO_SCL),
+                (true, 0) => (GAME_1_VS_POS, GAME_1_VS_SCL),
+                _ => (GAME_2_VS_POS, GAME_2_VS_SCL),
+            };
+            let mut game = Game::new(pos, scl, self.replay.seed);
+            game.clock = Clock::manual();
+            game.gamemode = self.replay.gamemode;
+            game.randomizer_type = self.replay.randomizer;
+            settings.apply(&mut game);
+            if self.replay.gamemode == GameMode::Ultra {
+                game.time_limit = Duration::from_millis(self.replay.time_limit);
+            }
+            game.line_goal = self.replay.line_goal;
+            game.dig_goal = self.replay.dig_goal;
+            game.dig_messy = self.replay.dig_messy;
+            game.reset_game(Some(self.replay.seed));
+            // Only Marathon starts at a chosen level
+            if self.replay.gamemode == GameMode::Marathon {
+                game.set_level(self.replay.start_level);
+            }
+            game
+        }).collect();
+        self.next_frame = 0;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.replay.length as f64
+    }
+
+    // Moves playback forward by `dt` of real time, scaled by the playback speed
+    pub fn update(&mut self, dt: Duration) {
+        if self.paused || self.is_finished() {
+            return;
+        }
+        let ticks = dt.as_secs_f64() / TICK.as_secs_f64() * PLAYBACK_SPEEDS[self.speed_index];
+        self.seek(self.position + ticks);
+    }
+
+    pub fn seek(&mut self, position: f64) {
+        let position = position.clamp(0., self.replay.length as f64);
+        if position < self.position {
+            self.rebuild();
+        }
+        self.position = position;
+        self.simulate_to(position as u64);
+    }
+
+    fn simulate_to(&mut self, tick: u64) {
+        // Apply every frame that has been reached by all players
+        while let Some(frame) = self.replay.frames.get(self.next_frame) {
+            if frame.ticks.iter().any(|t| *t > tick) {
+                break;
+            }
+            for (i, game) in self.games.iter_mut().enumerate() {
+                game.clock.advance_to(get_instant(game, frame.ticks[i]));
+                game.step(&InputFrame { events: frame.events[i].clone() });
+            }
+            if let [game_one, game_two] = &mut self.games[..] {
+                game_one.send_outbound_garbage(game_two);
+                game_two.send_outbound_garbage(game_one);
+            }
+            self.next_frame += 1;
+        }
+
+        // Run the ticks up to the position, but never past the next frame of a player
+        for (i, game) in self.games.iter_mut().enumerate() {
+            let limit = match self.replay.frames.get(self.next_frame) {
+                Some(frame) => frame.ticks[i].min(tick),
+                None => tick,
+            };
+            game.clock.advance_to(get_instant(game, limit));
+            game.run_pending_ticks();
+        }
+    }
+}
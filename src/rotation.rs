@@ -1,5 +1,3 @@
-use std::time::Instant;
-
 use crate::{Game, Piece, PieceType};
 
 pub const ROTATION_CW:  usize = 1;
@@ -103,18 +101,7 @@ impl Game {
         rotated_piece.midpoint = piece.midpoint;
         self.active_piece = rotated_piece;
 
-        // Fetch the suitable kick table
-        let kick_table = match rotation_type {
-            ROTATION_CW => match piece.piece_type {
-                PieceType::I => KICK_TABLE_CW_I[new_rotation],
-                _ => KICK_TABLE_CW_REGULAR[new_rotation],
-            },
-            ROTATION_CCW => match piece.piece_type {
-                PieceType::I => KICK_TABLE_CCW_I[new_rotation],
-                _ => KICK_TABLE_CCW_REGULAR[new_rotation],
-            },
-            _ => KICK_TABLE_180[new_rotation],
-        };
+        let kick_table = get_kick_table(piece.piece_type, rotation_type, new_rotation);
         
         // Try kick table offsets
         for (dx, dy) in kick_table {
@@ -123,31 +110,13 @@ impl Game {
 
                 // Check for T-Spin and T-Spin Mini
                 if piece.piece_type == PieceType::T {
-                    let (mr, mc) = self.active_piece.midpoint;
-                    // Four corners around midpoint (dr, dc)
-                    let around: [(isize, isize); 4] = [(1, -1), (1, 1), (-1, 1), (-1, -1)];
-                    // Amount of corners in front of the t-piece that is solid (0, 1 or 2)
-                    let mut front_count = 0;
-                    // Amount of corners behind the t-piece that is solid (0, 1 or 2)
-                    let mut back_count = 0;
-
-                    for (i, (dr, dc)) in around.iter().enumerate() {
-                        if self.is_solid_tile(mr + dr, mc + dc) {
-                            if i == new_rotation || i == (new_rotation + 1) % 4 {
-                                front_count += 1;
-                            } else {
-                                back_count += 1;
-                            }
-                        }
-                    }
-
-                    if front_count + back_count >= 3 {
+                    if let Some(mini) = get_t_spin(|r, c| self.is_solid_tile(r, c), self.active_piece.midpoint, new_rotation) {
                         self.t_spin = true;
-                        self.t_spin_mini = front_count == 1;
+                        self.t_spin_mini = mini;
                     }
                 }
 
-                if self.on_ground {self.last_drop = Instant::now()}
+                if self.on_ground {self.last_drop = self.clock.now()}
                 self.add_action();
                 return true;
             }
@@ -159,4 +128,47 @@ impl Game {
         self.active_piece = piece;
         false
     }
-}
\ No newline at end of file
+}
+
+// Positions to check when rotating into `new_rotation`
+pub fn get_kick_table(piece_type: PieceType, rotation_type: usize, new_rotation: usize) -> [(isize, isize); 6] {
+    match rotation_type {
+        ROTATION_CW => match piece_type {
+            PieceType::I => KICK_TABLE_CW_I[new_rotation],
+            _ => KICK_TABLE_CW_REGULAR[new_rotation],
+        },
+        ROTATION_CCW => match piece_type {
+            PieceType::I => KICK_TABLE_CCW_I[new_rotation],
+            _ => KICK_TABLE_CCW_REGULAR[new_rotation],
+        },
+        _ => KICK_TABLE_180[new_rotation],
+    }
+}
+
+// Checks the corners around a T piece that was just rotated.
+// Returns None if it isn't a T-Spin, otherwise whether it is a mini
+pub fn get_t_spin(is_solid: impl Fn(isize, isize) -> bool, midpoint: (isize, isize), rotation: usize) -> Option<bool> {
+    let (mr, mc) = midpoint;
+    // Four corners around midpoint (dr, dc)
+    let around: [(isize, isize); 4] = [(1, -1), (1, 1), (-1, 1), (-1, -1)];
+    // Amount of corners in front of the t-piece that is solid (0, 1 or 2)
+    let mut front_count = 0;
+    // Amount of corners behind the t-piece that is solid (0, 1 or 2)
+    let mut back_count = 0;
+
+    for (i, (dr, dc)) in around.iter().enumerate() {
+        if is_solid(mr + dr, mc + dc) {
+            if i == rotation || i == (rotation + 1) % 4 {
+                front_count += 1;
+            } else {
+                back_count += 1;
+            }
+        }
+    }
+
+    if front_count + back_count >= 3 {
+        Some(front_count == 1)
+    } else {
+        None
+    }
+}
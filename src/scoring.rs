@@ -13,6 +13,34 @@ pub enum ScoreType {
     TSpinTriple,
 }
 
+impl ScoreType {
+    pub fn from_clear(lines_cleared: usize, t_spin: bool, t_spin_mini: bool) -> Option<ScoreType> {
+        match lines_cleared {
+            0 => {
+                if t_spin_mini {Some(ScoreType::TSpinMini)}
+                else if t_spin {Some(ScoreType::TSpin)}
+                else {None}
+            },
+            1 => {
+                if t_spin_mini {Some(ScoreType::TSpinMiniSingle)}
+                else if t_spin {Some(ScoreType::TSpinSingle)}
+                else {Some(ScoreType::Single)}
+            },
+            2 => {
+                if t_spin {Some(ScoreType::TspinDouble)}
+                else {Some(ScoreType::Double)}
+            },
+            3 => {
+                if t_spin {Some(ScoreType::TSpinTriple)}
+                else {Some(ScoreType::Triple)}
+            },
+            _ => {
+                Some(ScoreType::Tetris)
+            }
+        }
+    }
+}
+
 impl Game {
     pub fn add_score(&mut self, score_type: &Option<ScoreType>) {
         let mut points = 0.;
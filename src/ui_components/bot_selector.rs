@@ -5,6 +5,7 @@ use ggez::{
 use std::collections::HashMap;
 
 use crate::animation_state::AnimationState;
+use crate::bots::difficulty::BOT_DIFFICULTIES;
 use crate::consts::{WINDOW_HEIGHT, WINDOW_WIDTH};
 
 pub fn render_bot_selector(
@@ -45,73 +46,28 @@ pub fn render_bot_selector(
             .scale(glam::Vec2::new(scl, scl)),
     );
 
-    let easy = Text::new(TextFragment {
-        text: "EASY".to_string(),
-        font: Some("Tetris font".to_string()),
-        color: Some(Color::WHITE),
-        scale: Some(PxScale::from(60.0)),
-    });
-
-    canvas.draw(
-        &easy,
-        graphics::DrawParam::new()
-            .dest(glam::Vec2::new(
-                center.0 - image_half_size.0 + 200.,
-                center.1 - 225.,
-            ))
-            .scale(glam::Vec2::new(scl, scl)),
-    );
-
-    let medium = Text::new(TextFragment {
-        text: "MEDIUM".to_string(),
-        font: Some("Tetris font".to_string()),
-        color: Some(Color::WHITE),
-        scale: Some(PxScale::from(60.0)),
-    });
-
-    canvas.draw(
-        &medium,
-        graphics::DrawParam::new()
-            .dest(glam::Vec2::new(
-                center.0 - image_half_size.0 + 200.,
-                center.1 - 75.,
-            ))
-            .scale(glam::Vec2::new(scl, scl)),
-    );
-
-    let hard = Text::new(TextFragment {
-        text: "HARD".to_string(),
-        font: Some("Tetris font".to_string()),
-        color: Some(Color::WHITE),
-        scale: Some(PxScale::from(60.0)),
-    });
-
-    canvas.draw(
-        &hard,
-        graphics::DrawParam::new()
-            .dest(glam::Vec2::new(
-                center.0 - image_half_size.0 + 200.,
-                center.1 + 75.,
-            ))
-            .scale(glam::Vec2::new(scl, scl)),
-    );
-
-    let back = Text::new(TextFragment {
-        text: "Back".to_string(),
-        font: Some("Tetris font".to_string()),
-        color: Some(Color::WHITE),
-        scale: Some(PxScale::from(60.0)),
-    });
-
-    canvas.draw(
-        &back,
-        graphics::DrawParam::new()
-            .dest(glam::Vec2::new(
-                center.0 - image_half_size.0 + 200.,
-                center.1 + 225.,
-            ))
-            .scale(glam::Vec2::new(scl, scl)),
-    );
+    // One item per difficulty and Back, spaced evenly in the box
+    let item_y = |i: i32| center.1 - 250. + 100. * i as f32;
+
+    let names = BOT_DIFFICULTIES.iter().map(|d| d.name).chain(["Back"]);
+    for (i, name) in names.enumerate() {
+        let item = Text::new(TextFragment {
+            text: name.to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(60.0)),
+        });
+
+        canvas.draw(
+            &item,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(
+                    center.0 - image_half_size.0 + 200.,
+                    item_y(i as i32),
+                ))
+                .scale(glam::Vec2::new(scl, scl)),
+        );
+    }
 
     let arrow = Text::new(TextFragment {
         text: ">".to_string(),
@@ -120,12 +76,7 @@ pub fn render_bot_selector(
         scale: Some(PxScale::from(60.0)),
     });
 
-    let arrow_y = match animation_state.selected_item_bot_selector {
-        0 => center.1 - 225.,
-        1 => center.1 - 75.,
-        2 => center.1 + 75.,
-        _ => center.1 + 225.,
-    };
+    let arrow_y = item_y(animation_state.selected_item_bot_selector);
 
     canvas.draw(
         &arrow,
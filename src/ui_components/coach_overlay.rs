@@ -0,0 +1,50 @@
+use ggez::{glam, graphics::{self, Canvas, Color, PxScale, Text, TextAlign, TextFragment, TextLayout}};
+
+use crate::bots::coach::Coach;
+use crate::Game;
+
+// Grade of the latest placement, between the hold box and the stats
+pub fn render_coach_overlay(coach: &Coach, game: &Game, canvas: &mut Canvas) {
+    if !coach.enabled {
+        return;
+    }
+    let pos = game.canvas_pos;
+    let scl = game.canvas_scl;
+
+    let mut label = Text::new(TextFragment{
+        text: "Coach".to_string(),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE),
+        scale: Some(PxScale::from(16.))
+    });
+    label.set_layout(TextLayout{
+        h_align: TextAlign::End,
+        v_align: TextAlign::Middle
+    });
+    canvas.draw(&label,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(pos.0 + 156. * scl, pos.1 + 240. * scl))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+
+    let grade = match coach.last_delta {
+        Some(delta) if Coach::is_best(delta) => "Best".to_string(),
+        Some(delta) => format!("{:.2}", delta),
+        None => "-".to_string(),
+    };
+    let mut grade = Text::new(TextFragment{
+        text: grade,
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE),
+        scale: Some(PxScale::from(24.))
+    });
+    grade.set_layout(TextLayout{
+        h_align: TextAlign::End,
+        v_align: TextAlign::Middle
+    });
+    canvas.draw(&grade,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(pos.0 + 156. * scl, pos.1 + 272. * scl))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+}
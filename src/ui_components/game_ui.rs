@@ -2,8 +2,7 @@ use std::{collections::HashMap, time::Duration};
 
 use ggez::{glam, graphics::{self, Canvas, Color, Image, PxScale, Text, TextAlign, TextFragment, TextLayout}};
 
-use crate::{consts::{GameMode, GARBAGE_CAP}, Game, Piece, PieceType};
-use crate::consts::{BOARD_AMOUNT_COLUMNS, BOARD_AMOUNT_ROWS};
+use crate::{consts::{GameMode, GARBAGE_CAP, NEXT_QUEUE_SIZE, PC_TRAINING_HEIGHT}, Game, Piece, PieceType};
 
 
 impl Game {
@@ -93,10 +92,20 @@ impl Game {
         let (mut x, y) = (pos.0 + 168. * scl, pos.1 + 608. * scl);
         if self.gamemode == GameMode::Versus {x += 36. * scl};
 
-        for r in 0..BOARD_AMOUNT_ROWS {
-            for c in 0..BOARD_AMOUNT_COLUMNS {
-                if let Some(piece_type) = self.board[r][c] {
-                    let image = assets.get(&piece_type).unwrap();
+        // During the line clear delay the board from before the clear is shown, with the cleared rows fading out
+        let (board, cleared_rows, fade) = match &self.line_clear {
+            Some((start, rows, board)) if self.clock.elapsed(*start) < self.line_clear_delay => {
+                let progress = self.clock.elapsed(*start).as_secs_f32() / self.line_clear_delay.as_secs_f32();
+                (*board, rows.clone(), 1. - progress)
+            }
+            _ => (self.board, Vec::new(), 1.),
+        };
+
+        for (r, row) in board.iter().enumerate() {
+            let alpha = if cleared_rows.contains(&r) {fade} else {1.};
+            for (c, cell) in row.iter().enumerate() {
+                if let Some(piece_type) = cell {
+                    let image = assets.get(piece_type).unwrap();
                     canvas.draw(
                         image,
                         graphics::DrawParam::new()
@@ -105,43 +114,64 @@ impl Game {
                                 y - r as f32 * 32. * scl
                             ))
                             .scale(glam::Vec2::new(scl, scl))
+                            .color(graphics::Color::new(1., 1., 1., alpha))
                     );
                 }
             }
         }
 
-        //Active piece
-        let image = assets.get(&self.active_piece.piece_type).unwrap();
-        let (mr, mc) = self.active_piece.midpoint;
-        self.active_piece.block_positions.iter().for_each(|(dr, dc)| {
-            canvas.draw(
-                image,
-                graphics::DrawParam::new()
+        // No piece to draw during the entry delay, the placed one is part of the board
+        if self.spawn_at.is_none() {
+            //Active piece
+            let image = assets.get(&self.active_piece.piece_type).unwrap();
+            let (mr, mc) = self.active_piece.midpoint;
+            self.active_piece.block_positions.iter().for_each(|(dr, dc)| {
+                canvas.draw(
+                    image,
+                    graphics::DrawParam::new()
+                        .dest(glam::Vec2::new(
+                            x + (mc + dc) as f32 * 32. * scl,
+                            y - (mr + dr) as f32 * 32. * scl
+                        ))
+                        .scale(glam::Vec2::new(scl, scl))
+                );
+            });
+
+            //Hint piece from the coach, brighter than the ghost so they can be told apart
+            if let Some(hint_piece) = &self.hint_piece {
+                let image = assets.get(&hint_piece.piece_type).unwrap();
+                let (mr, mc) = hint_piece.midpoint;
+                hint_piece.block_positions.iter().for_each(|(dr, dc)| {
+                    let param = graphics::DrawParam::new()
+                        .dest(glam::Vec2::new(
+                            x + (mc + dc) as f32 * 32. * scl,
+                            y - (mr + dr) as f32 * 32. * scl
+                        ))
+                        .scale(glam::Vec2::new(scl, scl))
+                        .color(graphics::Color::from_rgba(255, 255, 255, 90));
+
+                    canvas.draw(image, param);
+                });
+            }
+
+            //Ghost piece
+            let ghost_piece = self.get_ghost_piece();
+            let image = assets.get(&self.active_piece.piece_type).unwrap();
+
+            let (mr, mc) = ghost_piece.midpoint;
+            ghost_piece.block_positions.iter().for_each(|(dr, dc)| {
+                // SET POSITION AND OPACITY
+                let param = graphics::DrawParam::new()
                     .dest(glam::Vec2::new(
                         x + (mc + dc) as f32 * 32. * scl,
                         y - (mr + dr) as f32 * 32. * scl
                     ))
                     .scale(glam::Vec2::new(scl, scl))
-            );
-        });
-
-        //Ghost piece
-        let ghost_piece = self.get_ghost_piece();
-        let image = assets.get(&self.active_piece.piece_type).unwrap();
-
-        let (mr, mc) = ghost_piece.midpoint;
-        ghost_piece.block_positions.iter().for_each(|(dr, dc)| {
-            // SET POSITION AND OPACITY
-            let param = graphics::DrawParam::new()
-                .dest(glam::Vec2::new(
-                    x + (mc + dc) as f32 * 32. * scl,
-                    y - (mr + dr) as f32 * 32. * scl
-                ))
-                .scale(glam::Vec2::new(scl, scl))
-                .color(graphics::Color::from_rgba(255, 255, 255, 15));
+                    .color(graphics::Color::from_rgba(255, 255, 255, 15));
 
-            canvas.draw(image, param);
-        });
+                canvas.draw(image, param);
+            });
+        }
 
         //Hold piece
         if let Some(piece_type) = self.held_piece {
@@ -170,7 +200,7 @@ impl Game {
         let (mut x, mut y) = (pos.0 + 556. * scl, pos.1 + 80. * scl);
         if self.gamemode == GameMode::Versus {x += 36. * scl};
         
-        for i in 0..5 {
+        for i in 0..NEXT_QUEUE_SIZE {
             let piece_type = self.piece_queue[i];
             let (x_offset, y_offset) = get_piece_offset(piece_type, scl);
             x += x_offset;
@@ -207,7 +237,7 @@ impl Game {
         
         // Countdown
         if let Some(start) = self.countdown_start {
-            let elapsed = start.elapsed();
+            let elapsed = self.clock.elapsed(start);
             let remaining = self.countdown_duration.checked_sub(elapsed).unwrap_or(Duration::ZERO);
             let seconds_left = remaining.as_secs();
     
@@ -243,11 +273,11 @@ impl Game {
 
         // Line marker
         match self.gamemode {
-            GameMode::FourtyLines |
+            GameMode::Sprint |
             GameMode::Marathon => {
                 let mut lines_left = -(self.lines as isize);
                 if self.gamemode == GameMode::Marathon {lines_left += 150}
-                else {lines_left += 40}
+                else {lines_left += self.line_goal as isize}
                 
                 if lines_left <= 20 && lines_left > 0 {
                     let y_offset = 632. - (lines_left as f32 * 32.);
@@ -259,6 +289,16 @@ impl Game {
                     );
                 }
             }
+            // Highest row a perfect clear may use
+            GameMode::PcTraining => {
+                let y_offset = 632. - (PC_TRAINING_HEIGHT as f32 * 32.);
+                canvas.draw(
+                    assets.get("line_marker").unwrap(),
+                    graphics::DrawParam::new()
+                        .dest(glam::Vec2::new(x, y + y_offset * scl))
+                        .scale(glam::Vec2::new(scl, scl))
+                );
+            }
             _ => ()
         }
 
@@ -292,8 +332,8 @@ impl Game {
             GameMode::Marathon => {
                 self.render_marathon_stats(canvas, pos, scl);
             }
-            GameMode::FourtyLines => {
-                self.render_40l_stats(canvas, pos, scl);
+            GameMode::Sprint => {
+                self.render_sprint_stats(canvas, pos, scl);
             }
             GameMode::Survival => {
                 self.render_survival_stats(canvas, pos, scl);
@@ -301,6 +341,18 @@ impl Game {
             GameMode::Versus => {
                 self.render_vs_stats(canvas, pos, scl);
             }
+            GameMode::PcTraining => {
+                self.render_pc_training_stats(canvas, pos, scl);
+            }
+            GameMode::Ultra => {
+                self.render_ultra_stats(canvas, pos, scl);
+            }
+            GameMode::Dig => {
+                self.render_dig_stats(canvas, pos, scl);
+            }
+            GameMode::Master => {
+                self.render_master_stats(canvas, pos, scl);
+            }
         }
 
         self
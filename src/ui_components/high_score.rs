@@ -2,7 +2,7 @@ use ggez::{glam, graphics::{self, Canvas, Color, Image, PxScale, Text, TextFragm
 use std::{collections::HashMap, time::Duration};
 
 use crate::AppState;
-use crate::consts::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::consts::{HIGHSCORE_TABLES, WINDOW_HEIGHT, WINDOW_WIDTH};
 
 use super::stat_formatting::{get_formatted_score, get_formatted_time};
 
@@ -34,8 +34,9 @@ pub fn render_high_score(state: &AppState ,assets: &HashMap<String, Image>, canv
             .scale(glam::Vec2::new(scl, scl))
     );
 
-    let titles = ["SURVIVAL", "MARATHON", "40L"];
-    if animation_state.selected_item_high_score.0 == 0 || animation_state.selected_item_high_score.0 == 2 {
+    let selected = animation_state.selected_item_high_score.0 as usize;
+    let timed = HIGHSCORE_TABLES[selected].2;
+    if timed {
 
         let labels = Text::new(TextFragment{
             text: "NAME      TIME ".to_string(),
@@ -66,12 +67,12 @@ pub fn render_high_score(state: &AppState ,assets: &HashMap<String, Image>, canv
         );
     }
 
+    // The selected table in the middle, with its neighbours on each side
     for i in 0..3 {
+        let table = (selected + HIGHSCORE_TABLES.len() + i - 1) % HIGHSCORE_TABLES.len();
+        let mut text = HIGHSCORE_TABLES[table].0.to_string();
 
-        
-        let mut text = titles[i].to_string(); 
-
-        if animation_state.selected_item_high_score == (i as i32,0){
+        if i == 1 && animation_state.selected_item_high_score.1 == 0 {
             text = format!(">{}", text);
         }
         let gamemode = Text::new(TextFragment{
@@ -93,7 +94,7 @@ pub fn render_high_score(state: &AppState ,assets: &HashMap<String, Image>, canv
 
     for p in 0..5 {
         let mut _text: String = "".to_string();
-        if animation_state.selected_item_high_score.0 == 0 || animation_state.selected_item_high_score.0 == 2{
+        if timed {
             _text = format!("{}. {} : {}",p + 1,scores[p].0,get_formatted_time(Duration::from_millis(scores[p].1 as u64))); 
         }else {
             _text = format!("{}. {} : {}",p + 1,scores[p].0,get_formatted_score(scores[p].1 )); 
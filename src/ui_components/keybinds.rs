@@ -0,0 +1,129 @@
+use ggez::{
+    glam,
+    graphics::{self, Canvas, Color, PxScale, Text, TextAlign, TextFragment, TextLayout},
+};
+
+use crate::config::config_file::KeybindSlot;
+use crate::consts::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::menu_inputs::get_action_name;
+use crate::{AppState, ALL_GAME_ACTIONS};
+
+pub fn render_keybinds(canvas: &mut Canvas, scl: f32, state: &mut AppState) {
+    let assets = &state.menu_assets;
+    let animation_state = &state.animation_state;
+    let center = (WINDOW_WIDTH / 2., WINDOW_HEIGHT / 2.);
+
+    let image = assets.get("empty_box").unwrap();
+    let image_half_size = (image.width() as f32 / 2., image.height() as f32 / 2.);
+
+    canvas.draw(
+        image,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0 - image_half_size.0, center.1 - image_half_size.1))
+            .scale(glam::Vec2::new(scl, scl)),
+    );
+
+    // Title
+    let mut title = Text::new(TextFragment {
+        text: "Keybinds".to_string(),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE),
+        scale: Some(PxScale::from(90.)),
+    });
+    title.set_layout(TextLayout {
+        h_align: TextAlign::Middle,
+        v_align: TextAlign::Begin,
+    });
+    canvas.draw(
+        &title,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0, center.1 - 400.))
+            .scale(glam::Vec2::new(scl, scl)),
+    );
+
+    let selected = animation_state.selected_item_keybinds;
+    let slot = animation_state.keybind_slot;
+    let keybinds = state.config.keybinds.get_map(slot);
+    let buttons = &state.config.keybinds.gamepad[slot.get_player()];
+
+    // Layout, can only be switched for player one on keyboard
+    let switchable = matches!(slot, KeybindSlot::Solo | KeybindSlot::Versus(0));
+    let layout_text = if switchable {format!("< {} >", slot.get_name())} else {slot.get_name()};
+    let mut rows = vec![(layout_text, "KEY".to_string(), "PAD".to_string())];
+
+    for action in ALL_GAME_ACTIONS {
+        let (key, button) = if animation_state.waiting_for_key && rows.len() == selected {
+            ("PRESS A KEY".to_string(), "OR BUTTON".to_string())
+        } else {
+            (
+                keybinds.get(&action).map(|k| format!("{:?}", k).to_uppercase()).unwrap_or("-".to_string()),
+                buttons.get(&action).map(|b| format!("{:?}", b).to_uppercase()).unwrap_or("-".to_string()),
+            )
+        };
+        rows.push((get_action_name(action).to_string(), key, button));
+    }
+    rows.push(("RESTORE DEFAULTS".to_string(), String::new(), String::new()));
+    rows.push(("BACK".to_string(), String::new(), String::new()));
+
+    for (i, (label, key, button)) in rows.iter().enumerate() {
+        let y = center.1 - 270. + i as f32 * 50.;
+        let color = if animation_state.waiting_for_key && i == selected {Color::YELLOW} else {Color::WHITE};
+
+        let label = Text::new(TextFragment {
+            text: if i == selected {format!("> {}", label)} else {label.to_string()},
+            font: Some("Tetris font".to_string()),
+            color: Some(color),
+            scale: Some(PxScale::from(22.)),
+        });
+        canvas.draw(
+            &label,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0 - 440., y))
+                .scale(glam::Vec2::new(scl, scl)),
+        );
+
+        let key = Text::new(TextFragment {
+            text: key.to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(color),
+            scale: Some(PxScale::from(22.)),
+        });
+        canvas.draw(
+            &key,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0 - 20., y))
+                .scale(glam::Vec2::new(scl, scl)),
+        );
+
+        let button = Text::new(TextFragment {
+            text: button.to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(color),
+            scale: Some(PxScale::from(22.)),
+        });
+        canvas.draw(
+            &button,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0 + 220., y))
+                .scale(glam::Vec2::new(scl, scl)),
+        );
+    }
+
+    // Result of the latest rebind
+    let mut message = Text::new(TextFragment {
+        text: animation_state.keybind_message.clone(),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::YELLOW),
+        scale: Some(PxScale::from(20.)),
+    });
+    message.set_layout(TextLayout {
+        h_align: TextAlign::Middle,
+        v_align: TextAlign::Begin,
+    });
+    canvas.draw(
+        &message,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0, center.1 + 300.))
+            .scale(glam::Vec2::new(scl, scl)),
+    );
+}
@@ -41,7 +41,7 @@ pub fn render_main_menu(assets: &HashMap<String, Image>, canvas: &mut Canvas, sc
 
     canvas.draw(&start_game,
         graphics::DrawParam::new()
-            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 - 175.))
+            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 - 225.))
             .scale(glam::Vec2::new(scl, scl))
     );
 
@@ -55,7 +55,21 @@ pub fn render_main_menu(assets: &HashMap<String, Image>, canvas: &mut Canvas, sc
 
     canvas.draw(&highscores,
         graphics::DrawParam::new()
-            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1))
+            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 - 75.))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+
+    let replays = Text::new(TextFragment{
+        text: "REPLAYS".to_string(),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE), 
+        scale: Some(PxScale::from(60.0))
+
+    });
+
+    canvas.draw(&replays,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 + 75.))
             .scale(glam::Vec2::new(scl, scl))
     );
 
@@ -69,7 +83,7 @@ pub fn render_main_menu(assets: &HashMap<String, Image>, canvas: &mut Canvas, sc
 
     canvas.draw(&settings,
         graphics::DrawParam::new()
-            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 + 175.))
+            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 + 225.))
             .scale(glam::Vec2::new(scl, scl))
     );
 
@@ -82,9 +96,10 @@ pub fn render_main_menu(assets: &HashMap<String, Image>, canvas: &mut Canvas, sc
     });
 
     let arrow_y = match animation_state.selected_item_main_menu {
-        0 => center.1 - 175., 
-        1 => center.1,
-        _ => center.1 + 175.
+        0 => center.1 - 225., 
+        1 => center.1 - 75.,
+        2 => center.1 + 75.,
+        _ => center.1 + 225.
     }; 
 
     canvas.draw(&arrow,
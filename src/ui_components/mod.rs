@@ -3,6 +3,7 @@ pub mod game_ui;
 pub mod gamemode_selector;
 pub mod singleplayer_selector;
 pub mod marathon_prompt;
+pub mod option_prompt;
 pub mod reset_screen;
 pub mod main_menu;
 pub mod start_screen;
@@ -11,4 +12,8 @@ pub mod versus_rematch;
 pub mod high_score;
 pub mod input_name;
 pub mod settings;
-pub mod stat_formatting;
\ No newline at end of file
+pub mod keybinds;
+pub mod stat_formatting;
+pub mod replay_browser;
+pub mod replay_viewer;
+pub mod coach_overlay;
@@ -0,0 +1,154 @@
+use ggez::{glam, graphics::{self, Canvas, Color, Image, PxScale, Text, TextAlign, TextFragment, TextLayout}};
+use std::collections::HashMap;
+
+use crate::consts::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// Like the marathon prompt, for modes with a few settings. `settings` are (label, value), `selected` is the row of the arrow
+pub fn render_option_prompt(assets: &HashMap<String, Image>, canvas: &mut Canvas, scl: f32, title: &str, settings: &[(&str, String)], selected: usize){
+    let center = (WINDOW_WIDTH / 2., WINDOW_HEIGHT / 2.);
+
+    let image = assets.get("empty_box").unwrap();
+    let image_half_size = (image.width() as f32 / 2., image.height() as f32 / 2.);
+
+    canvas.draw(
+        image,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0 - image_half_size.0,center.1 - image_half_size.1))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+
+    // Title
+    let mut title = Text::new(TextFragment{
+        text: title.to_string(),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE), 
+        scale: Some(PxScale::from(90.))
+    });
+    title.set_layout(TextLayout {
+        h_align: TextAlign::Middle,
+        v_align: TextAlign::Begin
+    });
+    canvas.draw(&title,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0, center.1 - 400.))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+
+    // Settings, the last one right above confirm
+    for (i, (label, value)) in settings.iter().enumerate() {
+        let value_y = center.1 - (settings.len() - 1 - i) as f32 * 190.;
+
+        let mut label = Text::new(TextFragment{
+            text: label.to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE), 
+            scale: Some(PxScale::from(60.))
+        });
+        label.set_layout(TextLayout {
+            h_align: TextAlign::Middle,
+            v_align: TextAlign::Begin
+        });
+        canvas.draw(&label,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0, value_y - 100.))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+
+        let mut value_text = Text::new(TextFragment{
+            text: value.clone(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE), 
+            scale: Some(PxScale::from(60.))
+        });
+        value_text.set_layout(TextLayout {
+            h_align: TextAlign::Middle,
+            v_align: TextAlign::Begin
+        });
+        canvas.draw(&value_text,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0, value_y))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+
+        // Arrows
+        if selected == i {
+            // The font is monospaced, so the arrows just need a space more than the value on each side
+            let mut arrows = Text::new(TextFragment{
+                text: format!("<{}>", " ".repeat(value.len() + 2)),
+                font: Some("Tetris font".to_string()),
+                color: Some(Color::WHITE), 
+                scale: Some(PxScale::from(60.))
+            });
+            arrows.set_layout(TextLayout {
+                h_align: TextAlign::Middle,
+                v_align: TextAlign::Begin
+            });
+            canvas.draw(&arrows,
+                graphics::DrawParam::new()
+                    .dest(glam::Vec2::new(center.0, value_y))
+                    .scale(glam::Vec2::new(scl, scl))
+            );
+        }
+    }
+
+    // Confirm
+    let mut confirm = Text::new(TextFragment{
+        text: "CONFIRM".to_string(),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE), 
+        scale: Some(PxScale::from(60.))
+    });
+    confirm.set_layout(TextLayout {
+        h_align: TextAlign::Middle,
+        v_align: TextAlign::Begin
+    });
+    canvas.draw(&confirm,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0, center.1 + 200.))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+
+    // Back
+    let mut back = Text::new(TextFragment{
+        text: "BACK".to_string(),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE), 
+        scale: Some(PxScale::from(60.))
+    });
+    back.set_layout(TextLayout {
+        h_align: TextAlign::Middle,
+        v_align: TextAlign::Begin
+    });
+    canvas.draw(&back,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0, center.1 + 300.))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+
+    // Arrow
+    if selected == settings.len() {
+        let arrow = Text::new(TextFragment{
+            text: ">".to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE), 
+            scale: Some(PxScale::from(60.))
+        });
+        canvas.draw(&arrow,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0 - 300., center.1 + 200.))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+    } else if selected == settings.len() + 1 {
+        let arrow = Text::new(TextFragment{
+            text: ">".to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE), 
+            scale: Some(PxScale::from(60.))
+        });
+        canvas.draw(&arrow,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0 - 220., center.1 + 300.))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+    }
+}
\ No newline at end of file
@@ -0,0 +1,108 @@
+use ggez::{glam, graphics::{self, Canvas, Color, Image, PxScale, Text, TextFragment}};
+use std::collections::HashMap;
+
+use crate::animation_state::AnimationState;
+use crate::consts::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+const VISIBLE_REPLAYS: usize = 7;
+
+pub fn render_replay_browser(assets: &HashMap<String, Image>, canvas: &mut Canvas, scl: f32, animation_state: &mut AnimationState){
+    let center = (WINDOW_WIDTH / 2., WINDOW_HEIGHT / 2.);
+
+    let image = assets.get("empty_box").unwrap();
+    let image_half_size = (image.width() as f32 / 2., image.height() as f32 / 2.);
+
+    canvas.draw(
+        image,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0 - image_half_size.0,center.1 - image_half_size.1))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+
+    let title = Text::new(TextFragment{
+        text: "REPLAYS".to_string(),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE),
+        scale: Some(PxScale::from(90.0))
+
+    });
+
+    canvas.draw(&title,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 - 400.))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+
+    let replays = &animation_state.replay_list;
+    let selected = animation_state.selected_item_replay_browser;
+
+    if replays.is_empty() {
+        let empty = Text::new(TextFragment{
+            text: "NO REPLAYS YET".to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(30.0))
+
+        });
+
+        canvas.draw(&empty,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 - 250.))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+    }
+
+    // Scroll so the selected replay is always visible
+    let first = selected.min(replays.len()).saturating_sub(VISIBLE_REPLAYS - 1);
+
+    for (i, (_, label)) in replays.iter().enumerate().skip(first).take(VISIBLE_REPLAYS) {
+        let mut text = format!("{}. {}", i + 1, label);
+        if i == selected {
+            text = format!(">{}", text);
+        }
+
+        let replay = Text::new(TextFragment{
+            text,
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(30.0))
+
+        });
+
+        canvas.draw(&replay,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0 - image_half_size.0 + 170., center.1 - 250. + 60. * (i - first) as f32))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+    }
+
+    let back = Text::new(TextFragment{
+        text: "Back".to_string(),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE),
+        scale: Some(PxScale::from(60.0))
+
+    });
+
+    canvas.draw(&back,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 + 225.))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+
+    if selected == replays.len() {
+        let arrow = Text::new(TextFragment{
+            text: ">".to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(60.0))
+
+        });
+
+        canvas.draw(&arrow,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0 - image_half_size.0 + 100., center.1 + 225.))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use ggez::{glam, graphics::{self, Canvas, Color, PxScale, Text, TextFragment}};
+
+use crate::clock::TICK;
+use crate::replay::PLAYBACK_SPEEDS;
+use crate::AppState;
+
+use super::stat_formatting::get_formatted_time;
+
+pub fn render_replay_viewer(state: &mut AppState, canvas: &mut Canvas, scl: f32){
+    let Some(player) = &mut state.replay_player else {
+        return;
+    };
+
+    for game in player.games.iter_mut() {
+        game.render_board(&state.board_assets, canvas)
+            .render_pieces(&state.piece_assets, canvas)
+            .render_stats(canvas)
+            .render_misc(&state.misc_assets, canvas);
+    }
+
+    let position = TICK.mul_f64(player.position);
+    let length = TICK * player.replay.length as u32;
+    let status = if player.is_finished() {
+        "END"
+    } else if player.paused {
+        "PAUSED"
+    } else {
+        ""
+    };
+
+    let info = Text::new(TextFragment{
+        text: format!(
+            "REPLAY {} / {}  {}x  {}",
+            get_formatted_time(position),
+            get_formatted_time(length),
+            PLAYBACK_SPEEDS[player.speed_index],
+            status,
+        ),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE),
+        scale: Some(PxScale::from(30.0))
+
+    });
+
+    canvas.draw(&info,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(40., 40.))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+
+    let controls = Text::new(TextFragment{
+        text: "PAUSE: SELECT  SEEK: LEFT/RIGHT  SPEED: UP/DOWN  EXIT: BACK".to_string(),
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE),
+        scale: Some(PxScale::from(20.0))
+
+    });
+
+    canvas.draw(&controls,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(40., 1030.))
+            .scale(glam::Vec2::new(scl, scl))
+    );
+}
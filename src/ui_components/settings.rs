@@ -250,6 +250,47 @@ pub fn render_settings(canvas: &mut Canvas, scl: f32, state: &mut AppState) {
         }
     }
 
+    // Keybinds, one for each player
+    for (player, x) in [(0, center.0 - 230.), (1, center.0 + 230.)] {
+        let selected = state.animation_state.selected_item_settings == (player, 3);
+
+        let mut keybinds = Text::new(TextFragment {
+            text: if selected {"> KEYBINDS".to_string()} else {"KEYBINDS".to_string()},
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(40.)),
+        });
+        keybinds.set_layout(TextLayout {
+            h_align: TextAlign::Middle,
+            v_align: TextAlign::Begin,
+        });
+        canvas.draw(
+            &keybinds,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(x, center.1 + 215.))
+                .scale(glam::Vec2::new(scl, scl)),
+        );
+    }
+
+    // Randomizer, shared by both players
+    let randomizer = format!("RANDOMIZER: {}", state.game_one.randomizer_type.get_name());
+    let mut randomizer = Text::new(TextFragment {
+        text: if state.animation_state.selected_item_settings.1 == 4 {format!("< {} >", randomizer)} else {randomizer},
+        font: Some("Tetris font".to_string()),
+        color: Some(Color::WHITE),
+        scale: Some(PxScale::from(40.)),
+    });
+    randomizer.set_layout(TextLayout {
+        h_align: TextAlign::Middle,
+        v_align: TextAlign::Begin,
+    });
+    canvas.draw(
+        &randomizer,
+        graphics::DrawParam::new()
+            .dest(glam::Vec2::new(center.0, center.1 + 280.))
+            .scale(glam::Vec2::new(scl, scl)),
+    );
+
     // Confirm
     let mut confirm = Text::new(TextFragment {
         text: "CONFIRM".to_string(),
@@ -264,11 +305,11 @@ pub fn render_settings(canvas: &mut Canvas, scl: f32, state: &mut AppState) {
     canvas.draw(
         &confirm,
         graphics::DrawParam::new()
-            .dest(glam::Vec2::new(center.0, center.1 + 300.))
+            .dest(glam::Vec2::new(center.0, center.1 + 345.))
             .scale(glam::Vec2::new(scl, scl)),
     );
 
-    if state.animation_state.selected_item_settings.1 == 3 {
+    if state.animation_state.selected_item_settings.1 == 5 {
         let arrow = Text::new(TextFragment {
             text: ">".to_string(),
             font: Some("Tetris font".to_string()),
@@ -278,7 +319,7 @@ pub fn render_settings(canvas: &mut Canvas, scl: f32, state: &mut AppState) {
         canvas.draw(
             &arrow,
             graphics::DrawParam::new()
-                .dest(glam::Vec2::new(center.0 - 300., center.1 + 300.))
+                .dest(glam::Vec2::new(center.0 - 300., center.1 + 345.))
                 .scale(glam::Vec2::new(scl, scl)),
         );
     }
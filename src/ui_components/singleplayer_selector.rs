@@ -31,61 +31,22 @@ pub fn render_gamemode_selector(assets: &HashMap<String, Image>, canvas: &mut Ca
             .scale(glam::Vec2::new(scl, scl))
     );
 
-    let marathon = Text::new(TextFragment{
-        text: "Marathon".to_string(),
-        font: Some("Tetris font".to_string()),
-        color: Some(Color::WHITE), 
-        scale: Some(PxScale::from(60.0))
-
-    });
-
-    canvas.draw(&marathon,
-        graphics::DrawParam::new()
-            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 - 225.))
-            .scale(glam::Vec2::new(scl, scl))
-    );
-
-    let fourty_lines = Text::new(TextFragment{
-        text: "40 Lines".to_string(),
-        font: Some("Tetris font".to_string()),
-        color: Some(Color::WHITE), 
-        scale: Some(PxScale::from(60.0))
-
-    });
-
-    canvas.draw(&fourty_lines,
-        graphics::DrawParam::new()
-            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 - 75.))
-            .scale(glam::Vec2::new(scl, scl))
-    );
-
-    let survival = Text::new(TextFragment{
-        text: "Survival".to_string(),
-        font: Some("Tetris font".to_string()),
-        color: Some(Color::WHITE), 
-        scale: Some(PxScale::from(60.0))
-
-    });
-
-    canvas.draw(&survival,
-        graphics::DrawParam::new()
-            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 + 75.))
-            .scale(glam::Vec2::new(scl, scl))
-    );
-
-    let back = Text::new(TextFragment{
-        text: "Back".to_string(),
-        font: Some("Tetris font".to_string()),
-        color: Some(Color::WHITE), 
-        scale: Some(PxScale::from(60.0))
-
-    });
-
-    canvas.draw(&back,
-        graphics::DrawParam::new()
-            .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 + 225.))
-            .scale(glam::Vec2::new(scl, scl))
-    );
+    let items = ["Marathon", "Sprint", "Survival", "Ultra", "Dig", "Master", "PC Training", "Back"];
+    for (i, item) in items.iter().enumerate() {
+        let text = Text::new(TextFragment{
+            text: item.to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE), 
+            scale: Some(PxScale::from(60.0))
+
+        });
+
+        canvas.draw(&text,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(center.0 - image_half_size.0 + 200., center.1 - 290. + i as f32 * 80.))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+    }
 
     
     let arrow = Text::new(TextFragment{
@@ -96,12 +57,7 @@ pub fn render_gamemode_selector(assets: &HashMap<String, Image>, canvas: &mut Ca
 
     });
 
-    let arrow_y = match animation_state.selected_item_singleplayer_selector {
-        0 => {center.1 - 225.},
-        1 => {center.1 - 75.},
-        2 => {center.1 + 75.},
-        _ => {center.1 + 225.}
-    };
+    let arrow_y = center.1 - 290. + animation_state.selected_item_singleplayer_selector as f32 * 80.;
 
     canvas.draw(&arrow,
         graphics::DrawParam::new()
@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use ggez::{glam, graphics::{self, Canvas, Color, PxScale, Text, TextAlign, TextFragment, TextLayout}};
+
 pub fn get_formatted_time(duration: Duration) -> String {
     let mut secs = duration.as_secs();
     let mins = secs / 60;
@@ -33,4 +35,41 @@ pub fn get_formatted_score(score: usize) -> String {
         return format!("{},{:0>3}", kilo, s);
     }
     return format!("{},{:0>3},{:0>3}", mega, kilo, s);
-}
\ No newline at end of file
+}
+
+// Draws a small label with its value below it for every (label, value, y) stat, right aligned next to the board
+pub fn draw_stat_column(canvas: &mut Canvas, pos: (f32, f32), scl: f32, stats: &[(&str, String, f32)]) {
+    for (label, value, y) in stats {
+        let mut label = Text::new(TextFragment{
+            text: label.to_string(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(16.))
+        });
+        label.set_layout(TextLayout{
+            h_align: TextAlign::End,
+            v_align: TextAlign::Middle
+        });
+        canvas.draw(&label,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(pos.0 + 156. * scl, pos.1 + y * scl))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+
+        let mut value = Text::new(TextFragment{
+            text: value.clone(),
+            font: Some("Tetris font".to_string()),
+            color: Some(Color::WHITE),
+            scale: Some(PxScale::from(24.))
+        });
+        value.set_layout(TextLayout{
+            h_align: TextAlign::End,
+            v_align: TextAlign::Middle
+        });
+        canvas.draw(&value,
+            graphics::DrawParam::new()
+                .dest(glam::Vec2::new(pos.0 + 156. * scl, pos.1 + (y + 32.) * scl))
+                .scale(glam::Vec2::new(scl, scl))
+        );
+    }
+}